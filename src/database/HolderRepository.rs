@@ -0,0 +1,76 @@
+use crate::{database::MongoConnection::MongoConnection, models::Holder::{Holder, HolderStatus}};
+
+use futures::TryStreamExt;
+use mongodb::{bson::{doc, oid::ObjectId, to_bson}, error::Error, options::IndexOptions, results::UpdateResult, Collection, Database, IndexModel};
+
+pub struct HolderRepository {
+    col: Collection<Holder>,
+}
+
+impl HolderRepository {
+    pub async fn init(db: &Database) -> Self {
+        let col: Collection<Holder> = db.collection("Holders");
+        // No two holders ever share an x-coordinate, not even after one of them retired
+        let index = IndexModel::builder()
+            .keys(doc!{ "x": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build();
+        col.create_index(index, None).await.expect("Error creating the holder x-coordinate index");
+        HolderRepository { col }
+    }
+
+    /// Registers `new_holder` at the lowest x-coordinate no holder was ever given and returns it with its id and x.
+    /// Two registrations racing for the same x are told apart by the unique index, and the one that lost takes the
+    /// next free x. `None` once every x-coordinate is taken.
+    pub async fn create_holder(&self, mut new_holder: Holder) -> Result<Option<Holder>, Error> {
+        loop {
+            let taken: Vec<u8> = self.find_holders().await?.iter().map(|holder| holder.x).collect();
+            new_holder.x = match (1..=u8::MAX).find(|x| !taken.contains(x)) {
+                Some(x) => x,
+                None => return Ok(None),
+            };
+            match self.col.insert_one(&new_holder, None).await {
+                Err(err) if MongoConnection::isDuplicateKey(&err) => continue,
+                result => {
+                    new_holder.id = result?.inserted_id.as_object_id();
+                    return Ok(Some(new_holder));
+                }
+            }
+        }
+    }
+
+    pub async fn find_holder(&self, id: ObjectId) -> Result<Option<Holder>, Error> {
+        let holder = self
+            .col
+            .find_one(doc!{ "_id": id }, None)
+            .await?;
+        Ok(holder)
+    }
+
+    pub async fn find_holders(&self) -> Result<Vec<Holder>, Error> {
+        let holders = self
+            .col
+            .find(doc!{}, None)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(holders)
+    }
+
+    /// The holder at `x`, retired or not. It is the only one that was ever given `x`.
+    pub async fn find_holder_by_x(&self, x: u8) -> Result<Option<Holder>, Error> {
+        let holder = self
+            .col
+            .find_one(doc!{ "x": x as i32 }, None)
+            .await?;
+        Ok(holder)
+    }
+
+    pub async fn update_status(&self, id: ObjectId, status: HolderStatus) -> Result<UpdateResult, Error> {
+        let update = self
+            .col
+            .update_one(doc!{ "_id": id }, doc!{ "$set": { "status": to_bson(&status).unwrap() } }, None)
+            .await?;
+        Ok(update)
+    }
+}
\ No newline at end of file
@@ -0,0 +1,37 @@
+extern crate dotenv;
+
+use std::env;
+use dotenv::dotenv;
+use mongodb::{error::{Error, ErrorKind, WriteFailure}, Client, Database};
+
+/// Error code of a write that violates a unique index.
+const DUPLICATE_KEY: i32 = 11000;
+
+/// The one Mongo client of the process. The driver pools connections behind it, so every repository works on the
+/// `Database` it returns instead of opening a client of its own.
+pub struct MongoConnection;
+
+impl MongoConnection {
+    fn get_connection_string() -> String {
+        let host = env::var("MONGO_HOST").expect("MONGO_HOST env not set.");
+        let port = env::var("MONGO_PORT").expect("MONGO_PORT env not set."); 
+        let user = env::var("MONGO_USER").expect("MONGO_USER env not set.");
+        let pass = env::var("MONGO_PASS").expect("MONGO_PASS env not set.");
+        "mongodb://".to_owned() + &user + ":" + &pass + "@" + &host + ":" + &port
+    }
+
+    pub async fn client() -> Client {
+        dotenv().ok();
+        let uri = Self::get_connection_string();
+        Client::with_uri_str(uri).await.unwrap()
+    }
+
+    pub async fn init() -> Database {
+        Self::client().await.database("RustDB")
+    }
+
+    /// Whether a write failed because it would duplicate a unique key.
+    pub fn isDuplicateKey(err: &Error) -> bool {
+        matches!(err.kind.as_ref(), ErrorKind::Write(WriteFailure::WriteError(failure)) if failure.code == DUPLICATE_KEY)
+    }
+}
\ No newline at end of file
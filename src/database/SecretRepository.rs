@@ -1,41 +1,31 @@
-extern crate dotenv;
+use crate::models::{PartialSecret::PartialSecret, SecretCommitment::SecretCommitment};
 
-use crate::models::PartialSecret::PartialSecret;
+use futures::TryStreamExt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use std::env;
-use dotenv::dotenv;
-use mongodb::{bson::{extjson::de::Error, Bson}, results::{InsertOneResult}, Client, Collection};
+use mongodb::{bson::{doc, oid::ObjectId, Bson}, error::Error, results::{DeleteResult, InsertOneResult, UpdateResult}, Collection, Database};
+
+/// How long a refresh or resharing may hold the update of a key before another one can take it over.
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(600);
 
 pub struct SecretRepository {
     col: Collection<PartialSecret>,
+    commitments: Collection<SecretCommitment>,
 }
 
 impl SecretRepository {
-    fn get_connection_string() -> String {
-        let host = env::var("MONGO_HOST").expect("MONGO_HOST env not set.");
-        let port = env::var("MONGO_PORT").expect("MONGO_PORT env not set."); 
-        let user = env::var("MONGO_USER").expect("MONGO_USER env not set.");
-        let pass = env::var("MONGO_PASS").expect("MONGO_PASS env not set.");
-        "mongodb://".to_owned() + &user + ":" + &pass + "@" + &host + ":" + &port
-    }
-
-    pub async fn init() -> Self {
-        dotenv().ok();
-        let uri = Self::get_connection_string();
-        let client = Client::with_uri_str(uri).await.unwrap();
-        let db = client.database("RustDB");
+    pub async fn init(db: &Database) -> Self {
         let col: Collection<PartialSecret> = db.collection("PartialSecrets");
-        SecretRepository { col }
+        let commitments: Collection<SecretCommitment> = db.collection("SecretCommitments");
+        SecretRepository { col, commitments }
     }
 
     pub async fn save_muliple_secret(&self, new_secrets: Vec<PartialSecret>) -> Result<Vec<Bson>, Error> {
         let insertions = self
             .col
             .insert_many(new_secrets, None)
-            .await
-            .ok()
-            .expect("Error saving partial secrets");
-        let x: Vec<Bson> = insertions.inserted_ids.into_iter().map(|(_x, y)| y).collect();
+            .await?;
+        let x: Vec<Bson> = insertions.inserted_ids.into_values().collect();
         Ok(x)
     }
 
@@ -43,9 +33,213 @@ impl SecretRepository {
         let user = self
             .col
             .insert_one(new_secret, None)
-            .await
-            .ok()
-            .expect("Error saving partial secret");
+            .await?;
         Ok(user)
     }
+
+    pub async fn save_commitments(&self, new_commitments: SecretCommitment) -> Result<InsertOneResult, Error> {
+        let commitments = self
+            .commitments
+            .insert_one(new_commitments, None)
+            .await?;
+        Ok(commitments)
+    }
+
+    pub async fn find_commitments(&self, public_key: &str) -> Result<Option<SecretCommitment>, Error> {
+        let commitments = self
+            .commitments
+            .find_one(doc!{ "public_key": public_key }, None)
+            .await?;
+        Ok(commitments)
+    }
+
+    /// Returns the shares of `public_key` the stored commitments verify: the active generation and epoch, not revoked.
+    /// Shares staged by a refresh or resharing that has not switched the commitments yet are left out.
+    pub async fn find_secrets(&self, public_key: &str) -> Result<Vec<PartialSecret>, Error> {
+        let mut filter = doc!{ "public_key": public_key, "revoked": { "$ne": true } };
+        if let Some(commitments) = self.find_commitments(public_key).await? {
+            filter.insert("generation", counter(commitments.generation));
+            filter.insert("epoch", counter(commitments.epoch));
+        }
+        let secrets = self
+            .col
+            .find(filter, None)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(secrets)
+    }
+
+    /// Returns every share still stored in the pre-envelope `x||y` or `x||y||r` form, revoked ones included.
+    pub async fn find_legacy_secrets(&self) -> Result<Vec<PartialSecret>, Error> {
+        let secrets = self
+            .col
+            .find(doc!{ "partial_secret": { "$regex": "\\|\\|" } }, None)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(secrets)
+    }
+
+    /// Rewrites the encoding of a share without touching its epoch.
+    pub async fn replace_partial_secret(&self, id: ObjectId, partial_secret: String) -> Result<UpdateResult, Error> {
+        let update = self
+            .col
+            .update_one(doc!{ "_id": id }, doc!{ "$set": { "partial_secret": partial_secret } }, None)
+            .await?;
+        Ok(update)
+    }
+
+    /// Returns the current shares of every key of `user_id`, in the order they were dealt.
+    pub async fn find_user_secrets(&self, user_id: ObjectId) -> Result<Vec<PartialSecret>, Error> {
+        let secrets = self
+            .col
+            .find(doc!{ "user_id": user_id, "revoked": { "$ne": true } }, None)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(secrets)
+    }
+
+    /// Returns the current share of `public_key` sealed to `holder_key`.
+    pub async fn find_sealed_secret(&self, public_key: &str, holder_key: &str) -> Result<Option<PartialSecret>, Error> {
+        let secret = self
+            .col
+            .find_one(doc!{ "public_key": public_key, "encrypted_to": holder_key, "revoked": { "$ne": true } }, None)
+            .await?;
+        Ok(secret)
+    }
+
+    /// Returns every share assigned to the holder, revoked ones included, for a custody audit.
+    pub async fn find_holder_secrets(&self, holder_id: ObjectId) -> Result<Vec<PartialSecret>, Error> {
+        let secrets = self
+            .col
+            .find(doc!{ "holder_id": holder_id }, None)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(secrets)
+    }
+
+    /// Replaces a plaintext share with its ciphertext for `holder_key`, leaving a share that is already sealed alone.
+    pub async fn seal_secret(&self, id: ObjectId, ciphertext: String, holder_key: &str) -> Result<UpdateResult, Error> {
+        let update = self
+            .col
+            .update_one(doc!{ "_id": id, "encrypted_to": null }, doc!{ "$set": { "partial_secret": ciphertext, "encrypted_to": holder_key } }, None)
+            .await?;
+        Ok(update)
+    }
+
+    pub async fn revoke_secrets(&self, ids: Vec<ObjectId>) -> Result<UpdateResult, Error> {
+        let update = self
+            .col
+            .update_many(doc!{ "_id": { "$in": ids } }, doc!{ "$set": { "revoked": true } }, None)
+            .await?;
+        Ok(update)
+    }
+
+    /// Claims the next refresh or resharing of `public_key`, returns the token to stage and switch with, or `None` if the
+    /// key is no longer at `generation` and `epoch` or another update holds it. A claim older than `UPDATE_TIMEOUT` is
+    /// considered abandoned and taken over.
+    pub async fn begin_update(&self, public_key: &str, generation: u32, epoch: u32) -> Result<Option<ObjectId>, Error> {
+        let token = ObjectId::new();
+        let abandoned = SystemTime::now().checked_sub(UPDATE_TIMEOUT).unwrap_or(UNIX_EPOCH).duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as u32;
+        let mut oldest = [0u8; 12];
+        oldest[..4].copy_from_slice(&abandoned.to_be_bytes());
+        let update = self
+            .commitments
+            .update_one(
+                doc!{
+                    "public_key": public_key,
+                    "generation": counter(generation),
+                    "epoch": counter(epoch),
+                    "$or": [{ "pending": null }, { "pending": { "$lt": ObjectId::from_bytes(oldest) } }]
+                },
+                doc!{ "$set": { "pending": token } },
+                None
+            )
+            .await?;
+        Ok(if update.matched_count == 1 { Some(token) } else { None })
+    }
+
+    /// Deletes the shares an abandoned refresh or resharing staged past the active `generation` and `epoch`.
+    pub async fn discard_staged_secrets(&self, public_key: &str, generation: u32, epoch: u32) -> Result<DeleteResult, Error> {
+        let deletion = self
+            .col
+            .delete_many(
+                doc!{ "public_key": public_key, "$or": [{ "generation": { "$gt": generation } }, { "generation": generation, "epoch": { "$gt": epoch } }] },
+                None
+            )
+            .await?;
+        Ok(deletion)
+    }
+
+    /// Switches `public_key` to the staged shares of `generation` and `epoch` in one update of its commitments, from then
+    /// on `find_secrets` returns them instead of the previous ones. Returns `None` if the claim `token` was lost.
+    pub async fn activate_commitments(&self, public_key: &str, token: ObjectId, commitments: Vec<String>, secret_degree: u8, generation: u32, epoch: u32) -> Result<Option<UpdateResult>, Error> {
+        let update = self
+            .commitments
+            .update_one(
+                doc!{ "public_key": public_key, "pending": token },
+                doc!{
+                    "$set": { "commitments": commitments, "secret_degree": secret_degree as i32, "epoch": epoch, "generation": generation },
+                    "$unset": { "pending": "" }
+                },
+                None
+            )
+            .await?;
+        Ok(if update.matched_count == 1 { Some(update) } else { None })
+    }
+
+    /// Releases the claim `token` on `public_key` without switching, after staging failed.
+    pub async fn abandon_update(&self, public_key: &str, token: ObjectId) -> Result<UpdateResult, Error> {
+        let update = self
+            .commitments
+            .update_one(doc!{ "public_key": public_key, "pending": token }, doc!{ "$unset": { "pending": "" } }, None)
+            .await?;
+        Ok(update)
+    }
+
+    /// Deletes the shares of a refreshed-away epoch, which must not outlive the refresh.
+    pub async fn delete_secrets(&self, ids: Vec<ObjectId>) -> Result<DeleteResult, Error> {
+        let deletion = self
+            .col
+            .delete_many(doc!{ "_id": { "$in": ids } }, None)
+            .await?;
+        Ok(deletion)
+    }
+
+    /// Deletes the shares and commitments of `public_key`, to roll back a key whose dealing failed elsewhere.
+    pub async fn delete_key(&self, public_key: &str) -> Result<DeleteResult, Error> {
+        self
+            .commitments
+            .delete_many(doc!{ "public_key": public_key }, None)
+            .await?;
+        let deletion = self
+            .col
+            .delete_many(doc!{ "public_key": public_key }, None)
+            .await?;
+        Ok(deletion)
+    }
+
+    /// Deletes the shares and commitments of every key of `user_id`, to roll back a user whose creation failed.
+    pub async fn delete_user_keys(&self, user_id: ObjectId) -> Result<DeleteResult, Error> {
+        self
+            .commitments
+            .delete_many(doc!{ "user_id": user_id }, None)
+            .await?;
+        let deletion = self
+            .col
+            .delete_many(doc!{ "user_id": user_id }, None)
+            .await?;
+        Ok(deletion)
+    }
+}
+
+/// Filter on a generation or epoch counter, documents written before the counter existed count as 0.
+fn counter(value: u32) -> Bson {
+    match value {
+        0 => Bson::Document(doc!{ "$in": [0, null] }),
+        value => Bson::from(value),
+    }
 }
\ No newline at end of file
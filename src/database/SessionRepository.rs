@@ -0,0 +1,50 @@
+use crate::{database::MongoConnection::MongoConnection, util::session::Session};
+
+use futures::TryStreamExt;
+use mongodb::{bson::{doc}, error::Error, results::{InsertOneResult, UpdateResult}, Collection, Database};
+
+pub struct SessionRepository {
+    col: Collection<Session>,
+}
+
+impl SessionRepository {
+    pub async fn init(db: &Database) -> Self {
+        let col: Collection<Session> = db.collection("ProtocolSessions");
+        SessionRepository { col }
+    }
+
+    /// `None` if a session with the same id exists.
+    pub async fn create_session(&self, new_session: Session) -> Result<Option<InsertOneResult>, Error> {
+        match self.col.insert_one(new_session, None).await {
+            Err(err) if MongoConnection::isDuplicateKey(&err) => Ok(None),
+            result => result.map(Some),
+        }
+    }
+
+    pub async fn find_session(&self, session_id: &str) -> Result<Option<Session>, Error> {
+        let session = self
+            .col
+            .find_one(doc!{ "_id": session_id }, None)
+            .await?;
+        Ok(session)
+    }
+
+    pub async fn find_running_sessions(&self) -> Result<Vec<Session>, Error> {
+        let sessions = self
+            .col
+            .find(doc!{ "status": "running" }, None)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(sessions)
+    }
+
+    /// Stores `session` if nobody wrote it since it was read at `version`, i.e. only when the update matched.
+    pub async fn update_session(&self, session: &Session, version: u64) -> Result<UpdateResult, Error> {
+        let update = self
+            .col
+            .replace_one(doc!{ "_id": &session.session_id, "version": version as i64 }, session, None)
+            .await?;
+        Ok(update)
+    }
+}
\ No newline at end of file
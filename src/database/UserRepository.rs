@@ -1,29 +1,13 @@
-extern crate dotenv;
+use crate::models::{User::{User, Wallet}};
 
-use crate::models::{User::User};
-
-use std::env;
-use dotenv::dotenv;
-use mongodb::{bson::{extjson::de::Error, doc, Bson}, Client, Collection};
+use mongodb::{bson::{doc, oid::ObjectId, to_bson, Bson}, error::Error, results::{DeleteResult, UpdateResult}, Collection, Database};
 
 pub struct UserRepository {
     col: Collection<User>,
 }
 
 impl UserRepository {
-    fn get_connection_string() -> String {
-        let host = env::var("MONGO_HOST").expect("MONGO_HOST env not set.");
-        let port = env::var("MONGO_PORT").expect("MONGO_PORT env not set."); 
-        let user = env::var("MONGO_USER").expect("MONGO_USER env not set.");
-        let pass = env::var("MONGO_PASS").expect("MONGO_PASS env not set.");
-        "mongodb://".to_owned() + &user + ":" + &pass + "@" + &host + ":" + &port
-    }
-
-    pub async fn init() -> Self {
-        dotenv().ok();
-        let uri = Self::get_connection_string();
-        let client = Client::with_uri_str(uri).await.unwrap();
-        let db = client.database("RustDB");
+    pub async fn init(db: &Database) -> Self {
         let col: Collection<User> = db.collection("User");
         UserRepository { col }
     }
@@ -35,18 +19,13 @@ impl UserRepository {
             .await
             .ok();
 
-        if !exist.is_none() {
-            let mut exist = exist.unwrap();
-            let tmp = exist.advance().await;
-            if tmp.is_ok() {
-                if tmp.unwrap() == true {
-                    let cursor = exist;
-                    let user = cursor.deserialize_current();
-                    if user.is_ok() {
-                        let user = user.ok().unwrap();
-                        let x = doc!{ "insertedId": user.id.unwrap().to_string() };
-                        return Ok(x.into())
-                    }
+        if let Some(mut exist) = exist {
+            if let Ok(true) = exist.advance().await {
+                let cursor = exist;
+                let user = cursor.deserialize_current();
+                if let Ok(user) = user {
+                    let x = doc!{ "insertedId": user.id.unwrap().to_string() };
+                    return Ok(x.into())
                 }
             }
         }
@@ -54,9 +33,57 @@ impl UserRepository {
         let user = self
             .col
             .insert_one(new_user, None)
-            .await
-            .ok()
-            .expect("Error creating new user");
+            .await?;
         Ok(user.inserted_id)
     }
+
+    pub async fn update_wallet_degree(&self, pub_key: &str, degree: u8) -> Result<UpdateResult, Error> {
+        let update = self
+            .col
+            .update_one(doc!{ "wallets.pub_key": pub_key }, doc!{ "$set": { "wallets.$.degree": degree as i32 } }, None)
+            .await?;
+        Ok(update)
+    }
+
+    pub async fn find_user(&self, id: ObjectId) -> Result<Option<User>, Error> {
+        let user = self
+            .col
+            .find_one(doc!{ "_id": id }, None)
+            .await?;
+        Ok(user)
+    }
+
+    /// The wallet with the public key `pub_key`, whichever user it belongs to.
+    pub async fn find_wallet(&self, pub_key: &str) -> Result<Option<Wallet>, Error> {
+        let user = self
+            .col
+            .find_one(doc!{ "wallets.pub_key": pub_key }, None)
+            .await?;
+        Ok(user.and_then(|user| user.wallets.into_iter().find(|wallet| wallet.pub_key == pub_key)))
+    }
+
+    pub async fn add_wallet(&self, id: ObjectId, wallet: Wallet) -> Result<UpdateResult, Error> {
+        let update = self
+            .col
+            .update_one(doc!{ "_id": id }, doc!{ "$push": { "wallets": to_bson(&wallet).unwrap() } }, None)
+            .await?;
+        Ok(update)
+    }
+
+    pub async fn set_encryption_keys(&self, id: ObjectId, encryption_keys: Vec<String>) -> Result<UpdateResult, Error> {
+        let update = self
+            .col
+            .update_one(doc!{ "_id": id }, doc!{ "$set": { "encryption_keys": encryption_keys } }, None)
+            .await?;
+        Ok(update)
+    }
+
+    /// Deletes a user whose creation failed.
+    pub async fn delete_user(&self, id: ObjectId) -> Result<DeleteResult, Error> {
+        let deletion = self
+            .col
+            .delete_one(doc!{ "_id": id }, None)
+            .await?;
+        Ok(deletion)
+    }
 }
\ No newline at end of file
@@ -1,2 +1,5 @@
 pub mod SecretRepository;
-pub mod UserRepository;
\ No newline at end of file
+pub mod UserRepository;
+pub mod HolderRepository;
+pub mod SessionRepository;
+pub mod MongoConnection;
\ No newline at end of file
@@ -22,15 +22,50 @@ async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
 
     // INITIALIZE DB
+    let mongo = database::MongoConnection::MongoConnection::init().await;
 
     // Secret Repository
-    let secret_repository = database::SecretRepository::SecretRepository::init().await;
+    let secret_repository = database::SecretRepository::SecretRepository::init(&mongo).await;
     let secret_data = Data::new(secret_repository);
 
     // User Repositoty
-    let user_repository = database::UserRepository::UserRepository::init().await;
+    let user_repository = database::UserRepository::UserRepository::init(&mongo).await;
     let user_data = Data::new(user_repository);
 
+    // Holder Repository
+    let holder_repository = database::HolderRepository::HolderRepository::init(&mongo).await;
+    let holder_data = Data::new(holder_repository);
+
+    // CLUSTER IDENTITY
+    let cluster = util::cluster::ClusterConfig::fromEnv();
+    match &cluster {
+        Some(cluster) => log::info!("Running as cluster node x = {} with peers {:?}", cluster.x, cluster.peers.iter().map(|peer| peer.x).collect::<Vec<u8>>()),
+        None => log::info!("Running standalone"),
+    }
+    let cluster_data = Data::new(cluster);
+
+    // PROTOCOL SESSIONS
+    let session_repository = database::SessionRepository::SessionRepository::init(&mongo).await;
+    let (resumed, aborted) = services::SessionService::SessionService::recoverSessions(&session_repository).await.expect("Error recovering protocol sessions");
+    log::info!("Resumed {} protocol sessions, aborted {}", resumed, aborted);
+    let session_data = Data::new(session_repository);
+    let router = match (cluster_data.get_ref(), env::var("NODE_TRANSPORT")) {
+        (Some(cluster), Ok(address)) => {
+            let transport = util::transport::TcpTransport::bind(cluster, &address).await.expect("NODE_TRANSPORT CAN NOT BE BOUND OR NODE_TRANSPORT_KEY IS NOT IN ENV");
+            log::info!("Node transport listening on {}", address);
+            let router = util::session::SessionRouter::new(transport.outbox());
+            let requests = services::ClusterService::ClusterService::requests(&router, cluster).expect("Error joining the requests of the peers");
+            actix_web::rt::spawn(views::Cluster::serve_requests(requests, router.clone(), cluster.clone(), user_data.clone(), secret_data.clone()));
+            actix_web::rt::spawn(services::SessionService::SessionService::run(transport, session_data.clone(), router.clone()));
+            Some(router)
+        }
+        _ => {
+            actix_web::rt::spawn(services::SessionService::SessionService::watch(session_data.clone()));
+            None
+        }
+    };
+    let router_data = Data::new(router);
+
     // INITIALIZE SERVICES
     let wallet_service = services::WalletService::WalletService;
     let wallet_service_data = Data::new(wallet_service);
@@ -42,9 +77,41 @@ async fn main() -> std::io::Result<()> {
             .app_data(wallet_service_data.clone())
             .app_data(secret_data.clone())
             .app_data(user_data.clone())
-            .service(views::SaveSecret::save_secret)
+            .app_data(holder_data.clone())
+            .app_data(cluster_data.clone())
+            .app_data(session_data.clone())
+            .app_data(router_data.clone())
             .service(views::User::create_user)
-            .default_service(web::to(|| not_found()))
+            .service(views::VerifySecret::verify_secret)
+            .service(views::ShareSecret::share_secret)
+            .service(views::RefreshSecret::refresh_secret)
+            .service(views::ReshareSecret::reshare_secret)
+            .service(views::RepairSecret::repair_secret)
+            .service(views::SignHash::sign_hash)
+            .service(views::SignHash::sign_taproot)
+            .service(views::SignHash::sign_ed25519)
+            .service(views::SignHash::sign_bls)
+            .service(views::RecoverSecret::recover_secret)
+            .service(views::SplitBytes::split_bytes)
+            .service(views::SplitBytes::combine_bytes)
+            .service(views::Slip39::slip39_split)
+            .service(views::Slip39::slip39_combine)
+            .service(views::DeriveWallet::derive_wallet)
+            .service(views::DeriveChild::derive_child)
+            .service(views::HolderEnvelope::register_encryption_keys)
+            .service(views::HolderEnvelope::fetch_envelope)
+            .service(views::Holder::create_holder)
+            .service(views::Holder::list_holders)
+            .service(views::Holder::get_holder)
+            .service(views::Holder::update_holder_status)
+            .service(views::Holder::holder_shares)
+            .service(views::Cluster::node_info)
+            .service(views::Session::open_session)
+            .service(views::Session::get_session)
+            .service(views::Session::submit_message)
+            .service(views::Session::abort_session)
+            .service(views::Migration::migrate_share_envelopes)
+            .default_service(web::to(not_found))
     })
         .bind((host, port))?
         .run()
@@ -0,0 +1,79 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Serialize, Deserialize};
+
+use super::PartialSecret::PartialSecret;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HolderStatus {
+    /// Receives shares of new keys.
+    Active,
+    /// Keeps the shares it holds but is not assigned new ones.
+    Suspended,
+    /// Left the committee, its shares should be reshared away.
+    Retired,
+}
+
+/// Someone who keeps shares in custody. Every share dealt to a registered holder sits at the holder's own x-coordinate,
+/// so which holder is responsible for which share can always be told from the share alone.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Holder {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub name: String,
+    /// Free form contact metadata, e.g. an email address.
+    #[serde(default)]
+    pub contact: String,
+    /// Hex X25519 key shares are sealed to for this holder.
+    pub encryption_key: String,
+    /// Hex Ed25519 key the holder signs its messages with.
+    pub signing_key: String,
+    /// x-coordinate of every share the holder receives, assigned once at registration and never to another holder.
+    pub x: u8,
+    pub status: HolderStatus
+}
+
+/// What a custody audit shows of one share: which key it belongs to and where it sits, never its value.
+#[derive(Debug, Serialize)]
+pub struct HeldShare {
+    #[serde(rename = "_id")]
+    pub id: Option<ObjectId>,
+    pub public_key: String,
+    pub x: u8,
+    pub epoch: u32,
+    /// The share is sealed to the holder's encryption key rather than stored in plaintext.
+    pub sealed: bool,
+}
+
+impl HeldShare {
+    /// The audit view of a share of the holder at `x`.
+    pub fn of(secret: &PartialSecret, x: u8) -> Self {
+        HeldShare { id: secret.id, public_key: secret.public_key.clone(), x, epoch: secret.epoch, sealed: secret.encrypted_to.is_some() }
+    }
+}
+
+/// Where one dealt share goes: the holder it is assigned to and the key it is sealed to, either of which may be absent.
+#[derive(Debug, Clone, Default)]
+pub struct ShareCustody {
+    pub holder_id: Option<ObjectId>,
+    pub encryption_key: Option<String>
+}
+
+impl ShareCustody {
+    /// Custody of shares sealed to `encryption_keys` without registered holders.
+    pub fn sealed_to(encryption_keys: &[String]) -> Vec<Self> {
+        encryption_keys.iter().map(|key| ShareCustody { holder_id: None, encryption_key: Some(key.clone()) }).collect()
+    }
+
+    /// Custody of shares assigned to `holders`, sealed to the holders' encryption keys when `seal` is set.
+    pub fn assigned_to(holders: &[Holder], seal: bool) -> Vec<Self> {
+        holders.iter()
+            .map(|holder| ShareCustody { holder_id: holder.id, encryption_key: seal.then(|| holder.encryption_key.clone()) })
+            .collect()
+    }
+
+    /// Custody of plaintext shares dealt to the same holders as `secrets`, in the same order.
+    pub fn same_holders_as(secrets: &[PartialSecret]) -> Vec<Self> {
+        secrets.iter().map(|secret| ShareCustody { holder_id: secret.holder_id, encryption_key: None }).collect()
+    }
+}
\ No newline at end of file
@@ -1,6 +1,8 @@
 use mongodb::bson::oid::ObjectId;
 use serde::{Serialize, Deserialize};
 
+use crate::util::secp256k1;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PartialSecret {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -8,5 +10,29 @@ pub struct PartialSecret {
     pub user_id: ObjectId,
     pub public_key: String,
     pub partial_secret: String,
-    pub secret_degree: u8
+    pub secret_degree: u8,
+    /// Number of proactive refreshes the share went through, shares of different epochs do not combine.
+    #[serde(default)]
+    pub epoch: u32,
+    /// Incremented every time the secret is reshared to a new committee.
+    #[serde(default)]
+    pub generation: u32,
+    /// Set on the previous generation once a resharing completed.
+    #[serde(default)]
+    pub revoked: bool,
+    /// Curve of the key the share belongs to, `secp256k1::CURVE`, `ed25519::CURVE` or `bls::CURVE`.
+    #[serde(default = "default_curve")]
+    pub curve: String,
+    /// Hex X25519 key of the holder the share is sealed to. `partial_secret` then holds the hex HPKE ciphertext of the
+    /// share envelope, which only that holder can open.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted_to: Option<String>,
+    /// Registered holder the share is assigned to, its x-coordinate is the holder's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub holder_id: Option<ObjectId>
+}
+
+/// Documents written before curve tags only held secp256k1 shares.
+pub fn default_curve() -> String {
+    secp256k1::CURVE.to_owned()
 }
\ No newline at end of file
@@ -0,0 +1,28 @@
+use mongodb::bson::oid::ObjectId;
+use serde::{Serialize, Deserialize};
+
+use crate::util::feldman::FeldmanVSS;
+
+/// Commitments to the coefficients of the polynomial a secret was split with, `a_i * G` for Feldman or `a_i * G + b_i * H` for Pedersen.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SecretCommitment {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    pub public_key: String,
+    pub commitments: Vec<String>,
+    /// `FeldmanVSS::SCHEME` or `PedersenVSS::SCHEME`.
+    #[serde(default = "default_scheme")]
+    pub scheme: String,
+    pub secret_degree: u8,
+    /// Matches `PartialSecret.epoch` of the shares these commitments verify.
+    #[serde(default)]
+    pub epoch: u32,
+    /// Matches `PartialSecret.generation` of the shares these commitments verify.
+    #[serde(default)]
+    pub generation: u32
+}
+
+fn default_scheme() -> String {
+    FeldmanVSS::SCHEME.to_owned()
+}
\ No newline at end of file
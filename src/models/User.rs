@@ -2,19 +2,46 @@
 use mongodb::bson::{oid::ObjectId, Document, to_bson};
 use serde::{Serialize, Deserialize};
 
+use super::PartialSecret::default_curve;
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Wallet {
     pub pub_key: String,
-    pub degree: u8
+    pub degree: u8,
+    /// Curve of the wallet key, same tag as `PartialSecret.curve`.
+    #[serde(default = "default_curve")]
+    pub curve: String,
+    /// BIP-32 path of the key below the user's seed, absent for keys that come from their own key generation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+    /// Hex encoded BIP-32 chain code of the key, recorded with `derivation_path`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chain_code: Option<String>,
+    /// BIP-32 `xpub` of the change level one step above `derivation_path`, e.g. `m/44'/60'/0'/0`. Receiving addresses
+    /// are derived from it at their `address_index`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xpub: Option<String>
 }
 
 impl Wallet {
-    pub fn new(pub_key: String, degree: u8) -> Self {
-        Self { pub_key, degree }
+    pub fn new(pub_key: String, degree: u8, curve: &str) -> Self {
+        Self { pub_key, degree, curve: curve.to_owned(), derivation_path: None, chain_code: None, xpub: None }
+    }
+
+    /// A secp256k1 wallet whose key is derived from the user's seed along `derivation_path`, below the change level `xpub`.
+    pub fn derived(pub_key: String, degree: u8, derivation_path: &str, chain_code: &str, xpub: &str) -> Self {
+        Self {
+            pub_key,
+            degree,
+            curve: default_curve(),
+            derivation_path: Some(derivation_path.to_owned()),
+            chain_code: Some(chain_code.to_owned()),
+            xpub: Some(xpub.to_owned())
+        }
     }
 
     pub fn copy(&self) -> Wallet {
-        Wallet { pub_key: self.pub_key.clone(), degree: self.degree.clone() }
+        Wallet { pub_key: self.pub_key.clone(), degree: self.degree, curve: self.curve.clone(), derivation_path: self.derivation_path.clone(), chain_code: self.chain_code.clone(), xpub: self.xpub.clone() }
     }
 }
 
@@ -22,7 +49,18 @@ impl Wallet {
 pub struct User {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
-    pub wallets: Vec<Wallet>
+    /// Public key under which the shares of the user's BIP-39 entropy are stored, every derived wallet comes from it.
+    #[serde(default)]
+    pub seed: String,
+    /// BIP-32 `xpub` of a seed key generated jointly by the holders, every wallet is a non-hardened descendant of it.
+    /// Absent for a BIP-39 seed this server dealt.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_xpub: Option<String>,
+    pub wallets: Vec<Wallet>,
+    /// Hex X25519 keys of the holders in ascending order of their x-coordinates. Empty while the user's shares are
+    /// stored in plaintext.
+    #[serde(default)]
+    pub encryption_keys: Vec<String>
 }
 
 impl User {
@@ -31,6 +69,6 @@ impl User {
     }
 
     pub fn copy(&self) -> User {
-        User { id: self.id.clone(), wallets: self.wallets.iter().map(|x| x.copy()).collect() }
+        User { id: self.id, seed: self.seed.clone(), seed_xpub: self.seed_xpub.clone(), wallets: self.wallets.iter().map(|x| x.copy()).collect(), encryption_keys: self.encryption_keys.clone() }
     }
 }
\ No newline at end of file
@@ -1,2 +1,4 @@
 pub mod PartialSecret;
-pub mod User;
\ No newline at end of file
+pub mod User;
+pub mod SecretCommitment;
+pub mod Holder;
\ No newline at end of file
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use tokio::time::{timeout_at, Duration, Instant};
+
+use crate::{services::{SecretService::{SecretService, UserKeys}, ServiceError::ServiceError}, util::{bls::BlsFeldmanVSS, cluster::{ClusterConfig, ShareDelivery}, dkg::DistributedKeyGeneration, ed25519::Ed25519FeldmanVSS, feldman::FeldmanVSS, session::{SessionChannel, SessionError, SessionFrame, SessionRouter}, transport::{Transport, TransportError}}};
+
+/// Session every node receives the requests of its peers on.
+pub const REQUESTS: &str = "cluster/requests";
+/// Time a node waits for the reports of the others, or for the decision of the node that asked.
+pub const REPORT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Work a node asks its peers to take part in.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ClusterTask {
+    /// Generate the keys of the user `user_id` together and store this node's shares and the user.
+    CreateUser { user_id: String, degree: u8 },
+    /// Store the share of a key dealt by the sender.
+    StoreShare(ShareDelivery),
+}
+
+/// A task with the session its key generations and reports run under.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterRequest {
+    pub session_id: String,
+    pub task: ClusterTask,
+}
+
+/// What a node reports on its part of a task, and what the node that asked decides once every report is in: `Ok` to
+/// keep what was stored, or the reason to roll it back.
+pub type Outcome = Result<(), String>;
+
+pub struct ClusterService;
+
+fn reportSession(session_id: &str) -> String {
+    format!("{}/reports", session_id)
+}
+
+#[allow(dead_code)]
+impl ClusterService {
+    /// Joins the session the peers' requests come in on, see `ClusterService::ask`.
+    pub fn requests(router: &SessionRouter, cluster: &ClusterConfig) -> Result<SessionChannel, SessionError> {
+        router.join(REQUESTS, &cluster.coordinates())
+    }
+
+    /// Sends every request of `requests` to its node over the node transport. Returns the channel the nodes' reports come
+    /// in on, joined before any request leaves so no report is missed.
+    pub async fn ask(router: &SessionRouter, session_id: &str, requests: Vec<(u8, ClusterTask)>) -> Result<SessionChannel, SessionError> {
+        let mut participants: Vec<u8> = requests.iter().map(|(x, _)| *x).collect();
+        participants.push(router.local());
+        let reports = router.join(&reportSession(session_id), &participants)?;
+        for (to, task) in requests {
+            let request = ClusterRequest { session_id: session_id.to_owned(), task };
+            let payload = serde_json::to_vec(&request).map_err(|err| TransportError(err.to_string()))?;
+            router.send(to, &SessionFrame::new(REQUESTS, 0, &payload)).await?;
+        }
+        Ok(reports)
+    }
+
+    /// Joins the channel a node reports to `asking` on and hears its decision from.
+    pub fn answer(router: &SessionRouter, session_id: &str, asking: u8) -> Result<SessionChannel, SessionError> {
+        router.join(&reportSession(session_id), &[asking, router.local()])
+    }
+
+    pub async fn report(channel: &SessionChannel, to: u8, outcome: &Outcome) -> Result<(), TransportError> {
+        channel.send(to, serde_json::to_vec(outcome).unwrap()).await
+    }
+
+    /// Waits for the report of every other participant of `channel`, until `REPORT_TIMEOUT`. Fails with the first node
+    /// that could not do its part, or with the nodes that did not report in time.
+    pub async fn awaitReports(channel: &mut SessionChannel) -> Result<(), SessionError> {
+        let mut missing = channel.peers();
+        let deadline = Instant::now() + REPORT_TIMEOUT;
+        while !missing.is_empty() {
+            let (from, payload) = match timeout_at(deadline, channel.receive()).await {
+                Ok(received) => received?,
+                Err(_) => return Err(SessionError::Failed(format!("nodes {:?} did not report in time", missing))),
+            };
+            match serde_json::from_slice::<Outcome>(&payload) {
+                Ok(Ok(())) => missing.retain(|x| *x != from),
+                Ok(Err(reason)) => return Err(SessionError::Failed(format!("node {} failed: {}", from, reason))),
+                Err(_) => log::warn!("Dropped a report from node {} that is not an outcome", from),
+            }
+        }
+        Ok(())
+    }
+
+    /// Tells every other participant of `channel` whether to keep what it stored.
+    pub async fn decide(channel: &SessionChannel, decision: &Outcome) -> Result<(), TransportError> {
+        channel.broadcast(serde_json::to_vec(decision).unwrap()).await
+    }
+
+    /// Waits for the decision of `asking`, until `REPORT_TIMEOUT`. No decision in time counts as a rollback.
+    pub async fn awaitDecision(channel: &mut SessionChannel, asking: u8) -> Outcome {
+        let deadline = Instant::now() + REPORT_TIMEOUT;
+        loop {
+            match timeout_at(deadline, channel.receive()).await {
+                Ok(Ok((from, payload))) if from == asking => {
+                    return serde_json::from_slice::<Outcome>(&payload).unwrap_or_else(|_| Err("the decision is not an outcome".to_string()));
+                }
+                Ok(Ok(_)) => continue,
+                Ok(Err(err)) => return Err(err.to_string()),
+                Err(_) => return Err(format!("node {} did not decide in time", asking)),
+            }
+        }
+    }
+
+    /// Generates every key of a new user with the other nodes of the cluster, each node a participant of its own, see
+    /// `SecretService::userKeyGeneration`. Returns this node's shares only, next to the public data every node agrees on.
+    pub async fn userKeyGeneration(router: &SessionRouter, cluster: &ClusterConfig, session_id: &str, degree: u8) -> Result<UserKeys, ServiceError> {
+        let participants = cluster.coordinates();
+        let keys = cluster.signingKeys();
+        let seed = SecretService::seedKey(vec![
+            DistributedKeyGeneration::runParty::<FeldmanVSS>(router, &format!("{}/seed", session_id), degree, &participants, cluster.signingSecret(), keys.clone()).await?
+        ]);
+        let ed25519 = SecretService::ed25519Key(vec![
+            DistributedKeyGeneration::runParty::<Ed25519FeldmanVSS>(router, &format!("{}/ed25519", session_id), degree, &participants, cluster.signingSecret(), keys.clone()).await?
+        ]);
+        let bls = SecretService::blsKey(vec![
+            DistributedKeyGeneration::runParty::<BlsFeldmanVSS>(router, &format!("{}/bls", session_id), degree, &participants, cluster.signingSecret(), keys).await?
+        ]);
+        Ok(UserKeys {
+            seed_xpub: Some(SecretService::seedXpub(&seed.commitments)?),
+            wallets: SecretService::deriveWallets(&seed, &SecretService::JOINT_WALLETS)?,
+            seed,
+            ed25519,
+            bls,
+        })
+    }
+}
\ No newline at end of file
@@ -0,0 +1,32 @@
+use std::collections::HashMap;
+
+use mongodb::error::Error;
+
+use crate::{database::SecretRepository::SecretRepository, util::envelope::ShareEnvelope};
+
+pub struct MigrationService;
+
+impl MigrationService {
+    /// Rewrites every share stored in the legacy `x||y` form as an envelope, returns how many were migrated.
+    /// Shares that do not parse are left untouched so they can still be inspected by hand.
+    pub async fn migrateShareEnvelopes(db: &SecretRepository) -> Result<usize, Error> {
+        let mut schemes: HashMap<String, String> = HashMap::new();
+        let mut migrated = 0;
+        for secret in db.find_legacy_secrets().await? {
+            if !schemes.contains_key(&secret.public_key) {
+                let scheme = db.find_commitments(&secret.public_key).await?.map(|c| c.scheme).unwrap_or_default();
+                schemes.insert(secret.public_key.clone(), scheme);
+            }
+            let scheme = &schemes[&secret.public_key];
+            let envelope = match ShareEnvelope::fromShareString(&secret.partial_secret, scheme, &secret.curve, secret.secret_degree + 1, &secret.public_key).map(|envelope| envelope.toText()) {
+                Some(Ok(envelope)) => envelope,
+                _ => continue,
+            };
+            if let Some(id) = secret.id {
+                db.replace_partial_secret(id, envelope).await?;
+                migrated += 1;
+            }
+        }
+        Ok(migrated)
+    }
+}
\ No newline at end of file
@@ -1,42 +1,503 @@
-use std::{str::FromStr};
+use std::{fmt::Display, str::FromStr};
 
-use bigdecimal::{BigDecimal, num_bigint::{ToBigInt}};
-use rand::Rng;
-use primitive_types::U256;
+use rand::seq::SliceRandom;
+use serde::Serialize;
 
-use crate::util::shamir::ShamirAlgorithm;
+use crate::{services::ServiceError::ServiceError, util::{bip32::{DerivationError, DerivationPath, ExtendedPrivateKey, ExtendedPublicKey, ThresholdDerivation}, bip39::Bip39, bls::{self, BlsFeldmanVSS, BlsScalar, ThresholdBls}, byte_shamir::ByteShamir, dkg::{DistributedKeyGeneration, DkgGroup, DkgShare}, envelope::{EnvelopeError, ShareEnvelope}, ecdsa::ThresholdEcdsa, ed25519::{self, Ed25519FeldmanVSS, Ed25519Scalar}, frost_ed25519::FrostEd25519, feldman::FeldmanVSS, field::{Field, FieldElement}, frost::{Frost, TaprootKey}, hpke::{CiphertextError, Hpke, SHARE_INFO}, messaging::InMemoryBus, pedersen::PedersenVSS, refresh::ShareRefresh, repair::ShareRepair, reshare::ShareResharing, secp256k1, shamir::{ShamirAlgorithm, ShamirError}, slip39::Slip39}};
 
 pub struct SecretService;
 
-#[allow(dead_code)]
+/// Outcome of checking a holder's share against the published Feldman commitments.
+#[derive(Debug, Serialize)]
+pub struct ShareVerification {
+    /// The share lies on the committed polynomial.
+    pub valid_share: bool,
+    /// The committed secret is the private key of the wallet public key. Pedersen commitments hide the secret, so it is absent for them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches_public_key: Option<bool>,
+}
+
+/// Output of a distributed key generation, as much of it as the server gets to see.
+pub struct GeneratedKey<F: Field = FieldElement> {
+    /// `[x, y]` share of every holder.
+    pub shares: Vec<Vec<F>>,
+    pub commitments: Vec<String>,
+    /// Joint public key, in the form the wallet's chain uses.
+    pub public_key: String,
+}
+
+/// A wallet key derived from a user's seed and dealt to the seed's holders, with what its `Wallet` records.
+pub struct DerivedKey {
+    pub key: GeneratedKey,
+    pub derivation_path: String,
+    /// Hex encoded BIP-32 chain code of the derived key.
+    pub chain_code: String,
+    /// `xpub` of the change level the key is an address of.
+    pub xpub: String,
+}
+
+/// Every key a new user gets: the seed key with the wallets derived from it, and the Ed25519 and BLS keys.
+pub struct UserKeys {
+    pub seed: GeneratedKey,
+    /// `xpub` of a jointly generated seed, absent for a dealt BIP-39 seed.
+    pub seed_xpub: Option<String>,
+    pub wallets: Vec<DerivedKey>,
+    pub ed25519: GeneratedKey<Ed25519Scalar>,
+    pub bls: GeneratedKey<BlsScalar>,
+}
+
+/// Result of an error correcting reconstruction. The secret itself is never returned, only its public key.
+#[derive(Debug, Serialize)]
+pub struct RecoveredSecret {
+    pub public_key: String,
+    /// x-coordinates of the shares that did not lie on the recovered polynomial.
+    pub faulty_shares: Vec<String>,
+}
+
+/// Threshold ECDSA signature in the form Ethereum expects, `v` is `27` plus the recovery id.
+#[derive(Debug, Serialize)]
+pub struct EcdsaSignatureResponse {
+    pub r: String,
+    pub s: String,
+    pub v: u8,
+}
+
 impl SecretService {
-    fn getRandomDifferentNumbers(amount: u8) -> Vec<BigDecimal> {
-        assert!(!(amount <= 2_u8), "Amount: {} must be lower than {}", amount, 2);
-        let mut result: Vec<BigDecimal> = vec![];
-        while result.len() != amount as usize {
-            let rand = rand::thread_rng().gen_range(1..255);
-            if !result.contains(&BigDecimal::from(rand.to_bigint().unwrap())) {
-                result.push(BigDecimal::from(rand.to_bigint().unwrap()));
-            }
+    /// Wallets of a jointly generated seed, the non-hardened counterparts of the first BIP-44, BIP-84 and BIP-86 accounts.
+    pub const JOINT_WALLETS: [&'static str; 3] = [DerivationPath::JOINT_ETHEREUM, DerivationPath::JOINT_SEGWIT, DerivationPath::JOINT_TAPROOT];
+    /// Wallets of a dealt BIP-39 seed, at the hardened BIP-44, BIP-84 and BIP-86 accounts.
+    pub const DEALT_WALLETS: [&'static str; 3] = [DerivationPath::ETHEREUM, DerivationPath::SEGWIT, DerivationPath::TAPROOT];
+
+    /// Distinct random x-coordinates in `1..=255` for `amount` holders that are not in the holder registry, avoiding
+    /// the `registered` holders' own x-coordinates.
+    pub fn randomCoordinates(amount: u8, registered: &[u8]) -> Result<Vec<u8>, ServiceError> {
+        let mut free: Vec<u8> = (1..=u8::MAX).filter(|x| !registered.contains(x)).collect();
+        if amount == 0 || free.len() < amount as usize {
+            return Err(ShamirError::InvalidSharing(format!("{} x-coordinates are free, {} holders need one each", free.len(), amount)).into());
         }
-        result
+        free.shuffle(&mut rand::thread_rng());
+        free.truncate(amount as usize);
+        Ok(free)
+    }
+
+    /// The x-coordinates of `holders` as field elements, a registered holder's coordinate is `Holder.x`.
+    fn coordinates<F: Field>(holders: &[u8]) -> Vec<F> {
+        holders.iter().map(|x| F::from_u64(*x as u64)).collect()
+    }
+
+    /// Splits a hex encoded secret with Pedersen VSS into shares `[x, f(x), g(x)]` for the holders at `holders`, where `g` is a random blinding polynomial.
+    /// Also returns the hiding commitments, the first of which identifies the secret without revealing it.
+    pub fn pedersenSecretPartition(degree: u8, secret: String, holders: &[u8]) -> Result<(Vec<Vec<FieldElement>>, Vec<String>), ServiceError> {
+        let rand_nums = Self::coordinates(holders);
+        let secret = FieldElement::from_hex(secret.as_str())
+            .ok_or_else(|| ShamirError::InvalidSharing("secret must be a hex encoded scalar lower than the secp256k1 order".to_string()))?;
+        let polynomial = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(secret);
+        let blinding = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(FieldElement::random());
+        let shares = rand_nums.iter()
+            .map(|x| vec![*x, polynomial.evaluate_at(*x), blinding.evaluate_at(*x)])
+            .collect();
+        Ok((shares, FeldmanVSS::encodeCommitments(&PedersenVSS::commit(&polynomial, &blinding))))
+    }
+
+    /// Splits an arbitrary byte string byte-wise over GF(2^8), returning Vault compatible shares as hex.
+    pub fn splitBytes(degree: u8, secret: &[u8], parties: u8) -> Result<Vec<String>, ServiceError> {
+        Ok(ByteShamir::split(degree, secret, parties)?.iter().map(hex::encode).collect())
     }
 
-    pub fn secretPartition(degree: u8, secret: String, parties: u8) -> Vec<Vec<BigDecimal>> {
+    /// Combines hex encoded shares made by `splitBytes` (or by Vault) back into the secret bytes.
+    pub fn combineBytes(shares: &[String]) -> Result<Vec<u8>, ServiceError> {
+        let decoded = shares.iter()
+            .enumerate()
+            .map(|(i, share)| hex::decode(share).map_err(|_| ShamirError::MalformedShare(i)))
+            .collect::<Result<Vec<Vec<u8>>, ShamirError>>()?;
+        Ok(ByteShamir::combine(&decoded)?)
+    }
+
+    /// Backs up `secret` as SLIP-0039 mnemonics: `groups` are `(member_threshold, member_count)` pairs and any
+    /// `group_threshold` of them recover the secret, given the same passphrase.
+    pub fn slip39Split(group_threshold: u8, groups: &[(u8, u8)], secret: &[u8], passphrase: &str) -> Result<Vec<Vec<String>>, ServiceError> {
+        Ok(Slip39::generate(group_threshold, groups, secret, passphrase.as_bytes(), 1)?)
+    }
+
+    /// Combines SLIP-0039 mnemonics, ours or a Trezor's, back into the master secret.
+    pub fn slip39Combine(mnemonics: &[String], passphrase: &str) -> Result<Vec<u8>, ServiceError> {
+        Ok(Slip39::combine(mnemonics, passphrase.as_bytes())?)
+    }
+
+    /// Reconstructs the secret from possibly corrupted shares and reports its public key together with the shares that were wrong.
+    pub fn recoverSecret(degree: u8, values: Vec<Vec<FieldElement>>) -> Result<RecoveredSecret, ServiceError> {
         let shamir = ShamirAlgorithm::new(Some(degree));
-        let rand_nums = self::SecretService::getRandomDifferentNumbers(parties);
-        let secret = U256::from_str_radix(secret.as_str(), 16).unwrap().to_string();
-        let polynomial = shamir.polynomialGenerator(BigDecimal::from_str(&secret).unwrap());
-        let mut result: Vec<Vec<BigDecimal>> = vec![];
-        for i in 0..rand_nums.len() {
-            let evaluation = polynomial.evaluate_at(rand_nums[i].to_owned());
-            result.push(vec![rand_nums[i].to_owned(), evaluation])
+        let (secret, faulty) = shamir.robustCombine(&values)?;
+        Ok(RecoveredSecret {
+            public_key: secp256k1::point_to_raw_hex(&secp256k1::mul_base(&secret)),
+            faulty_shares: faulty.into_iter().map(|i| values[i][0].to_string()).collect(),
+        })
+    }
+
+    /// Runs a proactive refresh between the holders of `shares`. Returns their new shares and the updated commitments,
+    /// the secret and `C_0` stay the same.
+    pub fn refreshShares(degree: u8, shares: Vec<Vec<FieldElement>>, commitments: &[String]) -> Result<(Vec<Vec<FieldElement>>, Vec<String>), ServiceError> {
+        let decoded = FeldmanVSS::decodeCommitments(commitments).ok_or(ShamirError::InvalidCommitments)?;
+        let (refreshed, new_commitments) = ShareRefresh::refresh(degree, &shares, &decoded)?;
+        Ok((refreshed, FeldmanVSS::encodeCommitments(&new_commitments)))
+    }
+
+    /// Reshares the secret held by `shares` to the holders at the x-coordinates `new_holders` with a polynomial of `new_degree`,
+    /// without reconstructing it. Returns the new shares and their commitments.
+    pub fn reshareSecret(degree: u8, shares: Vec<Vec<FieldElement>>, commitments: &[String], new_degree: u8, new_holders: &[u8]) -> Result<(Vec<Vec<FieldElement>>, Vec<String>), ServiceError> {
+        let decoded = FeldmanVSS::decodeCommitments(commitments).ok_or(ShamirError::InvalidCommitments)?;
+        let new_xs = Self::coordinates(new_holders);
+        let (new_shares, new_commitments) = ShareResharing::reshare(degree, &shares, &decoded, new_degree, &new_xs)?;
+        Ok((new_shares, FeldmanVSS::encodeCommitments(&new_commitments)))
+    }
+
+    /// Recomputes the share of the holder at `lost_x` from the remaining holders' `shares`, without any of them learning the secret.
+    pub fn repairShare(degree: u8, shares: Vec<Vec<FieldElement>>, commitments: &[String], lost_x: FieldElement) -> Result<Vec<FieldElement>, ServiceError> {
+        let decoded = FeldmanVSS::decodeCommitments(commitments).ok_or(ShamirError::InvalidCommitments)?;
+        Ok(ShareRepair::repair(degree, &shares, &decoded, &lost_x)?)
+    }
+
+    /// The shares some or all holders got out of one key generation, with the joint key in the form `public_key` has.
+    fn generatedKey<G: DkgGroup>(shares: Vec<DkgShare<G>>, public_key: String) -> GeneratedKey<G::Scalar> {
+        GeneratedKey {
+            public_key,
+            commitments: shares[0].encodeCommitments(),
+            shares: shares.into_iter().map(|share| share.share).collect(),
         }
-        result
     }
 
-    pub fn getSecret(degree: u8, values: Vec<Vec<BigDecimal>>) -> String {
-        let shamir = ShamirAlgorithm::new(Some(degree));
-        shamir.fromValues(values).as_string()
+    /// A seed key out of a key generation, under its compressed first Feldman commitment.
+    pub fn seedKey(shares: Vec<DkgShare<FeldmanVSS>>) -> GeneratedKey {
+        let public_key = secp256k1::point_to_hex(&shares[0].commitments[0]);
+        Self::generatedKey(shares, public_key)
+    }
+
+    /// An Ed25519 key out of a key generation, under the hex encoded 32 byte Ed25519 public key.
+    pub fn ed25519Key(shares: Vec<DkgShare<Ed25519FeldmanVSS>>) -> GeneratedKey<Ed25519Scalar> {
+        let public_key = ed25519::point_to_hex(&shares[0].commitments[0]);
+        Self::generatedKey(shares, public_key)
+    }
+
+    /// A BLS key out of a key generation, under the hex encoded 48 byte compressed G1 public key.
+    pub fn blsKey(shares: Vec<DkgShare<BlsFeldmanVSS>>) -> GeneratedKey<BlsScalar> {
+        let public_key = bls::point_to_hex(&shares[0].commitments[0]);
+        Self::generatedKey(shares, public_key)
+    }
+
+    /// Generates every key of a new user between the holders at `holders`, with a BIP-39 seed from
+    /// `dealtHdWalletGeneration`. With `joint_seed` the seed key is generated jointly by `hdWalletGeneration` instead.
+    pub async fn userKeyGeneration(degree: u8, holders: &[u8], joint_seed: bool) -> Result<UserKeys, ServiceError> {
+        let (seed, wallets, seed_xpub) = if joint_seed {
+            let (seed, wallets) = Self::hdWalletGeneration(degree, holders, &Self::JOINT_WALLETS).await?;
+            let seed_xpub = Self::seedXpub(&seed.commitments)?;
+            (seed, wallets, Some(seed_xpub))
+        } else {
+            let (seed, wallets) = Self::dealtHdWalletGeneration(degree, holders, &Self::DEALT_WALLETS)?;
+            (seed, wallets, None)
+        };
+        Ok(UserKeys {
+            seed,
+            seed_xpub,
+            wallets,
+            ed25519: Self::ed25519KeyGeneration(degree, holders).await?,
+            bls: Self::blsKeyGeneration(degree, holders).await?,
+        })
+    }
+
+    /// Signs the 32 byte `hash` with the key the holders of `shares` share, running every holder in this process.
+    /// The key is the one the Feldman `commitments` commit to, and at least `2 * degree + 1` shares are needed.
+    pub fn signHash(degree: u8, shares: Vec<Vec<FieldElement>>, commitments: &[String], hash: &[u8; 32]) -> Result<EcdsaSignatureResponse, ServiceError> {
+        let decoded = FeldmanVSS::decodeCommitments(commitments).ok_or(ShamirError::InvalidCommitments)?;
+        let public_key = decoded.first().ok_or(ShamirError::InvalidCommitments)?;
+        let mut bus = InMemoryBus::new(shares.len());
+        let presignatures = ThresholdEcdsa::presign(degree, &shares, &mut bus)?;
+        let signature = ThresholdEcdsa::sign(degree, presignatures, hash, public_key, &mut bus)?;
+        Ok(EcdsaSignatureResponse { r: signature.r.to_hex(), s: signature.s.to_hex(), v: 27 + signature.recovery_id })
+    }
+
+    /// Whether a key with Feldman commitments of `scheme` signs with threshold ECDSA: every secp256k1 key other than a
+    /// Taproot wallet, see `isTaproot`. Such keys need `ThresholdEcdsa::requiredHolders` holders.
+    pub fn signsWithEcdsa(scheme: &str, derivation_path: Option<&str>) -> bool {
+        scheme == FeldmanVSS::SCHEME && !Self::isTaproot(derivation_path)
+    }
+
+    /// Whether a wallet at `derivation_path` is a Taproot wallet, i.e. below a BIP-86 account, whose public key is the
+    /// x-only output key. `None` for keys that are not derived wallets.
+    pub fn isTaproot(derivation_path: Option<&str>) -> bool {
+        derivation_path.and_then(|path| path.parse::<DerivationPath>().ok()).and_then(|path| path.purpose()) == Some(86)
+    }
+
+    /// The holders that sign with FROST for a key of degree `degree`, the first `degree + 1` of `shares`. Fewer are left to
+    /// `Frost::sign` and `FrostEd25519::sign` to refuse.
+    fn signingQuorum<T>(degree: u8, shares: &[T]) -> &[T] {
+        &shares[..shares.len().min(degree as usize + 1)]
+    }
+
+    /// Signs a Taproot sighash with FROST between the first `degree + 1` holders of `shares` and returns the hex encoded
+    /// 64 byte BIP-340 signature for a key-path spend of the output key the `commitments` belong to.
+    pub fn signTaproot(degree: u8, shares: Vec<Vec<FieldElement>>, commitments: &[String], sighash: &[u8; 32]) -> Result<String, ServiceError> {
+        let decoded = FeldmanVSS::decodeCommitments(commitments).ok_or(ShamirError::InvalidCommitments)?;
+        if decoded.is_empty() {
+            return Err(ShamirError::InvalidCommitments.into());
+        }
+        let key = TaprootKey::new(decoded, None);
+        let quorum = Self::signingQuorum(degree, &shares);
+        let mut bus = InMemoryBus::new(quorum.len());
+        let signature = Frost::sign(degree, &key, quorum, sighash, &mut bus)?;
+        Ok(hex::encode(signature.to_bytes()))
+    }
+
+    /// Generates the user's seed key jointly between the holders at `holders` and derives the wallet key at every one of the
+    /// non-hardened `paths` below it on the holders' shares, so neither the seed nor a wallet key exists in one place. The
+    /// seed's `public_key` is its compressed first Feldman commitment.
+    pub async fn hdWalletGeneration(degree: u8, holders: &[u8], paths: &[&str]) -> Result<(GeneratedKey, Vec<DerivedKey>), ServiceError> {
+        let seed = Self::seedKey(DistributedKeyGeneration::runLocally::<FeldmanVSS>(degree, holders).await?);
+        let keys = Self::deriveWallets(&seed, paths)?;
+        Ok((seed, keys))
+    }
+
+    /// Derives the wallet at every one of the non-hardened `paths` below the jointly generated `seed`, see `deriveWallet`.
+    pub fn deriveWallets(seed: &GeneratedKey, paths: &[&str]) -> Result<Vec<DerivedKey>, ServiceError> {
+        paths.iter()
+            .map(|path| Self::deriveWallet(seed.shares.clone(), &seed.commitments, path))
+            .collect()
+    }
+
+    /// The `xpub` of a jointly generated seed key, see `ExtendedPublicKey::jointRoot`.
+    pub fn seedXpub(commitments: &[String]) -> Result<String, ServiceError> {
+        let decoded = FeldmanVSS::decodeCommitments(commitments).ok_or(ShamirError::InvalidCommitments)?;
+        let point = decoded.first().ok_or(ShamirError::InvalidCommitments)?;
+        Ok(ExtendedPublicKey::jointRoot(*point).to_string())
+    }
+
+    /// Derives one more wallet along the non-hardened `path` below a jointly generated seed, on the seed's `seed_shares`.
+    /// Every share only moves by public tweaks, any subset of the holders' shares can be derived on its own.
+    pub fn deriveWallet(seed_shares: Vec<Vec<FieldElement>>, seed_commitments: &[String], path: &str) -> Result<DerivedKey, ServiceError> {
+        let decoded = FeldmanVSS::decodeCommitments(seed_commitments).ok_or(ShamirError::InvalidCommitments)?;
+        let root = ExtendedPublicKey::jointRoot(*decoded.first().ok_or(ShamirError::InvalidCommitments)?);
+        let path: DerivationPath = path.parse()?;
+        let change_path = path.parent().ok_or_else(|| DerivationError("the seed itself is not a wallet".to_string()))?;
+        let derived = ThresholdDerivation::derivePath(&root, &path, &seed_shares, &decoded)?;
+        Ok(DerivedKey {
+            key: GeneratedKey {
+                public_key: Self::walletPublicKey(&path, &derived.commitments),
+                commitments: FeldmanVSS::encodeCommitments(&derived.commitments),
+                shares: derived.shares,
+            },
+            derivation_path: path.to_string(),
+            chain_code: hex::encode(derived.public_key.chain_code),
+            xpub: root.derivePath(&change_path)?.to_string(),
+        })
+    }
+
+    /// Deals a fresh 256 bit BIP-39 entropy generated on this server to the holders at `holders` and derives the wallet
+    /// key at every one of `paths` from it, hardened steps included, each dealt to the same holders. The seed restores
+    /// from its mnemonic in any BIP-39 wallet, but the server sees the seed and every key while dealing them.
+    pub fn dealtHdWalletGeneration(degree: u8, holders: &[u8], paths: &[&str]) -> Result<(GeneratedKey, Vec<DerivedKey>), ServiceError> {
+        let xs: Vec<FieldElement> = Self::coordinates(holders);
+        let entropy = FieldElement::random();
+        let polynomial = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(entropy);
+        let commitments = FeldmanVSS::commit(&polynomial);
+        let seed = GeneratedKey {
+            public_key: secp256k1::point_to_hex(&commitments[0]),
+            commitments: FeldmanVSS::encodeCommitments(&commitments),
+            shares: xs.iter().map(|x| vec![*x, polynomial.evaluate_at(*x)]).collect(),
+        };
+        let keys = paths.iter()
+            .map(|path| Self::dealDerivedKey(degree, &xs, &entropy, path))
+            .collect::<Result<Vec<DerivedKey>, ServiceError>>()?;
+        Ok((seed, keys))
+    }
+
+    /// Derives one more wallet from a dealt BIP-39 seed by combining its `seed_shares` on this server, and deals it to the
+    /// same holders. Only for seeds from `dealtHdWalletGeneration`, which this server has seen anyway.
+    pub fn deriveDealtWallet(degree: u8, seed_shares: Vec<Vec<FieldElement>>, path: &str) -> Result<DerivedKey, ServiceError> {
+        let (entropy, _) = ShamirAlgorithm::new(Some(degree)).robustCombine(&seed_shares)?;
+        let xs: Vec<FieldElement> = seed_shares.iter().map(|share| share[0]).collect();
+        Self::dealDerivedKey(degree, &xs, &entropy, path)
+    }
+
+    /// Derives the address at `index` of a derived wallet's change level, i.e. its sibling at `…/change/index`, on the wallet's
+    /// shares, no reconstruction involved. `public_key`, `derivation_path` and the change level `xpub` are what the wallet's
+    /// `Wallet` records; the public key is checked against the commitments and the `xpub` against the public key.
+    pub fn deriveChildWallet(shares: Vec<Vec<FieldElement>>, commitments: &[String], public_key: &str, derivation_path: &str, xpub: &str, index: u32) -> Result<DerivedKey, ServiceError> {
+        let decoded = FeldmanVSS::decodeCommitments(commitments).ok_or(ShamirError::InvalidCommitments)?;
+        let path: DerivationPath = derivation_path.parse()?;
+        if decoded.is_empty() || Self::walletPublicKey(&path, &decoded) != public_key {
+            return Err(ShamirError::InvalidCommitments.into());
+        }
+        let change: ExtendedPublicKey = xpub.parse()?;
+        let (change_path, own_index) = match (path.parent(), path.0.last()) {
+            (Some(change_path), Some(own_index)) if change.depth as usize == change_path.0.len() => (change_path, *own_index),
+            _ => return Err(DerivationError(format!("xpub is not the change level of {}", path)).into()),
+        };
+        let derived = ThresholdDerivation::deriveSibling(&change, own_index, index, &shares, &decoded)?;
+        let child_path = DerivationPath([change_path.0, vec![index]].concat());
+        Ok(DerivedKey {
+            key: GeneratedKey {
+                public_key: Self::walletPublicKey(&child_path, &derived.commitments),
+                commitments: FeldmanVSS::encodeCommitments(&derived.commitments),
+                shares: derived.shares,
+            },
+            derivation_path: child_path.to_string(),
+            chain_code: hex::encode(derived.public_key.chain_code),
+            xpub: xpub.to_owned(),
+        })
+    }
+
+    fn dealDerivedKey(degree: u8, xs: &[FieldElement], entropy: &FieldElement, path: &str) -> Result<DerivedKey, ServiceError> {
+        let path: DerivationPath = path.parse()?;
+        let mnemonic = Bip39::entropyToMnemonic(&entropy.to_bytes())?;
+        let change_path = path.parent().ok_or_else(|| DerivationError("the seed itself is not a wallet".to_string()))?;
+        let change = ExtendedPrivateKey::fromSeed(&Bip39::toSeed(&mnemonic, ""))?.derivePath(&change_path)?;
+        let key = change.deriveChild(*path.0.last().unwrap())?;
+        let polynomial = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(key.key);
+        let commitments = FeldmanVSS::commit(&polynomial);
+        Ok(DerivedKey {
+            key: GeneratedKey {
+                public_key: Self::walletPublicKey(&path, &commitments),
+                commitments: FeldmanVSS::encodeCommitments(&commitments),
+                shares: xs.iter().map(|x| vec![*x, polynomial.evaluate_at(*x)]).collect(),
+            },
+            derivation_path: path.to_string(),
+            chain_code: hex::encode(key.chain_code),
+            xpub: change.publicKey().to_string(),
+        })
+    }
+
+    /// The form of the public key a wallet at `path` keeps: the x-only output key for BIP-86, the raw `X || Y` key for
+    /// Ethereum and the compressed key for other Bitcoin paths.
+    fn walletPublicKey(path: &DerivationPath, commitments: &[k256::ProjectivePoint]) -> String {
+        match (path.purpose(), path.coinType()) {
+            (Some(86), _) => hex::encode(TaprootKey::new(commitments.to_vec(), None).outputKeyBytes()),
+            (_, Some(60)) => secp256k1::point_to_raw_hex(&commitments[0]),
+            _ => secp256k1::point_to_hex(&commitments[0]),
+        }
+    }
+
+    /// Generates an Ed25519 key between the holders at `holders`. `public_key` is the hex encoded 32 byte Ed25519 public key.
+    pub async fn ed25519KeyGeneration(degree: u8, holders: &[u8]) -> Result<GeneratedKey<Ed25519Scalar>, ServiceError> {
+        Ok(Self::ed25519Key(DistributedKeyGeneration::runLocally::<Ed25519FeldmanVSS>(degree, holders).await?))
+    }
+
+    /// Signs `message` with FROST(Ed25519, SHA-512) between the first `degree + 1` holders of `shares` and returns the hex
+    /// encoded 64 byte Ed25519 signature.
+    pub fn signEd25519(degree: u8, shares: Vec<Vec<Ed25519Scalar>>, commitments: &[String], message: &[u8]) -> Result<String, ServiceError> {
+        let decoded = Ed25519FeldmanVSS::decodeCommitments(commitments).ok_or(ShamirError::InvalidCommitments)?;
+        let quorum = Self::signingQuorum(degree, &shares);
+        let mut bus = InMemoryBus::new(quorum.len());
+        Ok(hex::encode(FrostEd25519::sign(degree, &decoded, quorum, message, &mut bus)?))
+    }
+
+    /// Generates a BLS12-381 key between the holders at `holders`. `public_key` is the hex encoded 48 byte G1 public key.
+    pub async fn blsKeyGeneration(degree: u8, holders: &[u8]) -> Result<GeneratedKey<BlsScalar>, ServiceError> {
+        Ok(Self::blsKey(DistributedKeyGeneration::runLocally::<BlsFeldmanVSS>(degree, holders).await?))
+    }
+
+    /// Has every holder of `shares` sign `message` on its own, each behind its own transport, and combines the partial
+    /// signatures. Returns the hex encoded 96 byte compressed G2 signature.
+    pub async fn signBls(degree: u8, shares: Vec<Vec<BlsScalar>>, commitments: &[String], message: &[u8]) -> Result<String, ServiceError> {
+        let decoded = BlsFeldmanVSS::decodeCommitments(commitments).ok_or(ShamirError::InvalidCommitments)?;
+        if decoded.is_empty() {
+            return Err(ShamirError::InvalidCommitments.into());
+        }
+        Ok(bls::signature_to_hex(&ThresholdBls::signLocally(degree, shares, decoded, message.to_vec()).await?))
+    }
+
+    /// The curve tag of the keys shared under `scheme`.
+    pub fn curveOfScheme(scheme: &str) -> &'static str {
+        match scheme {
+            Ed25519FeldmanVSS::SCHEME => ed25519::CURVE,
+            BlsFeldmanVSS::SCHEME => bls::CURVE,
+            _ => secp256k1::CURVE,
+        }
+    }
+
+    /// Renders a share in the legacy `x||y` (or `x||y||r` for Pedersen) form.
+    pub fn formatShare<F: Display>(share: &[F]) -> String {
+        share.iter().map(|value| value.to_string()).collect::<Vec<String>>().join("||")
+    }
+
+    /// Renders a share as the bech32m `ShareEnvelope` stored in `PartialSecret.partial_secret`.
+    pub fn encodeShare<F: Display>(share: &[F], scheme: &str, degree: u8, public_key: &str) -> Result<String, ServiceError> {
+        Ok(ShareEnvelope::fromShareString(&Self::formatShare(share), scheme, Self::curveOfScheme(scheme), degree + 1, public_key)
+            .ok_or_else(|| EnvelopeError("share values must be decimal integers".to_string()))?
+            .toText()?)
+    }
+
+    /// Seals a share envelope to the holder's hex X25519 key, bound to the wallet `public_key`. Returns the hex ciphertext.
+    pub fn sealShare(envelope: &str, holder_key: &str, public_key: &str) -> Result<String, ServiceError> {
+        let recipient = Hpke::parsePublicKey(holder_key)
+            .ok_or_else(|| CiphertextError("holder keys must be 32 hex encoded bytes".to_string()))?;
+        Ok(hex::encode(Hpke::seal(&recipient, SHARE_INFO, public_key.as_bytes(), envelope.as_bytes())?))
+    }
+
+    /// Parses a share envelope, or the legacy `x||y` or `x||y||r` form, into `[x, y]` or `[x, y, r]`.
+    pub fn parseShare<F: FromStr>(share: &str) -> Option<Vec<F>> {
+        if ShareEnvelope::isText(share) {
+            return Self::parseShare(&ShareEnvelope::fromText(share).ok()?.toShareString());
+        }
+        let values: Vec<F> = share.split("||").map(|value| value.parse().ok()).collect::<Option<Vec<F>>>()?;
+        if values.len() == 2 || values.len() == 3 {
+            Some(values)
+        } else {
+            None
+        }
+    }
+
+    /// The x-coordinate of a share envelope or a share in the legacy form, which is stored in the clear either way.
+    pub fn shareCoordinate(share: &str) -> Option<u8> {
+        if ShareEnvelope::isText(share) {
+            return Self::shareCoordinate(&ShareEnvelope::fromText(share).ok()?.toShareString());
+        }
+        share.split("||").next()?.parse().ok()
+    }
+
+    /// `parseShare` for a share of a known key. A share envelope must carry the key's fingerprint, `scheme`, curve and
+    /// `threshold`; the legacy forms carry none of them and are taken as they are.
+    pub fn parseShareOf<F: FromStr>(share: &str, scheme: &str, threshold: u8, public_key: &str) -> Result<Vec<F>, ServiceError> {
+        if ShareEnvelope::isText(share) {
+            ShareEnvelope::fromText(share)?.checkKey(scheme, Self::curveOfScheme(scheme), threshold, public_key)?;
+        }
+        Ok(Self::parseShare(share).ok_or_else(|| EnvelopeError("not a share envelope or a share in the x||y form".to_string()))?)
+    }
+
+    /// Checks a holder's share against the stored commitments of `scheme` and, for Feldman, the wallet public key. The
+    /// public key of a wallet at a Taproot `derivation_path` is its x-only output key, see `isTaproot`.
+    pub fn verifyShare(share: &[FieldElement], commitments: &[String], scheme: &str, public_key: &str, derivation_path: Option<&str>) -> Option<ShareVerification> {
+        let commitments = FeldmanVSS::decodeCommitments(commitments)?;
+        if scheme == PedersenVSS::SCHEME {
+            let valid_share = share.len() == 3 && PedersenVSS::verify(&share[0], &share[1], &share[2], &commitments);
+            return Some(ShareVerification { valid_share, matches_public_key: None });
+        }
+        let matches_public_key = if Self::isTaproot(derivation_path) {
+            !commitments.is_empty() && TaprootKey::new(commitments.clone(), None).outputKeyBytes().to_vec() == hex::decode(public_key).ok()?
+        } else {
+            FeldmanVSS::verifyPublicKey(&commitments, &secp256k1::point_from_hex(public_key)?)
+        };
+        Some(ShareVerification {
+            valid_share: share.len() == 2 && FeldmanVSS::verify(&share[0], &share[1], &commitments),
+            matches_public_key: Some(matches_public_key),
+        })
+    }
+
+    /// `verifyShare` for the `x||y` shares of a BLS key.
+    pub fn verifyBlsShare(share: &[BlsScalar], commitments: &[String], public_key: &str) -> Option<ShareVerification> {
+        let commitments = BlsFeldmanVSS::decodeCommitments(commitments)?;
+        let public_key = bls::point_from_hex(public_key)?;
+        Some(ShareVerification {
+            valid_share: share.len() == 2 && BlsFeldmanVSS::verify(&share[0], &share[1], &commitments),
+            matches_public_key: Some(commitments.first() == Some(&public_key)),
+        })
+    }
+
+    /// `verifyShare` for the `x||y` shares of an Ed25519 key.
+    pub fn verifyEd25519Share(share: &[Ed25519Scalar], commitments: &[String], public_key: &str) -> Option<ShareVerification> {
+        let commitments = Ed25519FeldmanVSS::decodeCommitments(commitments)?;
+        let public_key = ed25519::point_from_hex(public_key)?;
+        Some(ShareVerification {
+            valid_share: share.len() == 2 && Ed25519FeldmanVSS::verify(&share[0], &share[1], &commitments),
+            matches_public_key: Some(commitments.first() == Some(&public_key)),
+        })
     }
 }
\ No newline at end of file
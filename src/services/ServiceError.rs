@@ -0,0 +1,97 @@
+use std::fmt;
+
+use crate::util::{bip32::DerivationError, bip39::MnemonicError, bls::BlsSigningError, dkg::DkgError, envelope::EnvelopeError, hpke::CiphertextError, session::SessionError, shamir::ShamirError, slip39::Slip39Error};
+
+/// Reasons a service call fails, by the subsystem it failed in. The subsystems only know their own errors, they meet
+/// here.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ServiceError {
+    /// Shares can not be dealt, combined or checked.
+    Sharing(ShamirError),
+    /// A mnemonic can not be decoded.
+    Mnemonic(MnemonicError),
+    /// A BIP-32 derivation step is not possible.
+    Derivation(DerivationError),
+    /// A share envelope is malformed or belongs to another key.
+    Envelope(EnvelopeError),
+    /// A share can not be sealed to or opened with a key.
+    Ciphertext(CiphertextError),
+    /// A protocol session between the holders failed.
+    Session(SessionError),
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::Sharing(err) => err.fmt(f),
+            ServiceError::Mnemonic(err) => err.fmt(f),
+            ServiceError::Derivation(err) => err.fmt(f),
+            ServiceError::Envelope(err) => err.fmt(f),
+            ServiceError::Ciphertext(err) => err.fmt(f),
+            ServiceError::Session(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<ShamirError> for ServiceError {
+    fn from(err: ShamirError) -> Self {
+        ServiceError::Sharing(err)
+    }
+}
+
+impl From<MnemonicError> for ServiceError {
+    fn from(err: MnemonicError) -> Self {
+        ServiceError::Mnemonic(err)
+    }
+}
+
+impl From<DerivationError> for ServiceError {
+    fn from(err: DerivationError) -> Self {
+        ServiceError::Derivation(err)
+    }
+}
+
+impl From<EnvelopeError> for ServiceError {
+    fn from(err: EnvelopeError) -> Self {
+        ServiceError::Envelope(err)
+    }
+}
+
+impl From<CiphertextError> for ServiceError {
+    fn from(err: CiphertextError) -> Self {
+        ServiceError::Ciphertext(err)
+    }
+}
+
+impl From<Slip39Error> for ServiceError {
+    fn from(err: Slip39Error) -> Self {
+        match err {
+            Slip39Error::Mnemonic(err) => ServiceError::Mnemonic(err),
+            Slip39Error::Sharing(err) => ServiceError::Sharing(err),
+        }
+    }
+}
+
+impl From<SessionError> for ServiceError {
+    fn from(err: SessionError) -> Self {
+        ServiceError::Session(err)
+    }
+}
+
+impl From<DkgError> for ServiceError {
+    fn from(err: DkgError) -> Self {
+        match err {
+            DkgError::Sharing(err) => ServiceError::Sharing(err),
+            DkgError::Session(err) => ServiceError::Session(err),
+        }
+    }
+}
+
+impl From<BlsSigningError> for ServiceError {
+    fn from(err: BlsSigningError) -> Self {
+        match err {
+            BlsSigningError::Sharing(err) => ServiceError::Sharing(err),
+            BlsSigningError::Session(err) => ServiceError::Session(err),
+        }
+    }
+}
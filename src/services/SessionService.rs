@@ -0,0 +1,188 @@
+use std::time::Duration;
+
+use actix_web::web::Data;
+use tokio::time::interval;
+
+use crate::{database::SessionRepository::SessionRepository, util::{session::{nowMillis, MessageOutcome, Session, SessionError, SessionFrame, SessionRouter}, transport::Transport}};
+
+/// How often running sessions are checked for rounds past their deadline.
+pub const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+/// Times a session is read again when another writer stored it in between.
+const WRITE_ATTEMPTS: usize = 5;
+
+pub struct SessionService;
+
+fn storageError(err: impl ToString) -> SessionError {
+    SessionError::Storage(err.to_string())
+}
+
+#[allow(dead_code)]
+impl SessionService {
+    /// Stores a newly opened session and records the frames peers sent for it before, returns the session as it then is.
+    /// `None` if a session with the same id exists.
+    pub async fn open(db: &SessionRepository, router: Option<&SessionRouter>, session: Session) -> Result<Option<Session>, SessionError> {
+        if db.create_session(session.clone()).await.map_err(storageError)?.is_none() {
+            return Ok(None);
+        }
+        match router {
+            Some(router) => {
+                Self::recordHeld(db, router, &session.session_id).await;
+                db.find_session(&session.session_id).await.map_err(storageError)
+            }
+            None => Ok(Some(session)),
+        }
+    }
+
+    /// Records this node's own message for `round` of a session and sends it to every other participant. Sending it
+    /// again is harmless, so a submit that failed to reach a peer can be repeated.
+    pub async fn submit(db: &SessionRepository, router: &SessionRouter, session_id: &str, round: u32, payload: &[u8]) -> Result<(Session, MessageOutcome), SessionError> {
+        let (session, outcome) = Self::record(db, session_id, router.local(), round, payload).await?;
+        for (to, frame) in session.frames(round, payload) {
+            router.send(to, &frame).await?;
+        }
+        Ok((session, outcome))
+    }
+
+    /// Applies `step` to the stored session and writes the result back, starting over from a fresh read if another
+    /// writer stored the session in between. Nothing is written when `step` fails.
+    async fn update<T, F: FnMut(&mut Session) -> Result<T, SessionError>>(db: &SessionRepository, session_id: &str, mut step: F) -> Result<(Session, T), SessionError> {
+        for _ in 0..WRITE_ATTEMPTS {
+            let mut session = db.find_session(session_id).await.map_err(storageError)?
+                .ok_or_else(|| SessionError::Unknown(session_id.to_owned()))?;
+            let outcome = step(&mut session)?;
+            let version = session.version;
+            session.version += 1;
+            if db.update_session(&session, version).await.map_err(storageError)?.matched_count == 1 {
+                return Ok((session, outcome));
+            }
+        }
+        Err(SessionError::Failed(format!("session {} kept changing while it was written", session_id)))
+    }
+
+    /// Records the message `from` sent for `round` of a session and closes every round that is then complete.
+    pub async fn record(db: &SessionRepository, session_id: &str, from: u8, round: u32, payload: &[u8]) -> Result<(Session, MessageOutcome), SessionError> {
+        let (session, outcome) = Self::update(db, session_id, |session| {
+            let now = nowMillis();
+            if session.expire(now) {
+                return Ok(None);
+            }
+            let outcome = session.accept(from, round, payload)?;
+            session.advance(now);
+            Ok(Some(outcome))
+        }).await?;
+        match outcome {
+            Some(outcome) => Ok((session, outcome)),
+            None => Err(SessionError::Failed(session.abort_reason.unwrap_or_default())),
+        }
+    }
+
+    pub async fn abort(db: &SessionRepository, session_id: &str, reason: &str) -> Result<Session, SessionError> {
+        let (session, _) = Self::update(db, session_id, |session| {
+            session.abort(reason);
+            Ok(())
+        }).await?;
+        Ok(session)
+    }
+
+    /// Aborts every running session whose round is past its deadline, returns how many were aborted.
+    pub async fn expireSessions(db: &SessionRepository) -> Result<usize, SessionError> {
+        let mut expired = 0;
+        let now = nowMillis();
+        for session in db.find_running_sessions().await.map_err(storageError)? {
+            if session.clone().expire(now) {
+                let (_, aborted) = Self::update(db, &session.session_id, |session| Ok(session.expire(now))).await?;
+                expired += aborted as usize;
+            }
+        }
+        Ok(expired)
+    }
+
+    /// Decides on startup what happens to every session the node was running when it stopped, see `Session::resume`.
+    /// Returns how many sessions were resumed and how many aborted.
+    pub async fn recoverSessions(db: &SessionRepository) -> Result<(usize, usize), SessionError> {
+        let (mut resumed, mut aborted) = (0, 0);
+        let now = nowMillis();
+        for session in db.find_running_sessions().await.map_err(storageError)? {
+            let (_, running) = Self::update(db, &session.session_id, |session| Ok(session.resume(now))).await?;
+            if running {
+                resumed += 1;
+            } else {
+                aborted += 1;
+            }
+        }
+        Ok((resumed, aborted))
+    }
+
+    /// Records a frame a peer sent over the transport in the session it belongs to, or hands it to the session if that
+    /// runs in this process. A frame of a session this node has not opened yet is kept by `router` and recorded once the
+    /// session is opened.
+    pub async fn receiveFrame(db: &SessionRepository, router: &SessionRouter, from: u8, bytes: &[u8]) -> Result<(), SessionError> {
+        let frame = match router.deliver(from, SessionFrame::fromBytes(bytes)?).await {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+        let session_id = frame.session_id.clone();
+        match Self::record(db, &session_id, from, frame.round, &frame.payloadBytes()?).await {
+            Err(SessionError::Unknown(_)) => {
+                if !router.hold(from, frame, nowMillis()) {
+                    return Err(SessionError::Failed(format!("too many frames wait for sessions that are not open, dropped one of {}", session_id)));
+                }
+                // The session may have been opened since the frame missed it, in which case nobody else takes it.
+                if db.find_session(&session_id).await.map_err(storageError)?.is_some() {
+                    Self::recordHeld(db, router, &session_id).await;
+                }
+                Ok(())
+            }
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Records the frames `router` kept for a session before it was opened.
+    async fn recordHeld(db: &SessionRepository, router: &SessionRouter, session_id: &str) {
+        for (from, frame) in router.take(session_id) {
+            let recorded = match frame.payloadBytes() {
+                Ok(payload) => Self::record(db, session_id, from, frame.round, &payload).await.map(|_| ()),
+                Err(err) => Err(err),
+            };
+            if let Err(err) = recorded {
+                log::warn!("Dropped an early frame from node {}: {}", from, err);
+            }
+        }
+    }
+
+    /// Feeds every frame arriving on `transport` into its session and times out stale rounds, until the transport stops.
+    pub async fn run<T: Transport>(mut transport: T, db: Data<SessionRepository>, router: SessionRouter) {
+        let mut sweep = interval(SWEEP_INTERVAL);
+        loop {
+            tokio::select! {
+                received = transport.receive() => match received {
+                    Ok((from, bytes)) => if let Err(err) = Self::receiveFrame(&db, &router, from, &bytes).await {
+                        log::warn!("Dropped a frame from node {}: {}", from, err);
+                    },
+                    Err(err) => {
+                        log::error!("{}, no more session messages are received", err);
+                        return Self::watch(db).await;
+                    }
+                },
+                _ = sweep.tick() => Self::sweep(&db).await,
+            }
+        }
+    }
+
+    /// Times out stale rounds, for nodes without a transport.
+    pub async fn watch(db: Data<SessionRepository>) {
+        let mut sweep = interval(SWEEP_INTERVAL);
+        loop {
+            sweep.tick().await;
+            Self::sweep(&db).await;
+        }
+    }
+
+    async fn sweep(db: &SessionRepository) {
+        match Self::expireSessions(db).await {
+            Ok(0) => (),
+            Ok(expired) => log::warn!("Aborted {} protocol sessions that timed out", expired),
+            Err(err) => log::error!("Checking protocol sessions for timeouts failed: {}", err),
+        }
+    }
+}
\ No newline at end of file
@@ -1,2 +1,6 @@
 pub mod WalletService;
-pub mod SecretService;
\ No newline at end of file
+pub mod SecretService;
+pub mod ServiceError;
+pub mod MigrationService;
+pub mod ClusterService;
+pub mod SessionService;
\ No newline at end of file
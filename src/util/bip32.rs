@@ -0,0 +1,418 @@
+use std::{fmt, str::FromStr};
+
+use base58::{FromBase58, ToBase58};
+use hmac::{Hmac, Mac};
+use k256::{elliptic_curve::sec1::ToEncodedPoint, ProjectivePoint};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256, Sha512};
+
+use super::{feldman::FeldmanVSS, field::{Field, FieldElement}, secp256k1};
+
+/// Child indices from this one on are hardened, their derivation needs the parent private key.
+pub const HARDENED: u32 = 1 << 31;
+
+/// Domain separation of the chain code of a jointly generated seed key.
+const JOINT_ROOT_TAG: &[u8] = b"rust_mpc/bip32-joint-root";
+
+/// Version bytes of a mainnet `xpub`.
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+
+/// A BIP-32 derivation step is not possible, the string says which.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DerivationError(pub String);
+
+impl fmt::Display for DerivationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Key derivation failed: {}", self.0)
+    }
+}
+
+/// A BIP-32 path such as `m/84'/0'/0'/0/0`, hardened steps marked with `'` or `h`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath(pub Vec<u32>);
+
+#[allow(dead_code)]
+impl DerivationPath {
+    pub const ETHEREUM: &'static str = "m/44'/60'/0'/0/0";
+    pub const SEGWIT: &'static str = "m/84'/0'/0'/0/0";
+    pub const TAPROOT: &'static str = "m/86'/0'/0'/0/0";
+    /// Non-hardened counterparts of the first BIP-44, BIP-84 and BIP-86 accounts, for seeds only ever held as shares.
+    pub const JOINT_ETHEREUM: &'static str = "m/44/60/0/0/0";
+    pub const JOINT_SEGWIT: &'static str = "m/84/0/0/0/0";
+    pub const JOINT_TAPROOT: &'static str = "m/86/0/0/0/0";
+
+    /// `m / purpose' / coin_type' / account' / change / address_index`, the layout BIP-44, BIP-84 and BIP-86 share.
+    pub fn account(purpose: u32, coin_type: u32, account: u32, change: u32, address_index: u32) -> Self {
+        DerivationPath(vec![purpose | HARDENED, coin_type | HARDENED, account | HARDENED, change, address_index])
+    }
+
+    pub fn purpose(&self) -> Option<u32> {
+        self.0.first().map(|index| index & !HARDENED)
+    }
+
+    pub fn coinType(&self) -> Option<u32> {
+        self.0.get(1).map(|index| index & !HARDENED)
+    }
+
+    /// The path one step up, e.g. the change level `m/84'/0'/0'/0` of an address path. `None` for `m` itself.
+    pub fn parent(&self) -> Option<Self> {
+        self.0.split_last().map(|(_, parent)| DerivationPath(parent.to_vec()))
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = DerivationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut steps = s.split('/');
+        if steps.next() != Some("m") {
+            return Err(DerivationError(format!("{} does not start at m", s)));
+        }
+        steps.map(|step| {
+            let (number, hardened) = match step.strip_suffix(['\'', 'h', 'H']) {
+                Some(number) => (number, HARDENED),
+                None => (step, 0),
+            };
+            match number.parse::<u32>() {
+                Ok(index) if index < HARDENED => Ok(index | hardened),
+                _ => Err(DerivationError(format!("{} is not a child index", step))),
+            }
+        }).collect::<Result<Vec<u32>, DerivationError>>().map(DerivationPath)
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in self.0.iter() {
+            if index & HARDENED != 0 {
+                write!(f, "/{}'", index & !HARDENED)?;
+            } else {
+                write!(f, "/{}", index)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A BIP-32 extended private key over secp256k1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendedPrivateKey {
+    pub key: FieldElement,
+    pub chain_code: [u8; 32],
+    pub depth: u8,
+    pub parent_fingerprint: [u8; 4],
+    pub child_number: u32,
+}
+
+/// A BIP-32 extended public key, enough to derive every non-hardened descendant's public key.
+#[derive(Debug, Clone, PartialEq)]
+/// It is written as the Base58Check `xpub` string of BIP-32.
+pub struct ExtendedPublicKey {
+    pub point: ProjectivePoint,
+    pub chain_code: [u8; 32],
+    pub depth: u8,
+    /// First 4 bytes of the parent key's `HASH160`, zero for a master key.
+    pub parent_fingerprint: [u8; 4],
+    /// Index this key was derived at, zero for a master key.
+    pub child_number: u32,
+}
+
+/// `HMAC-SHA512(key, data)` split into its left half, as a scalar, and its right half, the child chain code.
+/// Fails in the negligible case where the left half is not a valid scalar.
+fn hmacSplit(key: &[u8], data: &[u8]) -> Result<(FieldElement, [u8; 32]), DerivationError> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    let output = mac.finalize().into_bytes();
+    let tweak = FieldElement::from_bytes(&output[..32].try_into().unwrap())
+        .ok_or_else(|| DerivationError("derived scalar is not lower than the group order".to_string()))?;
+    Ok((tweak, output[32..].try_into().unwrap()))
+}
+
+fn compressed(point: &ProjectivePoint) -> Vec<u8> {
+    point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+}
+
+/// The 4 byte identifier of a key its children record, the start of `RIPEMD160(SHA256(compressed key))`.
+fn fingerprint(point: &ProjectivePoint) -> [u8; 4] {
+    let hash = Ripemd160::digest(Sha256::digest(compressed(point)));
+    hash[..4].try_into().unwrap()
+}
+
+fn checksum(payload: &[u8]) -> [u8; 4] {
+    Sha256::digest(Sha256::digest(payload))[..4].try_into().unwrap()
+}
+
+#[allow(dead_code)]
+impl ExtendedPrivateKey {
+    /// The master key of a BIP-39 (or any 16 to 64 byte) seed.
+    pub fn fromSeed(seed: &[u8]) -> Result<Self, DerivationError> {
+        let (key, chain_code) = hmacSplit(b"Bitcoin seed", seed)?;
+        if key.is_zero() {
+            return Err(DerivationError("master key is zero".to_string()));
+        }
+        Ok(ExtendedPrivateKey { key, chain_code, depth: 0, parent_fingerprint: [0; 4], child_number: 0 })
+    }
+
+    /// CKDpriv: hardened children hash the private key, the others the compressed public key.
+    pub fn deriveChild(&self, index: u32) -> Result<Self, DerivationError> {
+        let data = if index & HARDENED != 0 {
+            [&[0u8][..], &self.key.to_bytes()].concat()
+        } else {
+            compressed(&secp256k1::mul_base(&self.key))
+        };
+        let (tweak, chain_code) = hmacSplit(&self.chain_code, &[data, index.to_be_bytes().to_vec()].concat())?;
+        let key = tweak + self.key;
+        if key.is_zero() {
+            return Err(DerivationError(format!("child {} is zero", index)));
+        }
+        Ok(ExtendedPrivateKey { key, chain_code, depth: self.depth + 1, parent_fingerprint: fingerprint(&secp256k1::mul_base(&self.key)), child_number: index })
+    }
+
+    pub fn derivePath(&self, path: &DerivationPath) -> Result<Self, DerivationError> {
+        path.0.iter().try_fold(self.clone(), |key, index| key.deriveChild(*index))
+    }
+
+    pub fn publicKey(&self) -> ExtendedPublicKey {
+        ExtendedPublicKey {
+            point: secp256k1::mul_base(&self.key),
+            chain_code: self.chain_code,
+            depth: self.depth,
+            parent_fingerprint: self.parent_fingerprint,
+            child_number: self.child_number,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl ExtendedPublicKey {
+    /// The root of a seed key generated jointly, which has no BIP-39 seed to take a chain code from. The chain code is
+    /// hashed from the key itself, so every holder arrives at the same root without another round.
+    pub fn jointRoot(point: ProjectivePoint) -> Self {
+        let chain_code = Sha256::new().chain_update(JOINT_ROOT_TAG).chain_update(compressed(&point)).finalize().into();
+        ExtendedPublicKey { point, chain_code, depth: 0, parent_fingerprint: [0; 4], child_number: 0 }
+    }
+
+    /// The left half of CKDpub's HMAC for `index`, i.e. what gets added to the parent key, with the child chain code.
+    /// Only non-hardened children can be derived from public data.
+    pub fn childTweak(&self, index: u32) -> Result<(FieldElement, [u8; 32]), DerivationError> {
+        if index & HARDENED != 0 {
+            return Err(DerivationError(format!("hardened child {}' needs the private key", index & !HARDENED)));
+        }
+        hmacSplit(&self.chain_code, &[compressed(&self.point), index.to_be_bytes().to_vec()].concat())
+    }
+
+    /// CKDpub: `K_i = K + IL * G`.
+    pub fn deriveChild(&self, index: u32) -> Result<Self, DerivationError> {
+        let (tweak, chain_code) = self.childTweak(index)?;
+        let point = self.point + secp256k1::mul_base(&tweak);
+        if point == ProjectivePoint::IDENTITY {
+            return Err(DerivationError(format!("child {} is the point at infinity", index)));
+        }
+        Ok(ExtendedPublicKey { point, chain_code, depth: self.depth + 1, parent_fingerprint: fingerprint(&self.point), child_number: index })
+    }
+
+    pub fn derivePath(&self, path: &DerivationPath) -> Result<Self, DerivationError> {
+        path.0.iter().try_fold(self.clone(), |key, index| key.deriveChild(*index))
+    }
+}
+
+impl fmt::Display for ExtendedPublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let payload = [
+            &XPUB_VERSION[..],
+            &[self.depth],
+            &self.parent_fingerprint,
+            &self.child_number.to_be_bytes(),
+            &self.chain_code,
+            &compressed(&self.point),
+        ].concat();
+        write!(f, "{}", [payload.clone(), checksum(&payload).to_vec()].concat().to_base58())
+    }
+}
+
+impl FromStr for ExtendedPublicKey {
+    type Err = DerivationError;
+
+    /// Parses a Base58Check `xpub`, checking its checksum, version and key.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || DerivationError(format!("{} is not an xpub", s));
+        let bytes = s.from_base58().map_err(|_| invalid())?;
+        if bytes.len() != 82 || checksum(&bytes[..78]) != bytes[78..] || bytes[..4] != XPUB_VERSION {
+            return Err(invalid());
+        }
+        let point = secp256k1::point_from_hex(&hex::encode(&bytes[45..78])).ok_or_else(invalid)?;
+        Ok(ExtendedPublicKey {
+            point,
+            chain_code: bytes[13..45].try_into().unwrap(),
+            depth: bytes[4],
+            parent_fingerprint: bytes[5..9].try_into().unwrap(),
+            child_number: u32::from_be_bytes(bytes[9..13].try_into().unwrap()),
+        })
+    }
+}
+
+/// A non-hardened child of a Shamir shared key: the holders' shares of the child key and the matching commitments.
+pub struct DerivedShares {
+    pub public_key: ExtendedPublicKey,
+    pub shares: Vec<Vec<FieldElement>>,
+    pub commitments: Vec<ProjectivePoint>,
+}
+
+/// Non-hardened BIP-32 derivation on a key held as Shamir shares. The child key is `k + IL`, where `IL` only depends on
+/// public data, so every holder adds `IL` to its own share and the constant commitment moves by `IL * G`. The key is
+/// never reconstructed. Hardened children hash the private key itself and can not be derived this way.
+pub struct ThresholdDerivation;
+
+#[allow(dead_code)]
+impl ThresholdDerivation {
+    /// Derives child `index` of the key `parent` shared by `shares` (`[x, y, ...]`) and committed to by `commitments`.
+    pub fn deriveChild(parent: &ExtendedPublicKey, index: u32, shares: &[Vec<FieldElement>], commitments: &[ProjectivePoint]) -> Result<DerivedShares, DerivationError> {
+        if commitments.first() != Some(&parent.point) {
+            return Err(DerivationError("the commitments are not to the parent key".to_string()));
+        }
+        let (tweak, _) = parent.childTweak(index)?;
+        let public_key = parent.deriveChild(index)?;
+        let shares = shares.iter()
+            .map(|share| {
+                let mut child = share.clone();
+                child[1] = child[1] + tweak;
+                child
+            })
+            .collect();
+        let mut child_commitments = commitments.to_vec();
+        child_commitments[0] = public_key.point;
+        Ok(DerivedShares { public_key, shares, commitments: child_commitments })
+    }
+
+    /// Derives the descendant of `root` at `path`, every step of which must be non-hardened, one child at a time.
+    pub fn derivePath(root: &ExtendedPublicKey, path: &DerivationPath, shares: &[Vec<FieldElement>], commitments: &[ProjectivePoint]) -> Result<DerivedShares, DerivationError> {
+        let start = DerivedShares { public_key: root.clone(), shares: shares.to_vec(), commitments: commitments.to_vec() };
+        path.0.iter().try_fold(start, |parent, index| Self::deriveChild(&parent.public_key, *index, &parent.shares, &parent.commitments))
+    }
+
+    /// Derives child `index` of `parent` on the shares of its child `own_index`, e.g. another address of the change level
+    /// a wallet key sits on. The shares are first moved back by `own_index`'s public tweak to shares of `parent`.
+    pub fn deriveSibling(parent: &ExtendedPublicKey, own_index: u32, index: u32, shares: &[Vec<FieldElement>], commitments: &[ProjectivePoint]) -> Result<DerivedShares, DerivationError> {
+        if commitments.first() != Some(&parent.deriveChild(own_index)?.point) {
+            return Err(DerivationError("the key is not a child of the xpub".to_string()));
+        }
+        let (tweak, _) = parent.childTweak(own_index)?;
+        let parent_shares: Vec<Vec<FieldElement>> = shares.iter()
+            .map(|share| {
+                let mut parent_share = share.clone();
+                parent_share[1] = parent_share[1] - tweak;
+                parent_share
+            })
+            .collect();
+        let mut parent_commitments = commitments.to_vec();
+        parent_commitments[0] = parent.point;
+        Self::deriveChild(parent, index, &parent_shares, &parent_commitments)
+    }
+
+    /// A holder's check that its child share matches the child commitments, the same Feldman check as for any share.
+    pub fn verifyShare(share: &[FieldElement], derived: &DerivedShares) -> bool {
+        share.len() >= 2 && FeldmanVSS::verify(&share[0], &share[1], &derived.commitments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{bip39::Bip39, shamir::ShamirAlgorithm};
+
+    fn shareKey(key: FieldElement) -> (Vec<Vec<FieldElement>>, Vec<ProjectivePoint>) {
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(key);
+        let shares = (1..=5).map(|x| vec![FieldElement::from(x), polynomial.evaluate_at(FieldElement::from(x))]).collect();
+        (shares, FeldmanVSS::commit(&polynomial))
+    }
+
+    #[test]
+    fn test_vector_one() {
+        let master = ExtendedPrivateKey::fromSeed(&hex::decode("000102030405060708090a0b0c0d0e0f").unwrap()).unwrap();
+        assert_eq!(hex::encode(master.chain_code), "873dff81c02f525623fd1fe5167eac3a55a049de3d314bb42ee227ffed37d508");
+        assert_eq!(master.key.to_hex(), "e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35");
+
+        let child = master.derivePath(&"m/0'/1".parse().unwrap()).unwrap();
+        assert_eq!(hex::encode(child.chain_code), "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19");
+        assert_eq!(child.key.to_hex(), "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368");
+
+        assert_eq!(master.publicKey().to_string(), "xpub661MyMwAqRbcFtXgS5sYJABqqG9YLmC4Q1Rdap9gSE8NqtwybGhePY2gZ29ESFjqJoCu1Rupje8YtGqsefD265TMg7usUDFdp6W1EGMcet8");
+        assert_eq!(child.publicKey().to_string(), "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ");
+
+        let hardened = master.deriveChild(HARDENED).unwrap();
+        assert_eq!(hardened.publicKey().deriveChild(1).unwrap(), child.publicKey());
+        assert!(hardened.publicKey().deriveChild(HARDENED).is_err());
+    }
+
+    #[test]
+    fn test_ethereum_account_of_mnemonic() {
+        let seed = Bip39::toSeed("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about", "");
+        let key = ExtendedPrivateKey::fromSeed(&seed).unwrap().derivePath(&DerivationPath::ETHEREUM.parse().unwrap()).unwrap();
+        assert_eq!(key.key.to_hex(), "1ab42cc412b618bdea3a599e3c9bae199ebf030895b039e9db1e30dafb12b727");
+    }
+
+    #[test]
+    fn test_xpub_round_trip() {
+        let xpub = "xpub6ASuArnXKPbfEwhqN6e3mwBcDTgzisQN1wXN9BJcM47sSikHjJf3UFHKkNAWbWMiGj7Wf5uMash7SyYq527Hqck2AxYysAA7xmALppuCkwQ";
+        let key: ExtendedPublicKey = xpub.parse().unwrap();
+        assert_eq!(key.depth, 2);
+        assert_eq!(key.child_number, 1);
+        assert_eq!(key.to_string(), xpub);
+        assert!(xpub.replace('Q', "R").parse::<ExtendedPublicKey>().is_err());
+        assert!("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi".parse::<ExtendedPublicKey>().is_err());
+    }
+
+    #[test]
+    fn test_path_round_trip() {
+        let path: DerivationPath = "m/86h/0'/3'/1/7".parse().unwrap();
+        assert_eq!(path, DerivationPath::account(86, 0, 3, 1, 7));
+        assert_eq!(path.to_string(), "m/86'/0'/3'/1/7");
+        assert!("44'/0'".parse::<DerivationPath>().is_err());
+        assert!("m/2147483648".parse::<DerivationPath>().is_err());
+    }
+
+    #[test]
+    fn test_threshold_derivation_matches_private_derivation() {
+        let parent = ExtendedPrivateKey::fromSeed(&[7u8; 32]).unwrap().derivePath(&"m/84'/0'/0'/0".parse().unwrap()).unwrap();
+        let (shares, commitments) = shareKey(parent.key);
+
+        let derived = ThresholdDerivation::deriveChild(&parent.publicKey(), 9, &shares, &commitments).unwrap();
+        let child = parent.deriveChild(9).unwrap();
+        assert_eq!(derived.public_key, child.publicKey());
+        assert_eq!(ShamirAlgorithm::new(Some(2)).combine(&derived.shares[1..4]), Ok(child.key));
+        assert!(derived.shares.iter().all(|share| ThresholdDerivation::verifyShare(share, &derived)));
+        assert!(ThresholdDerivation::deriveChild(&parent.publicKey(), HARDENED | 9, &shares, &commitments).is_err());
+        assert_eq!(ThresholdDerivation::deriveChild(&child.publicKey(), 0, &shares, &commitments).err(), Some(DerivationError("the commitments are not to the parent key".to_string())));
+    }
+
+    #[test]
+    fn test_path_derivation_below_joint_root() {
+        let secret = FieldElement::random();
+        let (shares, commitments) = shareKey(secret);
+        let root = ExtendedPublicKey::jointRoot(commitments[0]);
+        assert_eq!(root, ExtendedPublicKey::jointRoot(secp256k1::mul_base(&secret)));
+
+        let path: DerivationPath = DerivationPath::JOINT_SEGWIT.parse().unwrap();
+        let derived = ThresholdDerivation::derivePath(&root, &path, &shares, &commitments).unwrap();
+        let private = ExtendedPrivateKey { key: secret, chain_code: root.chain_code, depth: 0, parent_fingerprint: [0; 4], child_number: 0 }.derivePath(&path).unwrap();
+        assert_eq!(derived.public_key, private.publicKey());
+        assert_eq!(derived.public_key, root.derivePath(&path).unwrap());
+        assert_eq!(ShamirAlgorithm::new(Some(2)).combine(&derived.shares[2..]), Ok(private.key));
+        assert!(ThresholdDerivation::derivePath(&root, &DerivationPath::SEGWIT.parse().unwrap(), &shares, &commitments).is_err());
+    }
+
+    #[test]
+    fn test_sibling_derivation_matches_private_derivation() {
+        let change = ExtendedPrivateKey::fromSeed(&[7u8; 32]).unwrap().derivePath(&"m/44'/60'/0'/0".parse().unwrap()).unwrap();
+        let wallet = change.deriveChild(0).unwrap();
+        let (shares, commitments) = shareKey(wallet.key);
+
+        let derived = ThresholdDerivation::deriveSibling(&change.publicKey(), 0, 4, &shares, &commitments).unwrap();
+        let address = change.deriveChild(4).unwrap();
+        assert_eq!(derived.public_key, address.publicKey());
+        assert_eq!(ShamirAlgorithm::new(Some(2)).combine(&derived.shares[..3]), Ok(address.key));
+        assert!(derived.shares.iter().all(|share| ThresholdDerivation::verifyShare(share, &derived)));
+        assert!(ThresholdDerivation::deriveSibling(&change.publicKey(), 1, 4, &shares, &commitments).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use std::fmt;
+
+use hmac::Hmac;
+use sha2::{Digest, Sha256, Sha512};
+
+use super::bip39_wordlist::ENGLISH;
+
+const PBKDF2_ROUNDS: u32 = 2048;
+
+/// A mnemonic, or the entropy it is made from, can not be decoded or does not fit with the others, the string says why.
+#[derive(Debug, PartialEq, Eq)]
+pub struct MnemonicError(pub String);
+
+impl fmt::Display for MnemonicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid mnemonic: {}", self.0)
+    }
+}
+
+/// BIP-39 mnemonic codes: entropy of 128 to 256 bits, a checksum of `ENT / 32` bits from its SHA-256, and 11 bits per word.
+pub struct Bip39;
+
+#[allow(dead_code)]
+impl Bip39 {
+    /// Encodes 16, 20, 24, 28 or 32 bytes of entropy as 12 to 24 English words.
+    pub fn entropyToMnemonic(entropy: &[u8]) -> Result<String, MnemonicError> {
+        if !(16..=32).contains(&entropy.len()) || !entropy.len().is_multiple_of(4) {
+            return Err(MnemonicError("entropy must be 16, 20, 24, 28 or 32 bytes".to_string()));
+        }
+        let checksum = Sha256::digest(entropy);
+        let bits = entropy.len() * 8 + entropy.len() / 4;
+        let bit = |i: usize| {
+            let byte = if i < entropy.len() * 8 { entropy[i / 8] } else { checksum[i / 8 - entropy.len()] };
+            (byte >> (7 - i % 8) & 1) as usize
+        };
+        Ok((0..bits / 11)
+            .map(|word| ENGLISH[(0..11).fold(0, |index, j| index << 1 | bit(word * 11 + j))])
+            .collect::<Vec<&str>>()
+            .join(" "))
+    }
+
+    /// Decodes a mnemonic back into its entropy, checking the words and the checksum.
+    pub fn mnemonicToEntropy(mnemonic: &str) -> Result<Vec<u8>, MnemonicError> {
+        let indices = mnemonic.split_whitespace()
+            .map(|word| ENGLISH.binary_search(&word).map_err(|_| MnemonicError(format!("{} is not in the word list", word))))
+            .collect::<Result<Vec<usize>, MnemonicError>>()?;
+        if !(12..=24).contains(&indices.len()) || !indices.len().is_multiple_of(3) {
+            return Err(MnemonicError(format!("{} words is not a valid length", indices.len())));
+        }
+        let bit = |i: usize| (indices[i / 11] >> (10 - i % 11) & 1) as u8;
+        let entropy: Vec<u8> = (0..indices.len() * 11 / 33 * 4)
+            .map(|byte| (0..8).fold(0, |value, j| value << 1 | bit(byte * 8 + j)))
+            .collect();
+        if Self::entropyToMnemonic(&entropy)?.split_whitespace().ne(mnemonic.split_whitespace()) {
+            return Err(MnemonicError("checksum does not match".to_string()));
+        }
+        Ok(entropy)
+    }
+
+    /// The 64 byte seed BIP-32 master keys are made from, PBKDF2-HMAC-SHA512 of the mnemonic salted with the passphrase.
+    pub fn toSeed(mnemonic: &str, passphrase: &str) -> [u8; 64] {
+        let mut seed = [0u8; 64];
+        pbkdf2::pbkdf2::<Hmac<Sha512>>(mnemonic.as_bytes(), format!("mnemonic{}", passphrase).as_bytes(), PBKDF2_ROUNDS, &mut seed);
+        seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trezor_vectors() {
+        let vectors = [
+            ("00000000000000000000000000000000", "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"),
+            ("80808080808080808080808080808080", "letter advice cage absurd amount doctor acoustic avoid letter advice cage above"),
+            ("9e885d952ad362caeb4efe34a8e91bd2", "ozone drill grab fiber curtain grace pudding thank cruise elder eight picnic"),
+            ("6610b25967cdcca9d59875f5cb50b0ea75433311869e930b", "gravity machine north sort system female filter attitude volume fold club stay feature office ecology stable narrow fog"),
+            ("f585c11aec520db57dd353c69554b21a89b20fb0650966fa0a9d6f74fd989d8f", "void come effort suffer camp survey warrior heavy shoot primary clutch crush open amazing screen patrol group space point ten exist slush involve unfold"),
+        ];
+        for (entropy, mnemonic) in vectors {
+            assert_eq!(Bip39::entropyToMnemonic(&hex::decode(entropy).unwrap()).unwrap(), mnemonic);
+            assert_eq!(Bip39::mnemonicToEntropy(mnemonic).unwrap(), hex::decode(entropy).unwrap());
+        }
+        assert_eq!(Bip39::mnemonicToEntropy("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon").err(),
+            Some(MnemonicError("checksum does not match".to_string())));
+    }
+
+    #[test]
+    fn test_seed() {
+        let seed = Bip39::toSeed("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about", "");
+        assert_eq!(hex::encode(&seed[..16]), "5eb00bbddcf069084889a8ab91555681");
+    }
+}
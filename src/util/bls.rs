@@ -0,0 +1,343 @@
+use std::fmt;
+
+use bigdecimal::num_bigint::BigUint;
+use bls12_381::{hash_to_curve::{ExpandMsgXmd, HashToCurve}, pairing, G1Affine, G1Projective, G2Affine, G2Projective, Scalar};
+use lazy_static::lazy_static;
+use sha2_09::Sha256;
+use tokio::time::{timeout_at, Duration, Instant};
+
+use super::{dkg::DkgGroup, field::Field, modular::{ModInt, Modulus}, polynomials::Polynomial, session::SessionError, shamir::{ShamirAlgorithm, ShamirError}, transport::{InMemoryTransport, Transport, TransportError}};
+
+pub const CURVE: &str = "bls12_381";
+
+lazy_static! {
+    /// `r`, the order of the BLS12-381 groups.
+    static ref BLS12_381_ORDER: BigUint = "52435875175126190479447740508185965837690552500527637822603658699938581184513".parse().unwrap();
+    /// Second generator in G1 for Pedersen commitments to BLS keys, hashed to the curve so nobody knows its discrete logarithm.
+    pub static ref BLS_PEDERSEN_H: G1Projective = <G1Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(b"rust_mpc/pedersen-vss/H", PEDERSEN_DST);
+}
+
+/// Domain separation tag of `BLS_PEDERSEN_H`, the suite `BLS12381G1_XMD:SHA-256_SSWU_RO_`.
+const PEDERSEN_DST: &[u8] = b"rust_mpc-pedersen-vss-V01-CS01-with-BLS12381G1_XMD:SHA-256_SSWU_RO_";
+/// Time a signer waits for the partial signatures of the others.
+pub const SIGNING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reasons signers that each hold one share can not sign together: too few valid partial signatures, or the channel
+/// between them failed.
+#[derive(Debug, PartialEq, Eq)]
+pub enum BlsSigningError {
+    Sharing(ShamirError),
+    Session(SessionError),
+}
+
+impl fmt::Display for BlsSigningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlsSigningError::Sharing(err) => err.fmt(f),
+            BlsSigningError::Session(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<ShamirError> for BlsSigningError {
+    fn from(err: ShamirError) -> Self {
+        BlsSigningError::Sharing(err)
+    }
+}
+
+impl From<TransportError> for BlsSigningError {
+    fn from(err: TransportError) -> Self {
+        BlsSigningError::Session(err.into())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Bls12381Order;
+
+impl Modulus for Bls12381Order {
+    fn modulus() -> &'static BigUint {
+        &BLS12_381_ORDER
+    }
+}
+
+/// Integers modulo the BLS12-381 group order, where BLS secret keys and their Shamir shares live.
+pub type BlsScalar = ModInt<Bls12381Order>;
+
+pub fn to_scalar(value: &BlsScalar) -> Scalar {
+    let mut bytes = [0u8; 32];
+    let le = value.value.to_bytes_le();
+    bytes[..le.len()].copy_from_slice(&le);
+    Scalar::from_bytes(&bytes).unwrap()
+}
+
+/// Returns `value * G1`.
+pub fn mul_base(value: &BlsScalar) -> G1Projective {
+    G1Projective::generator() * to_scalar(value)
+}
+
+/// Hex of the 48 byte compressed G1 point, the form BLS public keys are stored in.
+pub fn point_to_hex(point: &G1Projective) -> String {
+    hex::encode(G1Affine::from(point).to_compressed())
+}
+
+pub fn point_from_hex(encoded: &str) -> Option<G1Projective> {
+    let bytes: [u8; 48] = hex::decode(encoded.trim_start_matches("0x")).ok()?.try_into().ok()?;
+    Option::<G1Affine>::from(G1Affine::from_compressed(&bytes)).map(G1Projective::from)
+}
+
+pub fn signature_to_hex(signature: &G2Projective) -> String {
+    hex::encode(G2Affine::from(signature).to_compressed())
+}
+
+pub fn signature_from_bytes(bytes: &[u8]) -> Option<G2Projective> {
+    let bytes: [u8; 96] = bytes.try_into().ok()?;
+    Option::<G2Affine>::from(G2Affine::from_compressed(&bytes)).map(G2Projective::from)
+}
+
+/// Domain separation tag of the basic BLS signature scheme with public keys in G1: the ciphersuite
+/// `BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_` followed by the scheme's `NUL_` tag.
+pub const SIGNATURE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// Hashes a message into G2 with the IETF `hash_to_curve` suite `BLS12381G2_XMD:SHA-256_SSWU_RO_` under
+/// `SIGNATURE_DST`, so signatures verify with any other implementation of the ciphersuite.
+pub fn hashToG2(message: &[u8]) -> G2Projective {
+    hashToG2With(message, SIGNATURE_DST)
+}
+
+fn hashToG2With(message: &[u8], dst: &[u8]) -> G2Projective {
+    <G2Projective as HashToCurve<ExpandMsgXmd<Sha256>>>::hash_to_curve(message, dst)
+}
+
+/// Feldman verifiable secret sharing in G1, the counterpart of `FeldmanVSS` for BLS keys.
+pub struct BlsFeldmanVSS;
+
+#[allow(dead_code)]
+impl BlsFeldmanVSS {
+    pub const SCHEME: &'static str = "feldman-bls12_381";
+
+    pub fn commit(polynomial: &Polynomial<BlsScalar>) -> Vec<G1Projective> {
+        polynomial.coefficients.iter().map(mul_base).collect()
+    }
+
+    pub fn evaluateCommitments(commitments: &[G1Projective], x: &BlsScalar) -> G1Projective {
+        let x = to_scalar(x);
+        commitments.iter().rev().fold(G1Projective::identity(), |sum, commitment| sum * x + commitment)
+    }
+
+    pub fn verify(x: &BlsScalar, y: &BlsScalar, commitments: &[G1Projective]) -> bool {
+        if commitments.is_empty() || x.is_zero() {
+            return false;
+        }
+        Self::evaluateCommitments(commitments, x) == mul_base(y)
+    }
+
+    pub fn encodeCommitments(commitments: &[G1Projective]) -> Vec<String> {
+        commitments.iter().map(point_to_hex).collect()
+    }
+
+    pub fn decodeCommitments(commitments: &[String]) -> Option<Vec<G1Projective>> {
+        commitments.iter().map(|c| point_from_hex(c)).collect()
+    }
+}
+
+impl DkgGroup for BlsFeldmanVSS {
+    type Scalar = BlsScalar;
+    type Point = G1Projective;
+
+    fn mulBase(value: &BlsScalar) -> G1Projective {
+        mul_base(value)
+    }
+
+    fn blindingBase() -> G1Projective {
+        *BLS_PEDERSEN_H
+    }
+
+    fn mul(point: &G1Projective, value: &BlsScalar) -> G1Projective {
+        point * to_scalar(value)
+    }
+
+    fn add(a: &G1Projective, b: &G1Projective) -> G1Projective {
+        a + b
+    }
+
+    fn identity() -> G1Projective {
+        G1Projective::identity()
+    }
+
+    fn encodePoint(point: &G1Projective) -> String {
+        point_to_hex(point)
+    }
+
+    fn decodePoint(encoded: &str) -> Option<G1Projective> {
+        point_from_hex(encoded)
+    }
+}
+
+/// Threshold BLS signatures (Boldyreva) on BLS12-381 with public keys in G1 and signatures in G2. Every holder signs
+/// alone with its share, `sigma_i = y_i * H(m)`, and any `t + 1` valid partial signatures interpolate to the signature
+/// of the shared key. There is a single round and no nonces.
+pub struct ThresholdBls;
+
+#[allow(dead_code)]
+impl ThresholdBls {
+    pub fn partialSign(share: &[BlsScalar], message: &[u8]) -> G2Projective {
+        hashToG2(message) * to_scalar(&share[1])
+    }
+
+    /// Checks `e(G1, sigma_i) == e(Y_i, H(m))` with the holder's public share `Y_i` taken from the commitments.
+    pub fn verifyPartial(x: &BlsScalar, partial: &G2Projective, commitments: &[G1Projective], message: &[u8]) -> bool {
+        let public_share = BlsFeldmanVSS::evaluateCommitments(commitments, x);
+        Self::verifyWith(&public_share, &hashToG2(message), partial)
+    }
+
+    pub fn verify(public_key: &G1Projective, message: &[u8], signature: &G2Projective) -> bool {
+        Self::verifyWith(public_key, &hashToG2(message), signature)
+    }
+
+    fn verifyWith(public_key: &G1Projective, hashed: &G2Projective, signature: &G2Projective) -> bool {
+        pairing(&G1Affine::generator(), &G2Affine::from(signature)) == pairing(&G1Affine::from(public_key), &G2Affine::from(hashed))
+    }
+
+    /// Signs `message` as the holder of `share` among the other nodes of `transport`: sends its partial signature to
+    /// every other signer and combines once `degree + 1` valid ones, its own included, are in or `timeout` passed. Every
+    /// signer only touches its own share.
+    pub async fn signParty<T: Transport + Sync>(degree: u8, share: &[BlsScalar], commitments: &[G1Projective], message: &[u8], transport: &mut T, timeout: Duration) -> Result<G2Projective, BlsSigningError> {
+        let own = Self::partialSign(share, message);
+        transport.broadcast(G2Affine::from(own).to_compressed().to_vec()).await?;
+        let mut partials = vec![(share[0].clone(), own)];
+        let deadline = Instant::now() + timeout;
+        while partials.len() <= degree as usize {
+            let (from, payload) = match timeout_at(deadline, transport.receive()).await {
+                Ok(received) => received?,
+                Err(_) => break,
+            };
+            let x = BlsScalar::from_u64(from as u64);
+            match signature_from_bytes(&payload) {
+                Some(partial) if Self::verifyPartial(&x, &partial, commitments, message) && !partials.iter().any(|(seen, _)| *seen == x) => partials.push((x, partial)),
+                _ => log::warn!("Dropped an invalid partial BLS signature from node {}", from),
+            }
+        }
+        Ok(Self::combine(degree, &partials, commitments, message)?)
+    }
+
+    /// Has every holder of `shares` sign on its own, each a task with its own in-memory transport, and returns the
+    /// signature the first of them combined.
+    pub async fn signLocally(degree: u8, shares: Vec<Vec<BlsScalar>>, commitments: Vec<G1Projective>, message: Vec<u8>) -> Result<G2Projective, BlsSigningError> {
+        let xs = shares.iter()
+            .enumerate()
+            .map(|(i, share)| share.first().and_then(|x| x.to_string().parse::<u8>().ok()).ok_or(ShamirError::MalformedShare(i)))
+            .collect::<Result<Vec<u8>, ShamirError>>()?;
+        let mut tasks = Vec::new();
+        for (mut transport, share) in InMemoryTransport::network(&xs).into_iter().zip(shares) {
+            let (commitments, message) = (commitments.clone(), message.clone());
+            tasks.push(tokio::spawn(async move {
+                Self::signParty(degree, &share, &commitments, &message, &mut transport, SIGNING_TIMEOUT).await
+            }));
+        }
+        let mut signatures = Vec::new();
+        for task in tasks {
+            signatures.push(task.await.map_err(|err| BlsSigningError::Session(SessionError::Failed(err.to_string())))?);
+        }
+        signatures.into_iter().next().ok_or(ShamirError::NotEnoughShares { required: degree as usize + 1, given: 0 })?
+    }
+
+    /// Drops the partial signatures that do not verify and interpolates the first `degree + 1` others.
+    pub fn combine(degree: u8, partials: &[(BlsScalar, G2Projective)], commitments: &[G1Projective], message: &[u8]) -> Result<G2Projective, ShamirError> {
+        let valid: Vec<&(BlsScalar, G2Projective)> = partials.iter()
+            .filter(|(x, partial)| Self::verifyPartial(x, partial, commitments, message))
+            .take(degree as usize + 1)
+            .collect();
+        let xs: Vec<BlsScalar> = valid.iter().map(|(x, _)| x.clone()).collect();
+        ShamirAlgorithm::new(Some(degree)).validateCoordinates(&xs)?;
+        let lagrange = ShamirAlgorithm::lagrangeCoefficients(&xs, &BlsScalar::zero())?;
+        let signature = valid.iter()
+            .zip(lagrange.iter())
+            .fold(G2Projective::identity(), |sum, ((_, partial), coefficient)| sum + partial * to_scalar(coefficient));
+        if !Self::verify(&commitments[0], message, &signature) {
+            return Err(ShamirError::InvalidSignature);
+        }
+        Ok(signature)
+    }
+
+    /// Adds up signatures of different keys into one.
+    pub fn aggregate(signatures: &[G2Projective]) -> G2Projective {
+        signatures.iter().fold(G2Projective::identity(), |sum, signature| sum + signature)
+    }
+
+    /// Checks an aggregate of signatures on pairwise distinct messages, `e(G1, sigma) == prod e(pk_i, H(m_i))`. Requiring
+    /// distinct messages rules out rogue key attacks without proofs of possession.
+    pub fn verifyAggregate(public_keys: &[G1Projective], messages: &[&[u8]], signature: &G2Projective) -> bool {
+        if public_keys.len() != messages.len() || messages.iter().enumerate().any(|(i, message)| messages[..i].contains(message)) {
+            return false;
+        }
+        let expected = public_keys.iter()
+            .zip(messages)
+            .fold(None, |sum, (public_key, message)| {
+                let term = pairing(&G1Affine::from(public_key), &G2Affine::from(hashToG2(message)));
+                Some(match sum { Some(sum) => sum + term, None => term })
+            });
+        expected == Some(pairing(&G1Affine::generator(), &G2Affine::from(signature)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::dkg::DistributedKeyGeneration;
+
+    #[test]
+    fn test_order_matches_bls12_381() {
+        assert_eq!(to_scalar(&-BlsScalar::one()) + Scalar::one(), Scalar::zero());
+        assert!(bool::from(G2Affine::from(hashToG2(b"message")).is_torsion_free()));
+    }
+
+    #[test]
+    fn test_hash_to_g2_matches_rfc_9380() {
+        // RFC 9380 J.10.1, BLS12381G2_XMD:SHA-256_SSWU_RO_: x.c1 || x.c0 || y.c1 || y.c0 of hash_to_curve("abc").
+        let hashed = hashToG2With(b"abc", b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_");
+        assert_eq!(hex::encode(G2Affine::from(hashed).to_uncompressed()), [
+            "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8",
+            "02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6",
+            "00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16",
+            "1787327b68159716a37440985269cf584bcb1e621d3a7202be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48",
+        ].concat());
+        let empty = hashToG2With(b"", b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_");
+        assert_eq!(hex::encode(&G2Affine::from(empty).to_uncompressed()[..48]), "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d");
+        assert_ne!(hashToG2(b"abc"), hashed);
+    }
+
+    #[tokio::test]
+    async fn test_any_quorum_of_partial_signatures_combines() {
+        let (shares, commitments) = DistributedKeyGeneration::localFixture::<BlsFeldmanVSS>(2, &[1, 2, 3, 4, 5]).await;
+        let message = b"attestation";
+        let mut partials: Vec<(BlsScalar, G2Projective)> = shares.iter().map(|share| (share[0].clone(), ThresholdBls::partialSign(share, message))).collect();
+
+        let signature = ThresholdBls::combine(2, &partials[2..], &commitments, message).unwrap();
+        assert_eq!(ThresholdBls::combine(2, &partials[..3], &commitments, message), Ok(signature));
+        assert!(!ThresholdBls::verify(&commitments[0], b"other", &signature));
+
+        partials[0].1 = partials[1].1;
+        assert_eq!(ThresholdBls::combine(2, &partials[..4], &commitments, message), Ok(signature));
+        assert_eq!(ThresholdBls::combine(2, &partials[..3], &commitments, message), Err(ShamirError::NotEnoughShares { required: 3, given: 2 }));
+    }
+
+    #[tokio::test]
+    async fn test_signers_sign_on_their_own() {
+        let (shares, commitments) = DistributedKeyGeneration::localFixture::<BlsFeldmanVSS>(1, &[2, 4, 6]).await;
+        let signature = ThresholdBls::signLocally(1, shares[1..].to_vec(), commitments.clone(), b"block".to_vec()).await.unwrap();
+        assert!(ThresholdBls::verify(&commitments[0], b"block", &signature));
+        assert!(ThresholdBls::signLocally(1, shares[..1].to_vec(), commitments, b"block".to_vec()).await.is_err());
+    }
+
+    #[test]
+    fn test_aggregate_signatures_of_different_keys() {
+        let keys: Vec<BlsScalar> = (0..3).map(|_| BlsScalar::random()).collect();
+        let public_keys: Vec<G1Projective> = keys.iter().map(mul_base).collect();
+        let messages: Vec<&[u8]> = vec![b"a", b"b", b"c"];
+        let signatures: Vec<G2Projective> = keys.iter().zip(messages.iter()).map(|(key, message)| hashToG2(message) * to_scalar(key)).collect();
+
+        let aggregate = ThresholdBls::aggregate(&signatures);
+        assert!(ThresholdBls::verifyAggregate(&public_keys, &messages, &aggregate));
+        assert!(!ThresholdBls::verifyAggregate(&public_keys, &[b"a", b"b", b"b"], &aggregate));
+    }
+}
@@ -0,0 +1,528 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::time::{timeout_at, Duration, Instant};
+
+use super::{session::SessionError, transport::Transport};
+
+/// Domain separation of the bytes a dealer signs its proposal over.
+const PROPOSAL_CONTEXT: &[u8] = b"node-rpc-rust broadcast proposal";
+/// Messages of rounds that have not started yet kept per sender. An honest node sends at most `2n + 1` per round.
+pub const PENDING_PER_PEER: usize = 1024;
+/// Bytes of messages of rounds that have not started yet kept, over all senders.
+pub const PENDING_BYTES: usize = 4 << 20;
+/// Time a message of a round that has not started yet is kept.
+pub const PENDING_TTL: Duration = Duration::from_secs(60);
+
+/// The message a dealer broadcasts in one instance, signed so every node can show which dealer said what.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Proposal {
+    pub instance: String,
+    pub dealer: u8,
+    /// Hex encoded payload.
+    pub payload: String,
+    /// Hex Ed25519 signature of the dealer over the instance, the dealer and the payload.
+    pub signature: String,
+}
+
+/// The three kinds of messages of Bracha's reliable broadcast.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BroadcastMessage {
+    /// The dealer hands its proposal to every node.
+    Send { proposal: Proposal },
+    /// A node passes on the proposal it got from the dealer.
+    Echo { proposal: Proposal },
+    /// A node vouches for the proposal with the hex SHA-256 `digest` of its payload.
+    Ready { instance: String, dealer: u8, digest: String },
+}
+
+/// Proof that a node sent conflicting messages.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Equivocation {
+    /// The dealer signed two different proposals for one instance; anyone holding the dealer's key can check both.
+    Dealer { dealer: u8, first: Proposal, second: Proposal },
+    /// A node sent this node two different echoes or readies for one instance. The transport authenticated the sender,
+    /// but there is no signature to show anyone else.
+    Party { party: u8, instance: String },
+}
+
+/// One instance of Bracha's reliable broadcast, as seen by one node. With `n` nodes of which at most `f < n / 3` are
+/// faulty, every honest node delivers the same payload or none does, and the payload of an honest dealer is always
+/// delivered. A node echoes the first proposal the dealer sends it, sends ready once `⌈(n + f + 1) / 2⌉` echoes or
+/// `f + 1` readies agree, and delivers once `2f + 1` readies agree.
+///
+/// Echoes carry the dealer's signed proposal, so a dealer that sends different proposals to different nodes is caught
+/// as soon as one node sees both, with both signatures as evidence.
+pub struct ReliableBroadcast {
+    instance: String,
+    dealer: u8,
+    keys: BTreeMap<u8, VerifyingKey>,
+    faulty: usize,
+    /// Every validly signed proposal seen, by digest.
+    proposals: HashMap<String, Proposal>,
+    echoes: HashMap<u8, String>,
+    readies: HashMap<u8, String>,
+    echoed: bool,
+    readied: bool,
+    delivered: Option<Vec<u8>>,
+    equivocations: Vec<Equivocation>,
+}
+
+/// What a node got out of one broadcast round, in which every participant dealt one instance.
+#[derive(Debug, Clone, Default)]
+pub struct RoundOutcome {
+    /// Payloads delivered, by dealer.
+    pub delivered: BTreeMap<u8, Vec<u8>>,
+    /// Dealers whose instance did not deliver before the round timed out.
+    pub missing: Vec<u8>,
+    pub equivocations: Vec<Equivocation>,
+}
+
+/// A message of a round that has not started yet, with when it came in and its size on the wire.
+struct PendingMessage {
+    received: Instant,
+    from: u8,
+    size: usize,
+    message: BroadcastMessage,
+}
+
+/// Runs broadcast rounds over a transport, keyed by the participants' Ed25519 keys. Messages of rounds that have not
+/// started yet are kept until they do, within `PENDING_PER_PEER` and `PENDING_BYTES` and for at most `PENDING_TTL`,
+/// messages of rounds that ended are dropped.
+pub struct BroadcastChannel<'a, T: Transport> {
+    transport: &'a mut T,
+    signing_key: SigningKey,
+    keys: BTreeMap<u8, VerifyingKey>,
+    pending: Vec<PendingMessage>,
+    pending_bytes: usize,
+    finished: HashSet<String>,
+}
+
+fn broadcastError(reason: &str) -> SessionError {
+    SessionError::Failed(reason.to_string())
+}
+
+fn digest(payload: &[u8]) -> String {
+    hex::encode(Sha256::digest(payload))
+}
+
+/// Name of the instance `dealer` deals in `round`.
+pub fn instanceName(round: &str, dealer: u8) -> String {
+    format!("{}/{}", round, dealer)
+}
+
+#[allow(dead_code)]
+impl Proposal {
+    pub fn new(signing_key: &SigningKey, instance: &str, dealer: u8, payload: &[u8]) -> Self {
+        let mut proposal = Proposal { instance: instance.to_owned(), dealer, payload: hex::encode(payload), signature: String::new() };
+        proposal.signature = hex::encode(signing_key.sign(&proposal.signedBytes()).to_bytes());
+        proposal
+    }
+
+    pub fn verify(&self, dealer_key: &VerifyingKey) -> bool {
+        let signature: Option<[u8; 64]> = hex::decode(&self.signature).ok().and_then(|bytes| bytes.try_into().ok());
+        signature.is_some_and(|signature| dealer_key.verify(&self.signedBytes(), &Signature::from_bytes(&signature)).is_ok())
+            && hex::decode(&self.payload).is_ok()
+    }
+
+    pub fn payloadBytes(&self) -> Vec<u8> {
+        hex::decode(&self.payload).expect("Verified proposals are hex")
+    }
+
+    fn signedBytes(&self) -> Vec<u8> {
+        let mut bytes = PROPOSAL_CONTEXT.to_vec();
+        for field in [self.instance.as_bytes(), &[self.dealer], self.payload.as_bytes()] {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes
+    }
+}
+
+impl BroadcastMessage {
+    pub fn instance(&self) -> &str {
+        match self {
+            BroadcastMessage::Send { proposal } | BroadcastMessage::Echo { proposal } => &proposal.instance,
+            BroadcastMessage::Ready { instance, .. } => instance,
+        }
+    }
+
+    pub fn dealer(&self) -> u8 {
+        match self {
+            BroadcastMessage::Send { proposal } | BroadcastMessage::Echo { proposal } => proposal.dealer,
+            BroadcastMessage::Ready { dealer, .. } => *dealer,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl Equivocation {
+    /// The node the equivocation is attributed to.
+    pub fn party(&self) -> u8 {
+        match self {
+            Equivocation::Dealer { dealer, .. } => *dealer,
+            Equivocation::Party { party, .. } => *party,
+        }
+    }
+
+    /// Whether dealer evidence holds up on its own: two different proposals for one instance, both signed by `dealer_key`.
+    pub fn verify(&self, dealer_key: &VerifyingKey) -> bool {
+        match self {
+            Equivocation::Dealer { dealer, first, second } => first.instance == second.instance
+                && first.dealer == *dealer && second.dealer == *dealer
+                && first.payload != second.payload
+                && first.verify(dealer_key) && second.verify(dealer_key),
+            Equivocation::Party { .. } => false,
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl ReliableBroadcast {
+    /// The instance `instance` dealt by `dealer` between the holders of `keys`, the dealer included.
+    pub fn new(instance: &str, dealer: u8, keys: BTreeMap<u8, VerifyingKey>) -> Self {
+        ReliableBroadcast {
+            instance: instance.to_owned(),
+            dealer,
+            faulty: (keys.len().max(1) - 1) / 3,
+            keys,
+            proposals: HashMap::new(),
+            echoes: HashMap::new(),
+            readies: HashMap::new(),
+            echoed: false,
+            readied: false,
+            delivered: None,
+            equivocations: Vec::new(),
+        }
+    }
+
+    pub fn delivered(&self) -> Option<&Vec<u8>> {
+        self.delivered.as_ref()
+    }
+
+    pub fn equivocations(&self) -> &[Equivocation] {
+        &self.equivocations
+    }
+
+    /// Takes a message `from` sent and returns the messages this node now has to send to every participant, itself included.
+    pub fn handle(&mut self, from: u8, message: &BroadcastMessage) -> Result<Vec<BroadcastMessage>, SessionError> {
+        if !self.keys.contains_key(&from) {
+            return Err(broadcastError("the sender is not a participant"));
+        }
+        if message.instance() != self.instance || message.dealer() != self.dealer {
+            return Err(broadcastError("the message belongs to another instance"));
+        }
+        let mut outgoing = Vec::new();
+        match message {
+            BroadcastMessage::Send { proposal } => {
+                if from != self.dealer {
+                    return Err(broadcastError("only the dealer sends proposals"));
+                }
+                self.learn(proposal)?;
+                if !self.echoed {
+                    self.echoed = true;
+                    outgoing.push(BroadcastMessage::Echo { proposal: proposal.clone() });
+                }
+            }
+            BroadcastMessage::Echo { proposal } => {
+                let digest = self.learn(proposal)?;
+                self.count(from, digest, false);
+            }
+            BroadcastMessage::Ready { digest, .. } => self.count(from, digest.clone(), true),
+        }
+        if !self.readied {
+            if let Some(digest) = self.agreed(&self.echoes, (self.keys.len() + self.faulty + 2) / 2).or_else(|| self.agreed(&self.readies, self.faulty + 1)) {
+                self.readied = true;
+                outgoing.push(BroadcastMessage::Ready { instance: self.instance.clone(), dealer: self.dealer, digest });
+            }
+        }
+        if self.delivered.is_none() {
+            if let Some(proposal) = self.agreed(&self.readies, 2 * self.faulty + 1).and_then(|digest| self.proposals.get(&digest)) {
+                self.delivered = Some(proposal.payloadBytes());
+            }
+        }
+        Ok(outgoing)
+    }
+
+    /// Checks the dealer's signature on `proposal` and keeps it, noting the dealer as equivocating if it differs from
+    /// one seen before. Returns the digest of its payload.
+    fn learn(&mut self, proposal: &Proposal) -> Result<String, SessionError> {
+        // Every node echoes the same proposal, its signature only needs checking the first time.
+        if let Some((digest, _)) = self.proposals.iter().find(|(_, seen)| *seen == proposal) {
+            return Ok(digest.clone());
+        }
+        if !proposal.verify(&self.keys[&self.dealer]) {
+            return Err(broadcastError("the proposal is not signed by its dealer"));
+        }
+        let digest = digest(&proposal.payloadBytes());
+        if !self.proposals.contains_key(&digest) {
+            if let Some(first) = self.proposals.values().next() {
+                self.equivocations.push(Equivocation::Dealer { dealer: self.dealer, first: first.clone(), second: proposal.clone() });
+            }
+            self.proposals.insert(digest.clone(), proposal.clone());
+        }
+        Ok(digest)
+    }
+
+    /// Counts the echo or ready of `from`. Only its first one counts, a different second one is an equivocation.
+    fn count(&mut self, from: u8, digest: String, ready: bool) {
+        let votes = if ready { &mut self.readies } else { &mut self.echoes };
+        match votes.get(&from) {
+            None => {
+                votes.insert(from, digest);
+            }
+            Some(first) if *first != digest => {
+                let equivocation = Equivocation::Party { party: from, instance: self.instance.clone() };
+                if !self.equivocations.contains(&equivocation) {
+                    self.equivocations.push(equivocation);
+                }
+            }
+            Some(_) => (),
+        }
+    }
+
+    /// The digest at least `threshold` of `votes` agree on.
+    fn agreed(&self, votes: &HashMap<u8, String>, threshold: usize) -> Option<String> {
+        let mut tally: HashMap<&String, usize> = HashMap::new();
+        for digest in votes.values() {
+            *tally.entry(digest).or_default() += 1;
+        }
+        tally.into_iter().find(|(_, count)| *count >= threshold).map(|(digest, _)| digest.clone())
+    }
+}
+
+#[allow(dead_code)]
+impl<'a, T: Transport> BroadcastChannel<'a, T> {
+    /// A channel between the holders of `keys`, which must include this node's own key.
+    pub fn new(transport: &'a mut T, signing_key: SigningKey, keys: BTreeMap<u8, VerifyingKey>) -> Self {
+        BroadcastChannel { transport, signing_key, keys, pending: Vec::new(), pending_bytes: 0, finished: HashSet::new() }
+    }
+
+    /// Broadcasts `payload` in the round named `round` and waits until the instance of every participant delivered or
+    /// `timeout` passed. Every participant has to run the same round; round names must not be reused.
+    pub async fn round(&mut self, round: &str, payload: &[u8], timeout: Duration) -> Result<RoundOutcome, SessionError> {
+        let local = self.transport.local();
+        if self.finished.contains(round) {
+            return Err(broadcastError("this round already ran"));
+        }
+        let mut instances: BTreeMap<u8, ReliableBroadcast> = self.keys.keys()
+            .map(|dealer| (*dealer, ReliableBroadcast::new(&instanceName(round, *dealer), *dealer, self.keys.clone())))
+            .collect();
+        self.expire(Instant::now());
+        let (early, pending): (Vec<_>, Vec<_>) = self.pending.drain(..).partition(|pending| Self::belongsTo(round, &pending.message));
+        self.pending = pending;
+        self.pending_bytes -= early.iter().map(|pending| pending.size).sum::<usize>();
+        let mut queue: VecDeque<(u8, BroadcastMessage)> = early.into_iter().map(|pending| (pending.from, pending.message)).collect();
+
+        let send = BroadcastMessage::Send { proposal: Proposal::new(&self.signing_key, &instanceName(round, local), local, payload) };
+        self.sendAll(&send).await;
+        queue.push_back((local, send));
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            while let Some((from, message)) = queue.pop_front() {
+                let instance = match instances.get_mut(&message.dealer()) {
+                    Some(instance) => instance,
+                    None => continue,
+                };
+                match instance.handle(from, &message) {
+                    Ok(outgoing) => for message in outgoing {
+                        self.sendAll(&message).await;
+                        queue.push_back((local, message));
+                    },
+                    Err(err) => log::warn!("Dropped a broadcast message from node {}: {}", from, err),
+                }
+            }
+            if instances.values().all(|instance| instance.delivered().is_some()) {
+                break;
+            }
+            let (from, bytes) = match timeout_at(deadline, self.transport.receive()).await {
+                Ok(received) => received?,
+                Err(_) => break,
+            };
+            let message: BroadcastMessage = match serde_json::from_slice(&bytes) {
+                Ok(message) => message,
+                Err(err) => {
+                    log::warn!("Dropped a malformed broadcast message from node {}: {}", from, err);
+                    continue;
+                }
+            };
+            if Self::belongsTo(round, &message) {
+                queue.push_back((from, message));
+            } else if !self.finished.iter().any(|finished| Self::belongsTo(finished, &message)) && !self.keep(from, message, bytes.len(), Instant::now()) {
+                log::warn!("Dropped a broadcast message from node {} for a later round, too many are waiting", from);
+            }
+        }
+        self.finished.insert(round.to_owned());
+
+        let mut outcome = RoundOutcome::default();
+        for (dealer, instance) in instances {
+            match instance.delivered() {
+                Some(payload) => { outcome.delivered.insert(dealer, payload.clone()); }
+                None => outcome.missing.push(dealer),
+            }
+            outcome.equivocations.extend(instance.equivocations().iter().cloned());
+        }
+        Ok(outcome)
+    }
+
+    /// Keeps a message of a round that has not started yet, returns whether it was kept or dropped for the bounds.
+    fn keep(&mut self, from: u8, message: BroadcastMessage, size: usize, now: Instant) -> bool {
+        self.expire(now);
+        if self.pending_bytes + size > PENDING_BYTES || self.pending.iter().filter(|pending| pending.from == from).count() >= PENDING_PER_PEER {
+            return false;
+        }
+        self.pending.push(PendingMessage { received: now, from, size, message });
+        self.pending_bytes += size;
+        true
+    }
+
+    /// Drops the kept messages older than `PENDING_TTL`.
+    fn expire(&mut self, now: Instant) {
+        let pending_bytes = &mut self.pending_bytes;
+        self.pending.retain(|pending| {
+            let fresh = now.saturating_duration_since(pending.received) <= PENDING_TTL;
+            if !fresh {
+                *pending_bytes -= pending.size;
+            }
+            fresh
+        });
+    }
+
+    fn belongsTo(round: &str, message: &BroadcastMessage) -> bool {
+        message.instance() == instanceName(round, message.dealer())
+    }
+
+    /// Sends `message` to every other participant. A participant that can not be reached is only logged, the broadcast
+    /// tolerates as many of them as it tolerates faulty nodes.
+    async fn sendAll(&self, message: &BroadcastMessage) {
+        let bytes = serde_json::to_vec(message).expect("Broadcast messages always serialize");
+        for to in self.keys.keys().filter(|x| **x != self.transport.local()) {
+            if let Err(err) = self.transport.send(*to, bytes.clone()).await {
+                log::warn!("Could not send a broadcast message to node {}: {}", to, err);
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl RoundOutcome {
+    /// Nodes caught equivocating in the round, in ascending order.
+    pub fn faulty(&self) -> Vec<u8> {
+        let mut faulty: Vec<u8> = self.equivocations.iter().map(Equivocation::party).collect();
+        faulty.sort();
+        faulty.dedup();
+        faulty
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::transport::InMemoryTransport;
+
+    fn keys(n: u8) -> (Vec<SigningKey>, BTreeMap<u8, VerifyingKey>) {
+        let signing: Vec<SigningKey> = (1..=n).map(|x| SigningKey::from_bytes(&[x; 32])).collect();
+        let keys = signing.iter().enumerate().map(|(i, key)| (i as u8 + 1, key.verifying_key())).collect();
+        (signing, keys)
+    }
+
+    #[test]
+    fn test_instance_delivers_after_readies() {
+        let (signing, keys) = keys(4);
+        let mut instance = ReliableBroadcast::new("r/1", 1, keys);
+        let proposal = Proposal::new(&signing[0], "r/1", 1, b"payload");
+        let echo = BroadcastMessage::Echo { proposal: proposal.clone() };
+        assert_eq!(instance.handle(1, &BroadcastMessage::Send { proposal: proposal.clone() }).unwrap(), vec![echo.clone()]);
+        assert!(instance.handle(2, &BroadcastMessage::Send { proposal: proposal.clone() }).is_err());
+        instance.handle(1, &echo).unwrap();
+        instance.handle(2, &echo).unwrap();
+        let ready = instance.handle(3, &echo).unwrap();
+        assert!(matches!(ready.as_slice(), [BroadcastMessage::Ready { .. }]));
+        instance.handle(1, &ready[0]).unwrap();
+        instance.handle(2, &ready[0]).unwrap();
+        assert!(instance.delivered().is_none());
+        instance.handle(3, &ready[0]).unwrap();
+        assert_eq!(instance.delivered(), Some(&b"payload".to_vec()));
+
+        let other = Proposal::new(&signing[0], "r/1", 1, b"other");
+        instance.handle(4, &BroadcastMessage::Echo { proposal: other.clone() }).unwrap();
+        instance.handle(4, &BroadcastMessage::Echo { proposal: proposal.clone() }).unwrap();
+        assert_eq!(instance.equivocations().len(), 2);
+        assert!(instance.equivocations()[0].verify(&signing[0].verifying_key()));
+        assert_eq!(instance.equivocations()[1], Equivocation::Party { party: 4, instance: "r/1".to_string() });
+        let forged = Proposal { signature: proposal.signature.clone(), ..other };
+        assert_eq!(instance.handle(2, &BroadcastMessage::Echo { proposal: forged }), Err(broadcastError("the proposal is not signed by its dealer")));
+    }
+
+    #[tokio::test]
+    async fn test_honest_round_delivers_everywhere() {
+        let (signing, keys) = keys(4);
+        let mut rounds = Vec::new();
+        for (mut transport, signing_key) in InMemoryTransport::network(&[1, 2, 3, 4]).into_iter().zip(signing) {
+            let keys = keys.clone();
+            rounds.push(tokio::spawn(async move {
+                let x = transport.local();
+                let mut channel = BroadcastChannel::new(&mut transport, signing_key, keys);
+                let first = channel.round("dkg/0", &[x], Duration::from_secs(5)).await.unwrap();
+                let second = channel.round("dkg/1", &[x, x], Duration::from_secs(5)).await.unwrap();
+                (first, second)
+            }));
+        }
+        for round in rounds {
+            let (first, second) = round.await.unwrap();
+            assert_eq!(first.delivered, (1..=4).map(|x| (x, vec![x])).collect());
+            assert_eq!(second.delivered, (1..=4).map(|x| (x, vec![x, x])).collect());
+            assert!(first.missing.is_empty() && first.equivocations.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_messages_of_later_rounds_are_bounded() {
+        let (signing, keys) = keys(3);
+        let mut transport = InMemoryTransport::network(&[1, 2, 3]).remove(0);
+        let mut channel = BroadcastChannel::new(&mut transport, signing[0].clone(), keys);
+        let ready = |round: usize| BroadcastMessage::Ready { instance: instanceName(&format!("made-up/{}", round), 2), dealer: 2, digest: String::new() };
+        let start = Instant::now();
+        for round in 0..PENDING_PER_PEER {
+            assert!(channel.keep(2, ready(round), 100, start));
+        }
+        assert!(!channel.keep(2, ready(PENDING_PER_PEER), 100, start));
+        assert!(channel.keep(3, ready(0), 100, start));
+        assert!(!channel.keep(3, ready(1), PENDING_BYTES, start));
+        // Past the time limit the kept messages are dropped, which frees room for others.
+        assert!(channel.keep(2, ready(0), 100, start + PENDING_TTL + Duration::from_secs(1)));
+        assert_eq!((channel.pending.len(), channel.pending_bytes), (1, 100));
+    }
+
+    #[tokio::test]
+    async fn test_equivocating_dealer_is_caught() {
+        let (signing, keys) = keys(4);
+        let mut transports = InMemoryTransport::network(&[1, 2, 3, 4]);
+        let dealer = transports.remove(0);
+        let proposal = |payload: &[u8]| serde_json::to_vec(&BroadcastMessage::Send { proposal: Proposal::new(&signing[0], &instanceName("vss/0", 1), 1, payload) }).unwrap();
+        dealer.send(2, proposal(b"share for 2 and 3")).await.unwrap();
+        dealer.send(3, proposal(b"share for 2 and 3")).await.unwrap();
+        dealer.send(4, proposal(b"share for 4")).await.unwrap();
+
+        let mut rounds = Vec::new();
+        for (mut transport, signing_key) in transports.into_iter().zip(signing.into_iter().skip(1)) {
+            let keys = keys.clone();
+            rounds.push(tokio::spawn(async move {
+                let x = transport.local();
+                BroadcastChannel::new(&mut transport, signing_key, keys).round("vss/0", &[x], Duration::from_millis(300)).await.unwrap()
+            }));
+        }
+        for round in rounds {
+            let outcome = round.await.unwrap();
+            assert_eq!(outcome.delivered, (2..=4).map(|x| (x, vec![x])).collect());
+            assert_eq!(outcome.missing, vec![1]);
+            assert_eq!(outcome.faulty(), vec![1]);
+            assert!(outcome.equivocations.iter().all(|equivocation| equivocation.verify(&keys[&1])));
+        }
+    }
+}
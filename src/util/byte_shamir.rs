@@ -0,0 +1,109 @@
+use rand::seq::SliceRandom;
+
+use super::{field::Field, gf256::GF256, shamir::{ShamirAlgorithm, ShamirError}};
+
+/// Byte-wise Shamir sharing over GF(2^8) for secrets of any length, with the share layout of HashiCorp Vault's `shamir`
+/// package: every share is one y byte per secret byte, followed by the share's x-coordinate as the last byte.
+/// Each secret byte is shared with its own random polynomial, all of them evaluated at the same x-coordinates.
+pub struct ByteShamir;
+
+#[allow(dead_code)]
+impl ByteShamir {
+    /// Splits `secret` into `parties` shares, any `degree + 1` of which recover it. The x-coordinates are distinct random
+    /// values in `1..=255`, as Vault picks them.
+    pub fn split(degree: u8, secret: &[u8], parties: u8) -> Result<Vec<Vec<u8>>, ShamirError> {
+        let required = degree as usize + 1;
+        if (parties as usize) < required {
+            return Err(ShamirError::NotEnoughShares { required, given: parties as usize });
+        }
+        if degree == 0 {
+            return Err(ShamirError::InvalidSharing("degree must be at least 1, a single share would be the secret itself".to_string()));
+        }
+        if secret.is_empty() {
+            return Err(ShamirError::InvalidSharing("secret must not be empty".to_string()));
+        }
+        let mut xs: Vec<u8> = (1..=255).collect();
+        xs.shuffle(&mut rand::thread_rng());
+        xs.truncate(parties as usize);
+
+        let mut shares: Vec<Vec<u8>> = xs.iter().map(|_| Vec::with_capacity(secret.len() + 1)).collect();
+        for byte in secret.iter() {
+            let polynomial = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(GF256(*byte));
+            for (share, x) in shares.iter_mut().zip(xs.iter()) {
+                share.push(polynomial.evaluate_at(GF256(*x)).0);
+            }
+        }
+        for (share, x) in shares.iter_mut().zip(xs) {
+            share.push(x);
+        }
+        Ok(shares)
+    }
+
+    /// Recovers the secret from shares in the layout `split` produces by interpolating all of them, as Vault does: the
+    /// threshold is not known here, so fewer shares than it give a wrong secret rather than an error. The Lagrange
+    /// coefficients only depend on the x-coordinates, so they are computed once and reused for every byte.
+    pub fn combine(shares: &[Vec<u8>]) -> Result<Vec<u8>, ShamirError> {
+        let length = shares.first().map(|share| share.len()).unwrap_or(0);
+        if let Some(i) = shares.iter().position(|share| share.len() < 2 || share.len() != length) {
+            return Err(ShamirError::MalformedShare(i));
+        }
+        let xs: Vec<GF256> = shares.iter().map(|share| GF256(share[length - 1])).collect();
+        ShamirAlgorithm::new(Some(1)).validateCoordinates(&xs)?;
+        let coefficients = ShamirAlgorithm::lagrangeCoefficients(&xs, &GF256::zero())?;
+
+        Ok((0..length - 1)
+            .map(|i| shares.iter()
+                .zip(coefficients.iter())
+                .fold(GF256::zero(), |sum, (share, coefficient)| sum + GF256(share[i]) * *coefficient)
+                .0)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_and_combine_any_subset() {
+        let secret = b"correct horse battery staple, and then some more bytes".to_vec();
+        let shares = ByteShamir::split(2, &secret, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        for share in shares.iter() {
+            assert_eq!(share.len(), secret.len() + 1);
+            assert_ne!(share[secret.len()], 0);
+        }
+        assert_eq!(ByteShamir::combine(&shares[2..]), Ok(secret.clone()));
+        assert_eq!(ByteShamir::combine(&[shares[4].clone(), shares[0].clone(), shares[3].clone()]), Ok(secret.clone()));
+        assert_eq!(ByteShamir::combine(&shares), Ok(secret.clone()));
+        assert_ne!(ByteShamir::combine(&shares[..2]), Ok(secret));
+    }
+
+    #[test]
+    fn test_threshold_two() {
+        let shares = ByteShamir::split(1, b"two of three", 3).unwrap();
+        assert_eq!(ByteShamir::combine(&shares[1..]), Ok(b"two of three".to_vec()));
+        assert_eq!(ByteShamir::combine(&[shares[2].clone(), shares[0].clone()]), Ok(b"two of three".to_vec()));
+    }
+
+    #[test]
+    fn test_combine_vault_layout() {
+        // f(x) = 0x2a + 0x01 x + 0x02 x^2 for the single secret byte, shares at x = 1, 2, 3
+        let share = |x: u8| vec![(GF256(0x2a) + GF256(x) + GF256(0x02) * GF256(x) * GF256(x)).0, x];
+        assert_eq!(ByteShamir::combine(&[share(1), share(2), share(3)]), Ok(vec![0x2a]));
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        let shares = ByteShamir::split(2, &[1, 2, 3], 3).unwrap();
+        assert_eq!(ByteShamir::split(3, &[1, 2, 3], 3), Err(ShamirError::NotEnoughShares { required: 4, given: 3 }));
+        assert!(matches!(ByteShamir::split(0, &[1, 2, 3], 3), Err(ShamirError::InvalidSharing(_))));
+        assert!(matches!(ByteShamir::split(2, &[], 3), Err(ShamirError::InvalidSharing(_))));
+        assert_eq!(ByteShamir::combine(&shares[..1]), Err(ShamirError::NotEnoughShares { required: 2, given: 1 }));
+        assert_eq!(ByteShamir::combine(&[shares[0].clone(), shares[1].clone(), shares[2][1..].to_vec()]), Err(ShamirError::MalformedShare(2)));
+        assert_eq!(
+            ByteShamir::combine(&[shares[0].clone(), shares[1].clone(), shares[0].clone()]),
+            Err(ShamirError::DuplicateCoordinate(shares[0][3].to_string()))
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use std::{collections::BTreeMap, env, fmt};
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use super::hpke::{CiphertextError, Hpke, KEY_LENGTH, SHARE_INFO};
+
+/// Domain separation of the bytes a share delivery signature covers.
+const DELIVERY_CONTEXT: &[u8] = b"node-rpc-rust share delivery";
+
+/// Another node of the cluster, as listed in `PEERS`.
+#[derive(Debug, Clone)]
+pub struct Peer {
+    /// x-coordinate of the share the peer owns, which also names it.
+    pub x: u8,
+    /// `host:port` the peer's server listens on.
+    pub address: String,
+    pub signing_key: VerifyingKey,
+    pub encryption_key: [u8; KEY_LENGTH],
+    /// `host:port` the peer's node to node transport listens on, if it runs one.
+    pub transport_address: Option<String>,
+    /// X25519 public key the peer's transport authenticates with, kept apart from its encryption key.
+    pub transport_key: Option<[u8; KEY_LENGTH]>,
+}
+
+/// Identity of this node and the nodes it deals to. Every node owns the share at its own x-coordinate and nothing else.
+#[derive(Debug, Clone)]
+pub struct ClusterConfig {
+    pub x: u8,
+    signing_key: SigningKey,
+    encryption_key: [u8; KEY_LENGTH],
+    transport_key: Option<[u8; KEY_LENGTH]>,
+    pub peers: Vec<Peer>,
+}
+
+/// One share on its way from the dealing node to the node that owns it. The share is sealed to the recipient and the
+/// whole message is signed by the sender, so a relay can neither read nor alter it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareDelivery {
+    pub sender: u8,
+    pub recipient: u8,
+    pub user_id: String,
+    pub public_key: String,
+    pub scheme: String,
+    pub degree: u8,
+    pub commitments: Vec<String>,
+    /// Hex HPKE ciphertext of the share envelope, with the public key as associated data.
+    pub sealed_share: String,
+    /// Hex Ed25519 signature of the sender over every other field.
+    pub signature: String,
+}
+
+fn parseKey(value: &str) -> Option<[u8; KEY_LENGTH]> {
+    hex::decode(value).ok()?.try_into().ok()
+}
+
+/// Reasons a cluster configuration is refused or a share sent between its nodes is not accepted.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ClusterError {
+    /// The nodes' x-coordinates or keys do not fit together, the string says how.
+    InvalidConfig(String),
+    /// A share sent between cluster nodes is not addressed, signed or encoded as it should be, the string says how.
+    InvalidDelivery(String),
+    /// The share could not be sealed to the recipient or does not open with this node's key.
+    Ciphertext(CiphertextError),
+}
+
+impl fmt::Display for ClusterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClusterError::InvalidConfig(reason) => write!(f, "Invalid cluster configuration: {}", reason),
+            ClusterError::InvalidDelivery(reason) => write!(f, "Share delivery rejected: {}", reason),
+            ClusterError::Ciphertext(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<CiphertextError> for ClusterError {
+    fn from(err: CiphertextError) -> Self {
+        ClusterError::Ciphertext(err)
+    }
+}
+
+fn clusterError(reason: &str) -> ClusterError {
+    ClusterError::InvalidDelivery(reason.to_string())
+}
+
+#[allow(dead_code)]
+impl Peer {
+    /// Parses `x@host:port@signing key@encryption key`, keys in hex, optionally followed by
+    /// `@transport host:port@transport key`.
+    pub fn parse(peer: &str) -> Option<Self> {
+        let parts: Vec<&str> = peer.trim().split('@').collect();
+        if parts.len() != 4 && parts.len() != 6 {
+            return None;
+        }
+        let transport_key = match parts.get(5) {
+            Some(key) => Some(parseKey(key)?),
+            None => None,
+        };
+        Some(Peer {
+            x: parts[0].parse().ok().filter(|x| *x != 0)?,
+            address: parts[1].to_owned(),
+            signing_key: VerifyingKey::from_bytes(&parseKey(parts[2])?).ok()?,
+            encryption_key: parseKey(parts[3])?,
+            transport_address: parts.get(4).map(|address| address.to_string()),
+            transport_key,
+        })
+    }
+}
+
+#[allow(dead_code)]
+impl ClusterConfig {
+    pub fn new(x: u8, signing_key: [u8; KEY_LENGTH], encryption_key: [u8; KEY_LENGTH], transport_key: Option<[u8; KEY_LENGTH]>, peers: Vec<Peer>) -> Result<Self, ClusterError> {
+        let mut xs: Vec<u8> = peers.iter().map(|peer| peer.x).collect();
+        xs.push(x);
+        xs.sort();
+        if xs.contains(&0) {
+            return Err(ClusterError::InvalidConfig("no node can have the x-coordinate 0".to_string()));
+        }
+        if let Some(pair) = xs.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(ClusterError::InvalidConfig(format!("more than one node has the x-coordinate {}", pair[0])));
+        }
+        if transport_key == Some(encryption_key) {
+            return Err(ClusterError::InvalidConfig("the transport key must not be the encryption key".to_string()));
+        }
+        Ok(ClusterConfig { x, signing_key: SigningKey::from_bytes(&signing_key), encryption_key, transport_key, peers })
+    }
+
+    /// Reads the node identity from `NODE_X`, `NODE_SIGNING_KEY`, `NODE_ENCRYPTION_KEY` and the optional
+    /// `NODE_TRANSPORT_KEY` (hex private keys) and the other nodes from `PEERS`, a comma separated list of
+    /// `x@host:port@signing key@encryption key[@transport host:port@transport key]`.
+    /// `None` without `NODE_X`, in which case the server runs standalone and stores every share itself.
+    pub fn fromEnv() -> Option<Self> {
+        let x: u8 = env::var("NODE_X").ok()?.parse().expect("NODE_X IS NOT IN CORRECT FORMAT");
+        let signing_key = parseKey(&env::var("NODE_SIGNING_KEY").expect("NODE_SIGNING_KEY IS NOT IN ENV")).expect("NODE_SIGNING_KEY IS NOT IN CORRECT FORMAT");
+        let encryption_key = parseKey(&env::var("NODE_ENCRYPTION_KEY").expect("NODE_ENCRYPTION_KEY IS NOT IN ENV")).expect("NODE_ENCRYPTION_KEY IS NOT IN CORRECT FORMAT");
+        let transport_key = env::var("NODE_TRANSPORT_KEY").ok()
+            .map(|key| parseKey(&key).expect("NODE_TRANSPORT_KEY IS NOT IN CORRECT FORMAT"));
+        let peers = env::var("PEERS").expect("PEERS IS NOT IN ENV")
+            .split(',')
+            .filter(|peer| !peer.trim().is_empty())
+            .map(|peer| Peer::parse(peer).expect("PEERS IS NOT IN CORRECT FORMAT"))
+            .collect();
+        Some(Self::new(x, signing_key, encryption_key, transport_key, peers).expect("NODE_X AND PEERS MUST HAVE DISTINCT NON ZERO X-COORDINATES AND NODE_TRANSPORT_KEY ITS OWN KEY"))
+    }
+
+    /// x-coordinates of every node, this one included, in ascending order.
+    pub fn coordinates(&self) -> Vec<u8> {
+        let mut xs: Vec<u8> = self.peers.iter().map(|peer| peer.x).collect();
+        xs.push(self.x);
+        xs.sort();
+        xs
+    }
+
+    pub fn peer(&self, x: u8) -> Option<&Peer> {
+        self.peers.iter().find(|peer| peer.x == x)
+    }
+
+    pub fn signingKey(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    /// The private Ed25519 key, for protocols that sign their messages themselves.
+    pub fn signingSecret(&self) -> SigningKey {
+        self.signing_key.clone()
+    }
+
+    /// Ed25519 keys of every node, this one included, by x-coordinate.
+    pub fn signingKeys(&self) -> BTreeMap<u8, VerifyingKey> {
+        let mut keys: BTreeMap<u8, VerifyingKey> = self.peers.iter().map(|peer| (peer.x, peer.signing_key)).collect();
+        keys.insert(self.x, self.signingKey());
+        keys
+    }
+
+    pub fn encryptionKey(&self) -> [u8; KEY_LENGTH] {
+        PublicKey::from(&StaticSecret::from(self.encryption_key)).to_bytes()
+    }
+
+    /// The private X25519 key the node's transport authenticates with as its Noise static key, if it runs one.
+    pub fn staticKey(&self) -> Option<[u8; KEY_LENGTH]> {
+        self.transport_key
+    }
+
+    /// The peer whose transport key is `key`, i.e. who authenticated with it as its Noise static key.
+    pub fn peerWithKey(&self, key: &[u8; KEY_LENGTH]) -> Option<&Peer> {
+        self.peers.iter().find(|peer| peer.transport_key.as_ref() == Some(key))
+    }
+
+    /// Seals `envelope` to the peer at `recipient` and signs the delivery.
+    #[allow(clippy::too_many_arguments)]
+    pub fn seal(&self, recipient: u8, user_id: &str, public_key: &str, scheme: &str, degree: u8, commitments: &[String], envelope: &str) -> Result<ShareDelivery, ClusterError> {
+        let peer = self.peer(recipient).ok_or_else(|| clusterError("the recipient is not a peer of this node"))?;
+        let sealed = Hpke::seal(&peer.encryption_key, SHARE_INFO, public_key.as_bytes(), envelope.as_bytes())?;
+        let mut delivery = ShareDelivery {
+            sender: self.x,
+            recipient,
+            user_id: user_id.to_owned(),
+            public_key: public_key.to_owned(),
+            scheme: scheme.to_owned(),
+            degree,
+            commitments: commitments.to_vec(),
+            sealed_share: hex::encode(sealed),
+            signature: String::new(),
+        };
+        delivery.signature = hex::encode(self.signing_key.sign(&delivery.signedBytes()).to_bytes());
+        Ok(delivery)
+    }
+
+    /// Checks that `delivery` is addressed to this node and signed by the peer it claims to come from, and returns the
+    /// share envelope it carries.
+    pub fn open(&self, delivery: &ShareDelivery) -> Result<String, ClusterError> {
+        if delivery.recipient != self.x {
+            return Err(clusterError("the delivery is addressed to another node"));
+        }
+        let sender = self.peer(delivery.sender).ok_or_else(|| clusterError("the sender is not a peer of this node"))?;
+        let signature: [u8; 64] = hex::decode(&delivery.signature).ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| clusterError("the signature is not 64 hex encoded bytes"))?;
+        sender.signing_key.verify(&delivery.signedBytes(), &Signature::from_bytes(&signature))
+            .map_err(|_| clusterError("the signature does not verify"))?;
+        let sealed = hex::decode(&delivery.sealed_share).map_err(|_| clusterError("the sealed share is not hex encoded"))?;
+        let envelope = Hpke::open(&self.encryption_key, SHARE_INFO, delivery.public_key.as_bytes(), &sealed)?;
+        String::from_utf8(envelope).map_err(|_| clusterError("the share envelope is not text"))
+    }
+}
+
+impl ShareDelivery {
+    /// Every field but the signature, each length-prefixed so no two deliveries sign the same bytes.
+    fn signedBytes(&self) -> Vec<u8> {
+        let header = [self.sender, self.recipient, self.degree];
+        let mut fields: Vec<&[u8]> = vec![
+            &header,
+            self.user_id.as_bytes(),
+            self.public_key.as_bytes(),
+            self.scheme.as_bytes(),
+            self.sealed_share.as_bytes(),
+        ];
+        fields.extend(self.commitments.iter().map(|commitment| commitment.as_bytes()));
+        let mut bytes = DELIVERY_CONTEXT.to_vec();
+        for field in fields {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(x: u8) -> ([u8; 32], [u8; 32], Peer) {
+        let signing_key = [x; 32];
+        let (encryption_key, encryption_public) = Hpke::generateKeyPair();
+        let peer = format!("{}@127.0.0.1:80{:02}@{}@{}", x, x, hex::encode(SigningKey::from_bytes(&signing_key).verifying_key().to_bytes()), hex::encode(encryption_public));
+        (signing_key, encryption_key, Peer::parse(&peer).unwrap())
+    }
+
+    #[test]
+    fn test_delivery_reaches_only_its_node() {
+        let (dealer_signing, dealer_encryption, dealer) = node(1);
+        let (holder_signing, holder_encryption, holder) = node(2);
+        let (_, _, other) = node(3);
+        let dealer_config = ClusterConfig::new(1, dealer_signing, dealer_encryption, None, vec![holder.clone(), other.clone()]).unwrap();
+        let holder_config = ClusterConfig::new(2, holder_signing, holder_encryption, None, vec![dealer, other]).unwrap();
+        assert_eq!(dealer_config.coordinates(), vec![1, 2, 3]);
+
+        let commitments = vec!["02aa".to_string()];
+        let delivery = dealer_config.seal(2, "user", "02aa", "feldman", 1, &commitments, "share1envelope").unwrap();
+        assert_eq!(holder_config.open(&delivery).unwrap(), "share1envelope");
+        assert!(dealer_config.open(&delivery).is_err());
+
+        let mut forged = delivery.clone();
+        forged.degree = 2;
+        assert!(holder_config.open(&forged).is_err());
+        assert!(ClusterConfig::new(2, holder_signing, holder_encryption, None, vec![holder]).is_err());
+        assert!(ClusterConfig::new(2, holder_signing, holder_encryption, Some(holder_encryption), vec![]).is_err());
+    }
+}
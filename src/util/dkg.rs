@@ -0,0 +1,599 @@
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::time::{timeout_at, Duration, Instant};
+
+use super::{broadcast::BroadcastChannel, field::Field, polynomials::Polynomial, session::{SessionError, SessionRouter}, shamir::{ShamirAlgorithm, ShamirError}, transport::{InMemoryTransport, Transport, TransportError}};
+
+/// Time every round of a key generation may take.
+pub const ROUND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What a key generation needs of the group its key lives in. Implemented by the Feldman VSS of every curve keys are
+/// generated on, the commitment helpers work the same for all of them.
+pub trait DkgGroup: 'static {
+    type Scalar: Field + FromStr + Send + Sync;
+    type Point: Clone + PartialEq + Send + Sync;
+
+    /// Returns `value * G`.
+    fn mulBase(value: &Self::Scalar) -> Self::Point;
+
+    /// Second generator `H` of Pedersen commitments, nobody knows its discrete logarithm to `G`.
+    fn blindingBase() -> Self::Point;
+
+    fn mul(point: &Self::Point, value: &Self::Scalar) -> Self::Point;
+
+    fn add(a: &Self::Point, b: &Self::Point) -> Self::Point;
+
+    fn identity() -> Self::Point;
+
+    fn encodePoint(point: &Self::Point) -> String;
+
+    fn decodePoint(encoded: &str) -> Option<Self::Point>;
+
+    /// `C_i = a_i * G`, lowest degree first.
+    fn feldmanCommit(polynomial: &Polynomial<Self::Scalar>) -> Vec<Self::Point> {
+        polynomial.coefficients.iter().map(Self::mulBase).collect()
+    }
+
+    /// `C_i = a_i * G + b_i * H`, lowest degree first. Both polynomials have the same degree.
+    fn pedersenCommit(polynomial: &Polynomial<Self::Scalar>, blinding: &Polynomial<Self::Scalar>) -> Vec<Self::Point> {
+        polynomial.coefficients.iter()
+            .zip(blinding.coefficients.iter())
+            .map(|(a, b)| Self::add(&Self::mulBase(a), &Self::mul(&Self::blindingBase(), b)))
+            .collect()
+    }
+
+    /// Evaluates committed coefficients "in the exponent" at `x`.
+    fn evaluateAt(commitments: &[Self::Point], x: &Self::Scalar) -> Self::Point {
+        commitments.iter().rev().fold(Self::identity(), |sum, commitment| Self::add(&Self::mul(&sum, x), commitment))
+    }
+
+    fn feldmanVerify(x: &Self::Scalar, y: &Self::Scalar, commitments: &[Self::Point]) -> bool {
+        !commitments.is_empty() && !x.is_zero() && Self::evaluateAt(commitments, x) == Self::mulBase(y)
+    }
+
+    fn pedersenVerify(x: &Self::Scalar, y: &Self::Scalar, r: &Self::Scalar, commitments: &[Self::Point]) -> bool {
+        !commitments.is_empty() && !x.is_zero()
+            && Self::evaluateAt(commitments, x) == Self::add(&Self::mulBase(y), &Self::mul(&Self::blindingBase(), r))
+    }
+}
+
+/// The sub-share `(f_i(x_j), f'_i(x_j))` the dealer `i` deals the holder `j`, sent to the holder alone or revealed to
+/// everyone when the holder complains about it. Values are decimal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubShare {
+    pub dealer: u8,
+    pub holder: u8,
+    pub y: String,
+    pub r: String,
+}
+
+/// What one participant takes away from a key generation. The joint key is never computed by anyone.
+pub struct DkgShare<G: DkgGroup> {
+    /// `[x, sum_i f_i(x)]` of this participant.
+    pub share: Vec<G::Scalar>,
+    /// Feldman commitments to `sum_i f_i`, `commitments[0]` is the public key.
+    pub commitments: Vec<G::Point>,
+    /// x-coordinates of the dealers whose polynomials make up the key.
+    pub qualified: Vec<u8>,
+}
+
+/// One participant of a distributed key generation after Gennaro, Jarecki, Krawczyk and Rabin, fed the payloads every
+/// round of reliable broadcast delivered. Every participant deals a random polynomial with Pedersen VSS, sending each
+/// other participant its sub-share in private, and the joint secret is the sum of the qualified dealers' constant terms.
+///
+/// A holder whose sub-share does not verify complains, and the dealer has to reveal that sub-share; a dealer that does
+/// not, or draws more than `degree` complaints, is disqualified. Only then do the qualified dealers publish Feldman
+/// commitments. A dealer whose Feldman commitments do not match the sub-shares it dealt is exposed by its holders and
+/// has its polynomial rebuilt from their sub-shares, so nobody can bias the public key after seeing the others'.
+pub struct DkgParty<G: DkgGroup> {
+    degree: u8,
+    local: u8,
+    participants: Vec<u8>,
+    secret: Polynomial<G::Scalar>,
+    blinding: Polynomial<G::Scalar>,
+    /// Pedersen commitments of every dealer whose commitments were delivered and well formed.
+    pedersen: BTreeMap<u8, Vec<G::Point>>,
+    /// `(f_i(x), f'_i(x))` this participant got from each dealer `i`.
+    subshares: BTreeMap<u8, (G::Scalar, G::Scalar)>,
+    qualified: Vec<u8>,
+    /// Feldman commitments of the qualified dealers, `None` for those that published none or malformed ones.
+    feldman: BTreeMap<u8, Option<Vec<G::Point>>>,
+    /// Qualified dealers whose polynomial has to be rebuilt.
+    exposed: Vec<u8>,
+}
+
+/// Reasons a key generation run fails: its participants' shares do not add up to a key, or the session it runs in fails.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DkgError {
+    Sharing(ShamirError),
+    Session(SessionError),
+}
+
+impl fmt::Display for DkgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DkgError::Sharing(err) => err.fmt(f),
+            DkgError::Session(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<ShamirError> for DkgError {
+    fn from(err: ShamirError) -> Self {
+        DkgError::Sharing(err)
+    }
+}
+
+impl From<SessionError> for DkgError {
+    fn from(err: SessionError) -> Self {
+        DkgError::Session(err)
+    }
+}
+
+impl From<TransportError> for DkgError {
+    fn from(err: TransportError) -> Self {
+        DkgError::Session(err.into())
+    }
+}
+
+fn scalar<G: DkgGroup>(x: u8) -> G::Scalar {
+    G::Scalar::from_u64(x as u64)
+}
+
+fn decode<T: for<'de> Deserialize<'de>>(payload: &[u8]) -> Option<T> {
+    serde_json::from_slice(payload).ok()
+}
+
+fn encode<T: Serialize>(value: &T) -> Vec<u8> {
+    serde_json::to_vec(value).expect("Key generation messages always serialize")
+}
+
+#[allow(dead_code)]
+impl<G: DkgGroup> DkgParty<G> {
+    /// The participant at `local` of a key generation of `degree` between `participants`, with its polynomials sampled.
+    pub fn new(degree: u8, local: u8, participants: &[u8]) -> Result<Self, ShamirError> {
+        let mut participants = participants.to_vec();
+        participants.sort();
+        let xs: Vec<G::Scalar> = participants.iter().map(|x| scalar::<G>(*x)).collect();
+        ShamirAlgorithm::new(Some(degree)).validateCoordinates(&xs)?;
+        if !participants.contains(&local) {
+            return Err(ShamirError::InvalidSharing("this node is not a participant of the key generation".to_string()));
+        }
+        let secret = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(G::Scalar::random());
+        let blinding = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(G::Scalar::random());
+        let mut subshares = BTreeMap::new();
+        subshares.insert(local, (secret.evaluate_at(scalar::<G>(local)), blinding.evaluate_at(scalar::<G>(local))));
+        Ok(DkgParty {
+            degree,
+            local,
+            participants,
+            secret,
+            blinding,
+            pedersen: BTreeMap::new(),
+            subshares,
+            qualified: Vec::new(),
+            feldman: BTreeMap::new(),
+            exposed: Vec::new(),
+        })
+    }
+
+    fn dealt(&self, holder: u8) -> SubShare {
+        let x = scalar::<G>(holder);
+        SubShare {
+            dealer: self.local,
+            holder,
+            y: self.secret.evaluate_at(x.clone()).to_string(),
+            r: self.blinding.evaluate_at(x).to_string(),
+        }
+    }
+
+    /// Parses a sub-share, `None` unless it is well formed and lies on the dealer's Pedersen commitments.
+    fn verified(&self, subshare: &SubShare) -> Option<(G::Scalar, G::Scalar)> {
+        let y: G::Scalar = subshare.y.parse().ok()?;
+        let r: G::Scalar = subshare.r.parse().ok()?;
+        let commitments = self.pedersen.get(&subshare.dealer)?;
+        if !self.participants.contains(&subshare.holder) || !G::pedersenVerify(&scalar::<G>(subshare.holder), &y, &r, commitments) {
+            return None;
+        }
+        Some((y, r))
+    }
+
+    /// Private message to `holder`, its sub-share of this participant's polynomials.
+    pub fn subshare(&self, holder: u8) -> Vec<u8> {
+        encode(&self.dealt(holder))
+    }
+
+    /// Broadcast of the first round, the Pedersen commitments.
+    pub fn commitment(&self) -> Vec<u8> {
+        let commitments = G::pedersenCommit(&self.secret, &self.blinding);
+        encode(&commitments.iter().map(G::encodePoint).collect::<Vec<String>>())
+    }
+
+    /// Takes the delivered Pedersen commitments. Dealers without well formed ones take no further part as dealers.
+    pub fn takeCommitments(&mut self, delivered: &BTreeMap<u8, Vec<u8>>) {
+        for (dealer, payload) in delivered {
+            let commitments = decode::<Vec<String>>(payload)
+                .and_then(|encoded| encoded.iter().map(|point| G::decodePoint(point)).collect::<Option<Vec<G::Point>>>());
+            match commitments {
+                Some(commitments) if commitments.len() == self.degree as usize + 1 => { self.pedersen.insert(*dealer, commitments); }
+                _ => log::warn!("Dealer {} of a key generation published malformed commitments", dealer),
+            }
+        }
+    }
+
+    /// Takes the sub-share `from` sent this participant in private. Only the first one of every dealer counts.
+    pub fn takeSubshare(&mut self, from: u8, payload: &[u8]) {
+        match decode::<SubShare>(payload) {
+            Some(subshare) if subshare.dealer == from && subshare.holder == self.local => {
+                if let (Some(values), false) = (self.verified(&subshare), self.subshares.contains_key(&from)) {
+                    self.subshares.insert(from, values);
+                }
+            }
+            _ => log::warn!("Dealer {} sent a malformed sub-share", from),
+        }
+    }
+
+    /// Whether a sub-share of a dealer with commitments is still missing.
+    pub fn awaiting(&self) -> bool {
+        self.pedersen.keys().any(|dealer| !self.subshares.contains_key(dealer))
+    }
+
+    /// Broadcast of the second round, the dealers this participant has no valid sub-share from.
+    pub fn complaints(&self) -> Vec<u8> {
+        encode(&self.pedersen.keys().filter(|dealer| !self.subshares.contains_key(dealer)).cloned().collect::<Vec<u8>>())
+    }
+
+    fn complaintsAgainst(delivered: &BTreeMap<u8, Vec<u8>>, dealer: u8) -> Vec<u8> {
+        delivered.iter()
+            .filter(|(_, payload)| decode::<Vec<u8>>(payload).is_some_and(|accused| accused.contains(&dealer)))
+            .map(|(complainer, _)| *complainer)
+            .collect()
+    }
+
+    /// Broadcast of the third round, the sub-shares of this participant the delivered complaints ask it to reveal.
+    pub fn answers(&self, complaints: &BTreeMap<u8, Vec<u8>>) -> Vec<u8> {
+        encode(&Self::complaintsAgainst(complaints, self.local).into_iter().map(|holder| self.dealt(holder)).collect::<Vec<SubShare>>())
+    }
+
+    /// The sub-share `dealer` dealt this participant, sent in private or revealed to answer its complaint.
+    fn subshareOf(&self, dealer: u8) -> Result<&(G::Scalar, G::Scalar), ShamirError> {
+        self.subshares.get(&dealer).ok_or(ShamirError::MissingContribution { from: dealer.to_string(), to: self.local.to_string() })
+    }
+
+    /// Decides the qualified dealers from the delivered complaints and answers, taking the sub-shares revealed to answer
+    /// this participant's complaints. Fails if a dealer is qualified without this participant holding a valid sub-share of
+    /// it, which happens when its complaint was not delivered in time.
+    pub fn qualify(&mut self, complaints: &BTreeMap<u8, Vec<u8>>, answers: &BTreeMap<u8, Vec<u8>>) -> Result<(), ShamirError> {
+        let mut qualified = Vec::new();
+        for dealer in self.pedersen.keys().cloned().collect::<Vec<u8>>() {
+            let complainers = Self::complaintsAgainst(complaints, dealer);
+            let revealed: Vec<SubShare> = answers.get(&dealer).and_then(|payload| decode(payload)).unwrap_or_default();
+            let mut answered = true;
+            for holder in complainers.iter() {
+                let answer = revealed.iter()
+                    .find(|subshare| subshare.dealer == dealer && subshare.holder == *holder)
+                    .and_then(|subshare| self.verified(subshare));
+                match answer {
+                    Some(values) if *holder == self.local => { self.subshares.insert(dealer, values); }
+                    Some(_) => (),
+                    None => answered = false,
+                }
+            }
+            if answered && complainers.len() <= self.degree as usize {
+                qualified.push(dealer);
+            }
+        }
+        if qualified.is_empty() {
+            return Err(ShamirError::NoQualifiedDealers);
+        }
+        for dealer in qualified.iter() {
+            self.subshareOf(*dealer)?;
+        }
+        self.qualified = qualified;
+        Ok(())
+    }
+
+    /// Broadcast of the fourth round, the Feldman commitments of a qualified dealer, nothing from the others.
+    pub fn feldmanCommitment(&self) -> Vec<u8> {
+        if !self.qualified.contains(&self.local) {
+            return encode(&Vec::<String>::new());
+        }
+        encode(&G::feldmanCommit(&self.secret).iter().map(G::encodePoint).collect::<Vec<String>>())
+    }
+
+    /// Takes the delivered Feldman commitments. Broadcast of the fifth round, the sub-shares of the qualified dealers
+    /// whose Feldman commitments they do not lie on.
+    pub fn exposures(&mut self, delivered: &BTreeMap<u8, Vec<u8>>) -> Result<Vec<u8>, ShamirError> {
+        let mut exposures = Vec::new();
+        for dealer in self.qualified.clone() {
+            let commitments = delivered.get(&dealer)
+                .and_then(|payload| decode::<Vec<String>>(payload))
+                .and_then(|encoded| encoded.iter().map(|point| G::decodePoint(point)).collect::<Option<Vec<G::Point>>>())
+                .filter(|commitments| commitments.len() == self.degree as usize + 1);
+            let (y, r) = self.subshareOf(dealer)?.clone();
+            if commitments.as_ref().is_some_and(|commitments| !G::feldmanVerify(&scalar::<G>(self.local), &y, commitments)) {
+                exposures.push(SubShare { dealer, holder: self.local, y: y.to_string(), r: r.to_string() });
+            }
+            self.feldman.insert(dealer, commitments);
+        }
+        Ok(encode(&exposures))
+    }
+
+    /// Takes the delivered exposures, returns whether any polynomial has to be rebuilt. A dealer is exposed by a
+    /// sub-share that lies on its Pedersen commitments but not on its Feldman ones, or by publishing no Feldman commitments.
+    pub fn takeExposures(&mut self, delivered: &BTreeMap<u8, Vec<u8>>) -> bool {
+        self.exposed = self.qualified.iter()
+            .filter(|dealer| match &self.feldman[dealer] {
+                None => true,
+                Some(commitments) => delivered.iter().any(|(holder, payload)| {
+                    decode::<Vec<SubShare>>(payload).unwrap_or_default().iter()
+                        .filter(|subshare| subshare.dealer == **dealer && subshare.holder == *holder)
+                        .any(|subshare| self.verified(subshare).is_some_and(|(y, _)| !G::feldmanVerify(&scalar::<G>(*holder), &y, commitments)))
+                }),
+            })
+            .cloned()
+            .collect();
+        !self.exposed.is_empty()
+    }
+
+    /// Broadcast of the sixth round, only run when a dealer was exposed: this participant's sub-shares of the exposed dealers.
+    pub fn reconstruction(&self) -> Result<Vec<u8>, ShamirError> {
+        let revealed = self.exposed.iter()
+            .map(|dealer| {
+                let (y, r) = self.subshareOf(*dealer)?;
+                Ok(SubShare { dealer: *dealer, holder: self.local, y: y.to_string(), r: r.to_string() })
+            })
+            .collect::<Result<Vec<SubShare>, ShamirError>>()?;
+        Ok(encode(&revealed))
+    }
+
+    /// Rebuilds the exposed dealers' polynomials from the delivered reconstructions and adds up the qualified dealings.
+    pub fn finish(mut self, reconstructions: &BTreeMap<u8, Vec<u8>>) -> Result<DkgShare<G>, ShamirError> {
+        for dealer in self.exposed.clone() {
+            let values: Vec<Vec<G::Scalar>> = reconstructions.iter()
+                .filter_map(|(holder, payload)| {
+                    decode::<Vec<SubShare>>(payload).unwrap_or_default().into_iter()
+                        .find(|subshare| subshare.dealer == dealer && subshare.holder == *holder)
+                        .and_then(|subshare| self.verified(&subshare))
+                        .map(|(y, _)| vec![scalar::<G>(*holder), y])
+                })
+                .collect();
+            if values.len() <= self.degree as usize {
+                return Err(ShamirError::NotEnoughShares { required: self.degree as usize + 1, given: values.len() });
+            }
+            let polynomial = ShamirAlgorithm::new(Some(self.degree)).fromValues(values);
+            self.feldman.insert(dealer, Some(G::feldmanCommit(&polynomial)));
+        }
+
+        let x = scalar::<G>(self.local);
+        let mut y = G::Scalar::zero();
+        let mut commitments = vec![G::identity(); self.degree as usize + 1];
+        for dealer in self.qualified.iter() {
+            y = y + self.subshareOf(*dealer)?.0.clone();
+            let dealt = self.feldman[dealer].as_ref().expect("Exposed dealers are rebuilt");
+            for (sum, commitment) in commitments.iter_mut().zip(dealt) {
+                *sum = G::add(sum, commitment);
+            }
+        }
+        if !G::feldmanVerify(&x, &y, &commitments) {
+            return Err(ShamirError::InvalidContribution { from: format!("{:?}", self.qualified), to: x.to_string() });
+        }
+        Ok(DkgShare { share: vec![x, y], commitments, qualified: self.qualified })
+    }
+}
+
+#[allow(dead_code)]
+impl<G: DkgGroup> DkgShare<G> {
+    /// Broadcast of the last round: SHA-256 over the qualified dealers and the joint commitments, which every
+    /// participant has to agree on.
+    pub fn confirmation(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new().chain_update(&self.qualified);
+        for commitment in self.commitments.iter() {
+            hasher.update(G::encodePoint(commitment).as_bytes());
+        }
+        hasher.finalize().to_vec()
+    }
+
+    pub fn encodeCommitments(&self) -> Vec<String> {
+        self.commitments.iter().map(G::encodePoint).collect()
+    }
+}
+
+/// Runs key generations, one participant per node and every participant over its own transport.
+pub struct DistributedKeyGeneration;
+
+#[allow(dead_code)]
+impl DistributedKeyGeneration {
+    /// Runs this node's participant of a key generation of `degree` between `participants`. Sub-shares travel over
+    /// `direct`, everything else is broadcast on `broadcast`. Fails unless every participant confirms the same key.
+    pub async fn run<G: DkgGroup, T: Transport + Send + Sync>(degree: u8, participants: &[u8], direct: &mut T, broadcast: &mut BroadcastChannel<'_, T>, timeout: Duration) -> Result<DkgShare<G>, DkgError> {
+        let mut party = DkgParty::<G>::new(degree, direct.local(), participants)?;
+        for holder in party.participants.iter().filter(|x| **x != party.local) {
+            direct.send(*holder, party.subshare(*holder)).await?;
+        }
+        let commitments = broadcast.round("commit", &party.commitment(), timeout).await?;
+        party.takeCommitments(&commitments.delivered);
+        let deadline = Instant::now() + timeout;
+        while party.awaiting() {
+            match timeout_at(deadline, direct.receive()).await {
+                Ok(received) => {
+                    let (from, payload) = received?;
+                    party.takeSubshare(from, &payload);
+                }
+                Err(_) => break,
+            }
+        }
+        let complaints = broadcast.round("complain", &party.complaints(), timeout).await?;
+        let answers = broadcast.round("answer", &party.answers(&complaints.delivered), timeout).await?;
+        party.qualify(&complaints.delivered, &answers.delivered)?;
+        let feldman = broadcast.round("feldman", &party.feldmanCommitment(), timeout).await?;
+        let exposures = broadcast.round("expose", &party.exposures(&feldman.delivered)?, timeout).await?;
+        let reconstructions = if party.takeExposures(&exposures.delivered) {
+            broadcast.round("reconstruct", &party.reconstruction()?, timeout).await?.delivered
+        } else {
+            BTreeMap::new()
+        };
+        let participants = party.participants.len();
+        let share = party.finish(&reconstructions)?;
+        let confirmation = share.confirmation();
+        let confirmations = broadcast.round("confirm", &confirmation, timeout).await?;
+        if confirmations.delivered.len() != participants || confirmations.delivered.values().any(|other| *other != confirmation) {
+            return Err(SessionError::Failed("the participants of the key generation did not all confirm the same key".to_string()).into());
+        }
+        Ok(share)
+    }
+
+    /// Runs this node's participant of the key generation `session_id` over the node transport behind `router`, with
+    /// the participants' broadcast keys `keys`.
+    pub async fn runParty<G: DkgGroup>(router: &SessionRouter, session_id: &str, degree: u8, participants: &[u8], signing_key: SigningKey, keys: BTreeMap<u8, VerifyingKey>) -> Result<DkgShare<G>, DkgError> {
+        let mut direct = router.join(&format!("{}/direct", session_id), participants)?;
+        let mut broadcast = router.join(&format!("{}/broadcast", session_id), participants)?;
+        let mut channel = BroadcastChannel::new(&mut broadcast, signing_key, keys);
+        Self::run::<G, _>(degree, participants, &mut direct, &mut channel, ROUND_TIMEOUT).await
+    }
+
+    /// Runs a key generation between `participants` in this process for a node that holds every share itself: each
+    /// participant is a task of its own, with its own polynomials and an in-memory transport, and only its share leaves
+    /// it. Returns the participants' shares in their order.
+    pub async fn runLocally<G: DkgGroup>(degree: u8, participants: &[u8]) -> Result<Vec<DkgShare<G>>, DkgError> {
+        let signing_keys: Vec<SigningKey> = participants.iter()
+            .map(|_| {
+                let mut secret = [0u8; 32];
+                rand::thread_rng().fill_bytes(&mut secret);
+                SigningKey::from_bytes(&secret)
+            })
+            .collect();
+        let keys: BTreeMap<u8, VerifyingKey> = participants.iter().zip(signing_keys.iter()).map(|(x, key)| (*x, key.verifying_key())).collect();
+        let mut tasks = Vec::new();
+        for (transport, signing_key) in InMemoryTransport::network(participants).into_iter().zip(signing_keys) {
+            let router = SessionRouter::new(transport.outbox());
+            tokio::spawn(router.clone().forward(transport));
+            let (participants, keys) = (participants.to_vec(), keys.clone());
+            tasks.push(tokio::spawn(async move {
+                Self::runParty::<G>(&router, "keygen", degree, &participants, signing_key, keys).await
+            }));
+        }
+        let mut shares = Vec::new();
+        for task in tasks {
+            shares.push(task.await.map_err(|err| SessionError::Failed(err.to_string()))??);
+        }
+        Ok(shares)
+    }
+
+    /// Test fixture on top of `runLocally`: the participants' `[x, y]` shares in their order and the joint commitments.
+    #[cfg(test)]
+    pub async fn localFixture<G: DkgGroup>(degree: u8, participants: &[u8]) -> (Vec<Vec<G::Scalar>>, Vec<G::Point>) {
+        let output = Self::runLocally::<G>(degree, participants).await.unwrap();
+        (output.iter().map(|share| share.share.clone()).collect(), output[0].commitments.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{feldman::FeldmanVSS, field::FieldElement, secp256k1};
+
+    fn everyone(parties: &mut [DkgParty<FeldmanVSS>], mut message: impl FnMut(&mut DkgParty<FeldmanVSS>) -> Vec<u8>) -> BTreeMap<u8, Vec<u8>> {
+        parties.iter_mut().map(|party| (party.local, message(party))).collect()
+    }
+
+    #[test]
+    fn test_parties_disqualify_and_rebuild_cheating_dealers() {
+        let xs = [1, 2, 3, 4];
+        let mut parties: Vec<DkgParty<FeldmanVSS>> = xs.iter().map(|x| DkgParty::new(1, *x, &xs).unwrap()).collect();
+        let commitments = everyone(&mut parties, |party| party.commitment());
+        for party in parties.iter_mut() {
+            party.takeCommitments(&commitments);
+        }
+        for dealer in 0..4 {
+            for holder in 0..4 {
+                if dealer == holder {
+                    continue;
+                }
+                let mut subshare: SubShare = serde_json::from_slice(&parties[dealer].subshare(xs[holder])).unwrap();
+                // Dealer 1 hands holder 2 a wrong sub-share and will not answer the complaint.
+                if (dealer, holder) == (0, 1) {
+                    subshare.y = (subshare.y.parse::<FieldElement>().unwrap() + FieldElement::one()).to_string();
+                }
+                let payload = serde_json::to_vec(&subshare).unwrap();
+                parties[holder].takeSubshare(xs[dealer], &payload);
+            }
+        }
+        let complaints = everyone(&mut parties, |party| party.complaints());
+        assert_eq!(complaints[&2], b"[1]".to_vec());
+        let mut answers = everyone(&mut parties, |party| party.answers(&complaints));
+        answers.insert(1, b"[]".to_vec());
+        for party in parties.iter_mut() {
+            party.qualify(&complaints, &answers).unwrap();
+            assert_eq!(party.qualified, vec![2, 3, 4]);
+        }
+
+        // Dealer 4 publishes Feldman commitments to another secret than it dealt.
+        let mut feldman = everyone(&mut parties, |party| party.feldmanCommitment());
+        let honest: Vec<String> = serde_json::from_slice(&feldman[&4]).unwrap();
+        let mut forged = honest.clone();
+        forged[0] = secp256k1::point_to_hex(&secp256k1::mul_base(&FieldElement::random()));
+        feldman.insert(4, serde_json::to_vec(&forged).unwrap());
+        let exposures = everyone(&mut parties, |party| party.exposures(&feldman).unwrap());
+        for party in parties.iter_mut() {
+            assert!(party.takeExposures(&exposures));
+        }
+        let reconstructions = everyone(&mut parties, |party| party.reconstruction().unwrap());
+        let shares: Vec<DkgShare<FeldmanVSS>> = parties.into_iter().map(|party| party.finish(&reconstructions).unwrap()).collect();
+
+        let expected = [2, 3].iter().map(|x| serde_json::from_slice::<Vec<String>>(&feldman[x]).unwrap()[0].clone()).chain([honest[0].clone()])
+            .map(|point| secp256k1::point_from_hex(&point).unwrap())
+            .fold(k256::ProjectivePoint::IDENTITY, |sum, point| sum + point);
+        assert!(shares.iter().all(|share| share.commitments[0] == expected && share.confirmation() == shares[0].confirmation()));
+        let values: Vec<Vec<FieldElement>> = shares.iter().map(|share| share.share.clone()).collect();
+        assert_eq!(secp256k1::mul_base(&ShamirAlgorithm::new(Some(1)).combine(&values[2..]).unwrap()), expected);
+    }
+
+    #[test]
+    fn test_missing_subshare_without_delivered_complaint_fails() {
+        let xs = [1, 2, 3, 4];
+        let mut parties: Vec<DkgParty<FeldmanVSS>> = xs.iter().map(|x| DkgParty::new(1, *x, &xs).unwrap()).collect();
+        let commitments = everyone(&mut parties, |party| party.commitment());
+        for party in parties.iter_mut() {
+            party.takeCommitments(&commitments);
+        }
+        for dealer in 0..4 {
+            for holder in 0..4 {
+                // Dealer 1 withholds the sub-share of holder 2.
+                if dealer == holder || (dealer, holder) == (0, 1) {
+                    continue;
+                }
+                let payload = parties[dealer].subshare(xs[holder]);
+                parties[holder].takeSubshare(xs[dealer], &payload);
+            }
+        }
+        // The complaint of holder 2 misses the round, so nobody asks dealer 1 to reveal it.
+        let mut complaints = everyone(&mut parties, |party| party.complaints());
+        assert_eq!(complaints.remove(&2), Some(b"[1]".to_vec()));
+        let answers = everyone(&mut parties, |party| party.answers(&complaints));
+        for party in parties.iter_mut() {
+            let qualified = party.qualify(&complaints, &answers);
+            match party.local {
+                2 => assert_eq!(qualified, Err(ShamirError::MissingContribution { from: "1".to_string(), to: "2".to_string() })),
+                _ => assert_eq!(qualified, Ok(())),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_key_generation_runs_between_separate_parties() {
+        let shares = DistributedKeyGeneration::runLocally::<FeldmanVSS>(2, &[3, 7, 9, 12, 20]).await.unwrap();
+        for share in shares.iter() {
+            assert_eq!(share.qualified, vec![3, 7, 9, 12, 20]);
+            assert_eq!(share.commitments, shares[0].commitments);
+            assert!(FeldmanVSS::verify(&share.share[0], &share.share[1], &share.commitments));
+        }
+        let values: Vec<Vec<FieldElement>> = shares.iter().map(|share| share.share.clone()).collect();
+        let secret = ShamirAlgorithm::new(Some(2)).combine(&values[1..4]).unwrap();
+        assert_eq!(secp256k1::mul_base(&secret), shares[0].commitments[0]);
+    }
+}
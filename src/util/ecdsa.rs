@@ -0,0 +1,295 @@
+use k256::{ecdsa::{signature::hazmat::PrehashVerifier, Signature, VerifyingKey}, elliptic_curve::{point::AffineCoordinates, scalar::IsHigh}, ProjectivePoint};
+
+use super::{feldman::FeldmanVSS, field::{Field, FieldElement}, messaging::MessageBus, secp256k1, shamir::{ShamirAlgorithm, ShamirError}};
+
+/// The four polynomials every holder deals while presigning.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PresignPolynomial {
+    /// Degree `t`, shares of the nonce `k`. The constant terms' commitments add up to `R = k * G`.
+    Nonce,
+    /// Degree `t`, shares of the mask `a` used to invert `k` without opening it.
+    Mask,
+    /// Degree `2t` sharing of zero that re-randomises the shares of `k * a` before they are opened.
+    ProductZero,
+    /// Degree `2t` sharing of zero that re-randomises the signature shares.
+    SignatureZero,
+}
+
+impl PresignPolynomial {
+    const ALL: [PresignPolynomial; 4] = [PresignPolynomial::Nonce, PresignPolynomial::Mask, PresignPolynomial::ProductZero, PresignPolynomial::SignatureZero];
+
+    fn sharesZero(&self) -> bool {
+        matches!(self, PresignPolynomial::ProductZero | PresignPolynomial::SignatureZero)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum EcdsaMessage {
+    /// Presigning round one, point to point: a sub-share of one of the dealer's polynomials and its Feldman commitments.
+    Dealing { polynomial: PresignPolynomial, subshare: FieldElement, commitments: Vec<ProjectivePoint> },
+    /// Presigning round two, broadcast: the sender's re-randomised share of `k * a`.
+    ProductShare(FieldElement),
+    /// Signing, broadcast: the sender's share of `s`.
+    SignatureShare(FieldElement),
+}
+
+/// A standard ECDSA signature with the recovery id Ethereum's `v` is derived from. `s` is always the low one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EcdsaSignature {
+    pub r: FieldElement,
+    pub s: FieldElement,
+    pub recovery_id: u8,
+}
+
+/// One holder's half of a presignature. It does not depend on the message, and must sign exactly one.
+pub struct Presignature {
+    pub index: usize,
+    pub x: FieldElement,
+    /// `R = k * G`, the same for every holder.
+    pub r_point: ProjectivePoint,
+    /// Share of `k^-1`, degree `t`.
+    nonce_inverse: FieldElement,
+    /// Share of `k^-1 * d`, degree `2t`.
+    nonce_inverse_key: FieldElement,
+    /// Share of zero, degree `2t`.
+    zero: FieldElement,
+}
+
+impl Presignature {
+    /// `r`, the x-coordinate of `R` modulo the group order.
+    pub fn r(&self) -> FieldElement {
+        secp256k1::reduce_bytes(&self.r_point.to_affine().x().into())
+    }
+
+    /// Broadcasts this holder's share of `s = k^-1 * (z + r * d)` for the message hash `z`, using up the presignature.
+    pub fn sign(self, hash: &[u8; 32], bus: &mut impl MessageBus<EcdsaMessage>) {
+        let z = secp256k1::reduce_bytes(hash);
+        let share = z * self.nonce_inverse + self.r() * self.nonce_inverse_key + self.zero;
+        bus.broadcast(self.index, EcdsaMessage::SignatureShare(share));
+    }
+}
+
+/// A key holder taking part in presigning.
+pub struct EcdsaParty {
+    pub index: usize,
+    pub x: FieldElement,
+    key_share: FieldElement,
+    degree: u8,
+    xs: Vec<FieldElement>,
+    nonce: FieldElement,
+    mask: FieldElement,
+    product_zero: FieldElement,
+    signature_zero: FieldElement,
+    r_point: ProjectivePoint,
+}
+
+#[allow(dead_code)]
+impl EcdsaParty {
+    /// `share` is the holder's `[x, y]` key share and `xs` the x-coordinates of all holders, in bus order.
+    pub fn new(index: usize, share: &[FieldElement], degree: u8, xs: Vec<FieldElement>) -> Self {
+        Self {
+            index,
+            x: share[0],
+            key_share: share[1],
+            degree,
+            xs,
+            nonce: FieldElement::zero(),
+            mask: FieldElement::zero(),
+            product_zero: FieldElement::zero(),
+            signature_zero: FieldElement::zero(),
+            r_point: ProjectivePoint::IDENTITY,
+        }
+    }
+
+    /// Presigning round one: deals the four presigning polynomials to every holder.
+    pub fn dealPresignature(&self, bus: &mut impl MessageBus<EcdsaMessage>) {
+        for polynomial in PresignPolynomial::ALL {
+            let (degree, constant) = if polynomial.sharesZero() { (self.degree * 2, FieldElement::zero()) } else { (self.degree, FieldElement::random()) };
+            let dealt = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(constant);
+            let commitments = FeldmanVSS::commit(&dealt);
+            for (to, x) in self.xs.iter().enumerate() {
+                bus.send(self.index, to, EcdsaMessage::Dealing { polynomial, subshare: dealt.evaluate_at(*x), commitments: commitments.clone() });
+            }
+        }
+    }
+
+    /// Verifies and adds up the round one dealings.
+    pub fn receiveDealings(&mut self, bus: &mut impl MessageBus<EcdsaMessage>) -> Result<(), ShamirError> {
+        for envelope in bus.receive(self.index) {
+            if let EcdsaMessage::Dealing { polynomial, subshare, commitments } = envelope.payload {
+                let degree = if polynomial.sharesZero() { self.degree * 2 } else { self.degree };
+                let valid = commitments.len() == degree as usize + 1
+                    && (!polynomial.sharesZero() || commitments[0] == ProjectivePoint::IDENTITY)
+                    && FeldmanVSS::verify(&self.x, &subshare, &commitments);
+                if !valid {
+                    return Err(ShamirError::InvalidContribution { from: self.xs[envelope.from].to_string(), to: self.x.to_string() });
+                }
+                match polynomial {
+                    PresignPolynomial::Nonce => {
+                        self.nonce = self.nonce + subshare;
+                        self.r_point += commitments[0];
+                    },
+                    PresignPolynomial::Mask => self.mask = self.mask + subshare,
+                    PresignPolynomial::ProductZero => self.product_zero = self.product_zero + subshare,
+                    PresignPolynomial::SignatureZero => self.signature_zero = self.signature_zero + subshare,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Presigning round two: opens the masked nonce `k * a` through the bus.
+    pub fn openMaskedNonce(&self, bus: &mut impl MessageBus<EcdsaMessage>) {
+        bus.broadcast(self.index, EcdsaMessage::ProductShare(self.nonce * self.mask + self.product_zero));
+    }
+
+    /// Interpolates `k * a` from the opened shares and turns the mask share into a share of `k^-1`.
+    pub fn finishPresignature(&mut self, bus: &mut impl MessageBus<EcdsaMessage>) -> Result<Presignature, ShamirError> {
+        let products: Vec<Vec<FieldElement>> = bus.receive(self.index)
+            .into_iter()
+            .filter_map(|envelope| match envelope.payload {
+                EcdsaMessage::ProductShare(value) => Some(vec![self.xs[envelope.from], value]),
+                _ => None,
+            })
+            .collect();
+        let masked_nonce = ShamirAlgorithm::new(Some(self.degree * 2)).combine(&products)?;
+        let nonce_inverse = self.mask * masked_nonce.inverse().ok_or(ShamirError::InvalidSignature)?;
+        Ok(Presignature {
+            index: self.index,
+            x: self.x,
+            r_point: self.r_point,
+            nonce_inverse,
+            nonce_inverse_key: nonce_inverse * self.key_share,
+            zero: self.signature_zero,
+        })
+    }
+}
+
+/// Threshold ECDSA over secp256k1 after Gennaro, Jarecki, Krawczyk and Rabin ("Robust threshold DSS signatures").
+/// Holders of a degree `t` Shamir sharing of the key jointly produce presignatures, which later sign a message hash in a
+/// single round. Everything is done with Shamir shares, so no party ever holds `k`, `k^-1` or the key, but products of two
+/// sharings have degree `2t`: the scheme needs an honest majority, at least `2t + 1` holders, and all of them take part.
+pub struct ThresholdEcdsa;
+
+#[allow(dead_code)]
+impl ThresholdEcdsa {
+    /// Holders a key of `degree` needs to sign at all, `2 * degree + 1`. Keys dealt to fewer holders can never sign.
+    pub fn requiredHolders(degree: u8) -> usize {
+        2 * degree as usize + 1
+    }
+
+    /// Runs both presigning rounds between the holders of `key_shares`, the i-th share being party `i` on the bus.
+    /// Every party finishes receiving a round before anyone sends in the next one.
+    pub fn presign(degree: u8, key_shares: &[Vec<FieldElement>], bus: &mut impl MessageBus<EcdsaMessage>) -> Result<Vec<Presignature>, ShamirError> {
+        let required = Self::requiredHolders(degree);
+        if key_shares.len() < required {
+            return Err(ShamirError::NotEnoughShares { required, given: key_shares.len() });
+        }
+        ShamirAlgorithm::new(Some(degree)).validateShares(key_shares)?;
+        let xs: Vec<FieldElement> = key_shares.iter().map(|share| share[0]).collect();
+        let mut parties: Vec<EcdsaParty> = key_shares.iter()
+            .enumerate()
+            .map(|(index, share)| EcdsaParty::new(index, share, degree, xs.clone()))
+            .collect();
+
+        for party in parties.iter() {
+            party.dealPresignature(bus);
+        }
+        for party in parties.iter_mut() {
+            party.receiveDealings(bus)?;
+        }
+        for party in parties.iter() {
+            party.openMaskedNonce(bus);
+        }
+        parties.iter_mut().map(|party| party.finishPresignature(bus)).collect()
+    }
+
+    /// Signs the 32 byte `hash` with fresh presignatures and checks the result against `public_key`.
+    /// Ethereum signs keccak-256 of the message, Bitcoin a double SHA-256, the hashing is left to the caller.
+    pub fn sign(degree: u8, presignatures: Vec<Presignature>, hash: &[u8; 32], public_key: &ProjectivePoint, bus: &mut impl MessageBus<EcdsaMessage>) -> Result<EcdsaSignature, ShamirError> {
+        let first = presignatures.first().ok_or(ShamirError::NotEnoughShares { required: Self::requiredHolders(degree), given: 0 })?;
+        let (index, r_point) = (first.index, first.r_point);
+        let xs: Vec<FieldElement> = presignatures.iter().map(|presignature| presignature.x).collect();
+        for presignature in presignatures {
+            presignature.sign(hash, bus);
+        }
+        let shares: Vec<Vec<FieldElement>> = bus.receive(index)
+            .into_iter()
+            .filter_map(|envelope| match envelope.payload {
+                EcdsaMessage::SignatureShare(value) => Some(vec![xs[envelope.from], value]),
+                _ => None,
+            })
+            .collect();
+        for other in 0..bus.parties() {
+            bus.receive(other);
+        }
+        Self::combineSignature(degree, &r_point, &shares, hash, public_key)
+    }
+
+    /// Interpolates `s` from the `[x, s_i]` signature shares, normalises it to low-s and verifies the signature.
+    pub fn combineSignature(degree: u8, r_point: &ProjectivePoint, shares: &[Vec<FieldElement>], hash: &[u8; 32], public_key: &ProjectivePoint) -> Result<EcdsaSignature, ShamirError> {
+        let s = ShamirAlgorithm::new(Some(degree * 2)).combine(shares)?;
+        let affine = r_point.to_affine();
+        let x_bytes: [u8; 32] = affine.x().into();
+        let r = secp256k1::reduce_bytes(&x_bytes);
+        let mut recovery_id = u8::from(bool::from(affine.y_is_odd())) | (u8::from(r.to_bytes() != x_bytes) << 1);
+        let s = if bool::from(secp256k1::to_scalar(&s).is_high()) {
+            recovery_id ^= 1;
+            -s
+        } else {
+            s
+        };
+
+        let signature = Signature::from_scalars(r.to_bytes(), s.to_bytes()).map_err(|_| ShamirError::InvalidSignature)?;
+        let key = VerifyingKey::from_affine(public_key.to_affine()).map_err(|_| ShamirError::InvalidSignature)?;
+        key.verify_prehash(hash, &signature).map_err(|_| ShamirError::InvalidSignature)?;
+        Ok(EcdsaSignature { r, s, recovery_id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::messaging::InMemoryBus;
+    use k256::ecdsa::RecoveryId;
+    use sha2::{Digest, Sha256};
+
+    fn shareKey(degree: u8, holders: i64) -> (FieldElement, Vec<Vec<FieldElement>>) {
+        let key = FieldElement::random();
+        let polynomial = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(key);
+        let shares = (1..=holders).map(|x| vec![FieldElement::from(x), polynomial.evaluate_at(FieldElement::from(x))]).collect();
+        (key, shares)
+    }
+
+    #[test]
+    fn test_threshold_signature_verifies_and_recovers() {
+        let (key, shares) = shareKey(2, 5);
+        let public_key = secp256k1::mul_base(&key);
+        let hash: [u8; 32] = Sha256::digest(b"rust_mpc threshold ecdsa").into();
+        let mut bus = InMemoryBus::new(shares.len());
+
+        let presignatures = ThresholdEcdsa::presign(2, &shares, &mut bus).unwrap();
+        let signature = ThresholdEcdsa::sign(2, presignatures, &hash, &public_key, &mut bus).unwrap();
+        assert!(!bool::from(secp256k1::to_scalar(&signature.s).is_high()));
+
+        let encoded = Signature::from_scalars(signature.r.to_bytes(), signature.s.to_bytes()).unwrap();
+        let recovered = VerifyingKey::recover_from_prehash(&hash, &encoded, RecoveryId::from_byte(signature.recovery_id).unwrap()).unwrap();
+        assert_eq!(recovered, VerifyingKey::from_affine(public_key.to_affine()).unwrap());
+    }
+
+    #[test]
+    fn test_presign_needs_an_honest_majority() {
+        let (_, shares) = shareKey(2, 4);
+        let mut bus = InMemoryBus::new(shares.len());
+        assert_eq!(ThresholdEcdsa::presign(2, &shares, &mut bus).err(), Some(ShamirError::NotEnoughShares { required: 5, given: 4 }));
+    }
+
+    #[test]
+    fn test_wrong_key_does_not_verify() {
+        let (_, shares) = shareKey(2, 5);
+        let mut bus = InMemoryBus::new(shares.len());
+        let presignatures = ThresholdEcdsa::presign(2, &shares, &mut bus).unwrap();
+        let other_key = secp256k1::mul_base(&FieldElement::random());
+        assert_eq!(ThresholdEcdsa::sign(2, presignatures, &[7u8; 32], &other_key, &mut bus), Err(ShamirError::InvalidSignature));
+    }
+}
@@ -0,0 +1,180 @@
+use bigdecimal::num_bigint::BigUint;
+use curve25519_dalek::{constants::ED25519_BASEPOINT_POINT, edwards::{CompressedEdwardsY, EdwardsPoint}, traits::Identity, Scalar};
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha512};
+
+use super::{dkg::DkgGroup, field::Field, modular::{ModInt, Modulus}, polynomials::Polynomial};
+
+pub const CURVE: &str = "ed25519";
+
+lazy_static! {
+    /// `L = 2^252 + 27742317777372353535851937790883648493`, the order of the ed25519 base point.
+    static ref ED25519_ORDER: BigUint = "7237005577332262213973186563042994240857116359379907606001950938285454250989".parse().unwrap();
+    /// Second generator for Pedersen commitments to Ed25519 keys, see `hashToPoint`.
+    pub static ref ED25519_PEDERSEN_H: EdwardsPoint = hashToPoint(b"rust_mpc/pedersen-vss/ed25519/H");
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Ed25519Order;
+
+impl Modulus for Ed25519Order {
+    fn modulus() -> &'static BigUint {
+        &ED25519_ORDER
+    }
+}
+
+/// Integers modulo the ed25519 group order, where Ed25519 secret scalars and their Shamir shares live.
+pub type Ed25519Scalar = ModInt<Ed25519Order>;
+
+pub fn to_scalar(value: &Ed25519Scalar) -> Scalar {
+    let mut bytes = [0u8; 32];
+    let le = value.value.to_bytes_le();
+    bytes[..le.len()].copy_from_slice(&le);
+    Scalar::from_bytes_mod_order(bytes)
+}
+
+pub fn from_scalar(scalar: &Scalar) -> Ed25519Scalar {
+    Ed25519Scalar::new(BigUint::from_bytes_le(scalar.as_bytes()))
+}
+
+/// Little-endian encoding, as Ed25519 and RFC 9591 serialize scalars.
+pub fn scalar_to_bytes(value: &Ed25519Scalar) -> [u8; 32] {
+    to_scalar(value).to_bytes()
+}
+
+/// SHA-512 of the concatenated `parts`, reduced modulo the group order.
+pub fn hash_to_scalar(parts: &[&[u8]]) -> Ed25519Scalar {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    from_scalar(&Scalar::from_bytes_mod_order_wide(&hasher.finalize().into()))
+}
+
+/// Try-and-increment hashing into the prime order subgroup: the first hash of `tag` and a counter that decompresses to a
+/// point, times the cofactor. Nobody knows its discrete logarithm to `B`.
+pub fn hashToPoint(tag: &[u8]) -> EdwardsPoint {
+    let mut counter: u32 = 0;
+    loop {
+        let digest = Sha512::new().chain_update(tag).chain_update(counter.to_be_bytes()).finalize();
+        let bytes: [u8; 32] = digest[..32].try_into().unwrap();
+        if let Some(point) = CompressedEdwardsY(bytes).decompress().map(|point| point.mul_by_cofactor()) {
+            if point != EdwardsPoint::identity() {
+                return point;
+            }
+        }
+        counter += 1;
+    }
+}
+
+/// Returns `value * B`.
+pub fn mul_base(value: &Ed25519Scalar) -> EdwardsPoint {
+    ED25519_BASEPOINT_POINT * to_scalar(value)
+}
+
+/// Hex of the 32 byte compressed point, the standard form of an Ed25519 public key.
+pub fn point_to_hex(point: &EdwardsPoint) -> String {
+    hex::encode(point.compress().as_bytes())
+}
+
+pub fn point_from_hex(encoded: &str) -> Option<EdwardsPoint> {
+    let bytes: [u8; 32] = hex::decode(encoded.trim_start_matches("0x")).ok()?.try_into().ok()?;
+    CompressedEdwardsY(bytes).decompress()
+}
+
+/// Feldman verifiable secret sharing over edwards25519, the counterpart of `FeldmanVSS` for Ed25519 keys.
+pub struct Ed25519FeldmanVSS;
+
+#[allow(dead_code)]
+impl Ed25519FeldmanVSS {
+    pub const SCHEME: &'static str = "feldman-ed25519";
+
+    pub fn commit(polynomial: &Polynomial<Ed25519Scalar>) -> Vec<EdwardsPoint> {
+        polynomial.coefficients.iter().map(mul_base).collect()
+    }
+
+    pub fn evaluateCommitments(commitments: &[EdwardsPoint], x: &Ed25519Scalar) -> EdwardsPoint {
+        let x = to_scalar(x);
+        commitments.iter().rev().fold(EdwardsPoint::identity(), |sum, commitment| sum * x + commitment)
+    }
+
+    pub fn verify(x: &Ed25519Scalar, y: &Ed25519Scalar, commitments: &[EdwardsPoint]) -> bool {
+        if commitments.is_empty() || x.is_zero() {
+            return false;
+        }
+        Self::evaluateCommitments(commitments, x) == mul_base(y)
+    }
+
+    pub fn encodeCommitments(commitments: &[EdwardsPoint]) -> Vec<String> {
+        commitments.iter().map(point_to_hex).collect()
+    }
+
+    pub fn decodeCommitments(commitments: &[String]) -> Option<Vec<EdwardsPoint>> {
+        commitments.iter().map(|c| point_from_hex(c)).collect()
+    }
+}
+
+impl DkgGroup for Ed25519FeldmanVSS {
+    type Scalar = Ed25519Scalar;
+    type Point = EdwardsPoint;
+
+    fn mulBase(value: &Ed25519Scalar) -> EdwardsPoint {
+        mul_base(value)
+    }
+
+    fn blindingBase() -> EdwardsPoint {
+        *ED25519_PEDERSEN_H
+    }
+
+    fn mul(point: &EdwardsPoint, value: &Ed25519Scalar) -> EdwardsPoint {
+        point * to_scalar(value)
+    }
+
+    fn add(a: &EdwardsPoint, b: &EdwardsPoint) -> EdwardsPoint {
+        a + b
+    }
+
+    fn identity() -> EdwardsPoint {
+        EdwardsPoint::identity()
+    }
+
+    fn encodePoint(point: &EdwardsPoint) -> String {
+        point_to_hex(point)
+    }
+
+    fn decodePoint(encoded: &str) -> Option<EdwardsPoint> {
+        point_from_hex(encoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{dkg::DistributedKeyGeneration, shamir::ShamirAlgorithm};
+
+    #[test]
+    fn test_order_matches_curve25519_dalek() {
+        assert_eq!(to_scalar(&-Ed25519Scalar::one()) + Scalar::ONE, Scalar::ZERO);
+        let value = Ed25519Scalar::random();
+        assert_eq!(from_scalar(&to_scalar(&value)), value);
+    }
+
+    #[tokio::test]
+    async fn test_key_generation_shares_the_public_key() {
+        let output = DistributedKeyGeneration::runLocally::<Ed25519FeldmanVSS>(2, &[1, 2, 3, 4]).await.unwrap();
+        let commitments = output[0].commitments.clone();
+        let shares: Vec<Vec<Ed25519Scalar>> = output.iter().map(|share| share.share.clone()).collect();
+        for share in shares.iter() {
+            assert!(Ed25519FeldmanVSS::verify(&share[0], &share[1], &commitments));
+        }
+        let secret = ShamirAlgorithm::new(Some(2)).combine(&shares[1..]).unwrap();
+        assert_eq!(mul_base(&secret), commitments[0]);
+        assert_eq!(Ed25519FeldmanVSS::decodeCommitments(&Ed25519FeldmanVSS::encodeCommitments(&commitments)), Some(commitments));
+    }
+
+    #[test]
+    fn test_blinding_base_is_in_the_prime_order_subgroup() {
+        assert!(ED25519_PEDERSEN_H.is_torsion_free());
+        assert_ne!(*ED25519_PEDERSEN_H, ED25519_BASEPOINT_POINT);
+    }
+}
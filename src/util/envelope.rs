@@ -0,0 +1,224 @@
+use std::fmt;
+
+use bech32::{FromBase32, ToBase32, Variant};
+use bigdecimal::num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use super::{bls::{self, BlsFeldmanVSS}, ed25519::{self, Ed25519FeldmanVSS}, feldman::FeldmanVSS, pedersen::PedersenVSS, secp256k1};
+
+/// Human readable part of the bech32m text form.
+pub const HRP: &str = "share";
+pub const VERSION: u8 = 1;
+/// Scheme tags by id. Id 0 is a share stored without commitments.
+const SCHEMES: [&str; 5] = ["", FeldmanVSS::SCHEME, PedersenVSS::SCHEME, Ed25519FeldmanVSS::SCHEME, BlsFeldmanVSS::SCHEME];
+const CURVES: [&str; 3] = [secp256k1::CURVE, ed25519::CURVE, bls::CURVE];
+const FINGERPRINT_LENGTH: usize = 8;
+const CRC_LENGTH: usize = 4;
+
+/// A share with everything needed to use it on its own: which scheme, field and threshold it belongs to, which key, and a checksum.
+///
+/// Binary layout, integers big-endian:
+/// `version | scheme id | curve id | threshold | fingerprint (8) | x length | x | value count | (value length | value)* | CRC-32`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareEnvelope {
+    pub version: u8,
+    /// Scheme tag as in `SecretCommitment.scheme`, empty for a share without commitments.
+    pub scheme: String,
+    /// Curve tag as in `PartialSecret.curve`, i.e. the field of the share values.
+    pub curve: String,
+    /// Number of shares needed to combine, `degree + 1`.
+    pub threshold: u8,
+    /// First 8 bytes of the SHA-256 of the public key the share belongs to.
+    pub fingerprint: [u8; FINGERPRINT_LENGTH],
+    /// x-coordinate of the holder, as an unsigned big-endian integer.
+    pub index: Vec<u8>,
+    /// `y`, or `y` and `r` for Pedersen shares, as unsigned big-endian integers.
+    pub values: Vec<Vec<u8>>,
+}
+
+/// A share envelope is malformed, fails its checksum or belongs to another key, the string says how.
+#[derive(Debug, PartialEq, Eq)]
+pub struct EnvelopeError(pub String);
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid share envelope: {}", self.0)
+    }
+}
+
+fn envelopeError(reason: &str) -> EnvelopeError {
+    EnvelopeError(reason.to_string())
+}
+
+#[allow(dead_code)]
+impl ShareEnvelope {
+    /// Wraps a share in the `x||y` or `x||y||r` form. `None` if a part is not a decimal integer.
+    pub fn fromShareString(share: &str, scheme: &str, curve: &str, threshold: u8, public_key: &str) -> Option<Self> {
+        let parts = share.split("||")
+            .map(|part| part.parse::<BigUint>().ok().map(|value| value.to_bytes_be()))
+            .collect::<Option<Vec<Vec<u8>>>>()?;
+        if parts.len() < 2 {
+            return None;
+        }
+        Some(ShareEnvelope {
+            version: VERSION,
+            scheme: scheme.to_owned(),
+            curve: curve.to_owned(),
+            threshold,
+            fingerprint: Self::fingerprintOf(public_key),
+            index: parts[0].clone(),
+            values: parts[1..].to_vec(),
+        })
+    }
+
+    /// The `x||y` or `x||y||r` form `SecretService::parseShare` reads.
+    pub fn toShareString(&self) -> String {
+        [&self.index].into_iter()
+            .chain(self.values.iter())
+            .map(|value| BigUint::from_bytes_be(value).to_string())
+            .collect::<Vec<String>>()
+            .join("||")
+    }
+
+    pub fn fingerprintOf(public_key: &str) -> [u8; FINGERPRINT_LENGTH] {
+        let key = hex::decode(public_key.trim_start_matches("0x")).unwrap_or_else(|_| public_key.as_bytes().to_vec());
+        Sha256::digest(key)[..FINGERPRINT_LENGTH].try_into().unwrap()
+    }
+
+    pub fn matchesKey(&self, public_key: &str) -> bool {
+        self.fingerprint == Self::fingerprintOf(public_key)
+    }
+
+    /// Checks that the envelope holds a share of `public_key` as it was dealt: same scheme, curve and threshold.
+    pub fn checkKey(&self, scheme: &str, curve: &str, threshold: u8, public_key: &str) -> Result<(), EnvelopeError> {
+        if !self.matchesKey(public_key) {
+            return Err(envelopeError("the share belongs to another key"));
+        }
+        if self.scheme != scheme || self.curve != curve {
+            return Err(EnvelopeError(format!("the share is a {} share on {}, the key is {} on {}", self.scheme, self.curve, scheme, curve)));
+        }
+        if self.threshold != threshold {
+            return Err(EnvelopeError(format!("the share has a threshold of {}, the key {}", self.threshold, threshold)));
+        }
+        Ok(())
+    }
+
+    pub fn toBytes(&self) -> Result<Vec<u8>, EnvelopeError> {
+        let scheme = SCHEMES.iter().position(|scheme| *scheme == self.scheme).ok_or_else(|| envelopeError("unknown scheme"))? as u8;
+        let curve = CURVES.iter().position(|curve| *curve == self.curve).ok_or_else(|| envelopeError("unknown curve"))? as u8;
+        let mut bytes = vec![self.version, scheme, curve, self.threshold];
+        bytes.extend_from_slice(&self.fingerprint);
+        bytes.push(self.index.len() as u8);
+        bytes.extend_from_slice(&self.index);
+        bytes.push(self.values.len() as u8);
+        for value in self.values.iter() {
+            bytes.push(value.len() as u8);
+            bytes.extend_from_slice(value);
+        }
+        bytes.extend_from_slice(&crc32fast::hash(&bytes).to_be_bytes());
+        Ok(bytes)
+    }
+
+    pub fn fromBytes(bytes: &[u8]) -> Result<Self, EnvelopeError> {
+        if bytes.len() < 4 + FINGERPRINT_LENGTH + 2 + CRC_LENGTH {
+            return Err(envelopeError("too short"));
+        }
+        let (body, crc) = bytes.split_at(bytes.len() - CRC_LENGTH);
+        if crc32fast::hash(body).to_be_bytes() != crc {
+            return Err(envelopeError("checksum does not match"));
+        }
+        if body[0] != VERSION {
+            return Err(EnvelopeError(format!("version {} is not supported", body[0])));
+        }
+        let scheme = SCHEMES.get(body[1] as usize).ok_or_else(|| envelopeError("unknown scheme"))?;
+        let curve = CURVES.get(body[2] as usize).ok_or_else(|| envelopeError("unknown curve"))?;
+
+        let mut position = 4 + FINGERPRINT_LENGTH;
+        let mut read = |length: usize| -> Result<&[u8], EnvelopeError> {
+            let slice = body.get(position..position + length).ok_or_else(|| envelopeError("truncated"))?;
+            position += length;
+            Ok(slice)
+        };
+        let index_length = read(1)?[0] as usize;
+        let index = read(index_length)?.to_vec();
+        let count = read(1)?[0] as usize;
+        let mut values: Vec<Vec<u8>> = vec![];
+        for _ in 0..count {
+            let length = read(1)?[0] as usize;
+            values.push(read(length)?.to_vec());
+        }
+        if position != body.len() {
+            return Err(envelopeError("trailing bytes"));
+        }
+        Ok(ShareEnvelope {
+            version: body[0],
+            scheme: scheme.to_string(),
+            curve: curve.to_string(),
+            threshold: body[3],
+            fingerprint: body[4..4 + FINGERPRINT_LENGTH].try_into().unwrap(),
+            index,
+            values,
+        })
+    }
+
+    /// bech32m rendering of `toBytes`, e.g. `share1q...`.
+    pub fn toText(&self) -> Result<String, EnvelopeError> {
+        bech32::encode(HRP, self.toBytes()?.to_base32(), Variant::Bech32m).map_err(|err| EnvelopeError(err.to_string()))
+    }
+
+    pub fn fromText(text: &str) -> Result<Self, EnvelopeError> {
+        let (hrp, data, variant) = bech32::decode(text).map_err(|err| EnvelopeError(err.to_string()))?;
+        if hrp != HRP || variant != Variant::Bech32m {
+            return Err(envelopeError("not a bech32m share"));
+        }
+        Self::fromBytes(&Vec::<u8>::from_base32(&data).map_err(|err| EnvelopeError(err.to_string()))?)
+    }
+
+    /// Whether a stored `partial_secret` is already in the text form rather than the legacy `x||y`.
+    pub fn isText(partial_secret: &str) -> bool {
+        partial_secret.starts_with(HRP) && partial_secret.as_bytes().get(HRP.len()) == Some(&b'1')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::field::{Field, FieldElement};
+
+    #[test]
+    fn test_round_trip_through_text() {
+        let y = FieldElement::random();
+        let share = format!("17||{}", y);
+        let envelope = ShareEnvelope::fromShareString(&share, FeldmanVSS::SCHEME, secp256k1::CURVE, 3, "02abcdef").unwrap();
+        let text = envelope.toText().unwrap();
+        assert!(ShareEnvelope::isText(&text));
+        assert!(!ShareEnvelope::isText(&share));
+
+        let decoded = ShareEnvelope::fromText(&text).unwrap();
+        assert_eq!(decoded, envelope);
+        assert_eq!(decoded.toShareString(), share);
+        assert!(decoded.matchesKey("02abcdef"));
+        assert!(!decoded.matchesKey("02abcdee"));
+        assert_eq!(decoded.checkKey(FeldmanVSS::SCHEME, secp256k1::CURVE, 3, "02abcdef"), Ok(()));
+        assert!(decoded.checkKey(FeldmanVSS::SCHEME, secp256k1::CURVE, 3, "02abcdee").is_err());
+        assert!(decoded.checkKey(PedersenVSS::SCHEME, secp256k1::CURVE, 3, "02abcdef").is_err());
+        assert!(decoded.checkKey(FeldmanVSS::SCHEME, ed25519::CURVE, 3, "02abcdef").is_err());
+        assert!(decoded.checkKey(FeldmanVSS::SCHEME, secp256k1::CURVE, 2, "02abcdef").is_err());
+    }
+
+    #[test]
+    fn test_rejects_corruption() {
+        let envelope = ShareEnvelope::fromShareString("5||7||11", PedersenVSS::SCHEME, secp256k1::CURVE, 2, "c0ffee").unwrap();
+        let mut bytes = envelope.toBytes().unwrap();
+        assert_eq!(ShareEnvelope::fromBytes(&bytes), Ok(envelope.clone()));
+        bytes[12] ^= 1;
+        assert_eq!(ShareEnvelope::fromBytes(&bytes), Err(envelopeError("checksum does not match")));
+
+        let mut text = envelope.toText().unwrap();
+        text.replace_range(10..11, if &text[10..11] == "q" { "p" } else { "q" });
+        assert!(ShareEnvelope::fromText(&text).is_err());
+        assert!(ShareEnvelope::fromShareString("5", FeldmanVSS::SCHEME, secp256k1::CURVE, 2, "c0ffee").is_none());
+        let unknown = ShareEnvelope { scheme: "shamir-x".to_string(), ..envelope };
+        assert_eq!(unknown.toText(), Err(envelopeError("unknown scheme")));
+    }
+}
@@ -0,0 +1,98 @@
+use k256::ProjectivePoint;
+
+use super::{dkg::DkgGroup, field::{Field, FieldElement}, pedersen::PEDERSEN_H, polynomials::Polynomial, secp256k1};
+
+/// Feldman verifiable secret sharing over secp256k1. The dealer publishes `C_i = a_i * G` for every coefficient `a_i`
+/// of the sharing polynomial, which lets a holder check `y * G == sum(x^i * C_i)` without learning anything beyond `C_0 = secret * G`.
+pub struct FeldmanVSS;
+
+#[allow(dead_code)]
+impl FeldmanVSS {
+    pub const SCHEME: &'static str = "feldman";
+
+    /// Commits to every coefficient of `polynomial`, lowest degree first.
+    pub fn commit(polynomial: &Polynomial<FieldElement>) -> Vec<ProjectivePoint> {
+        polynomial.coefficients.iter().map(secp256k1::mul_base).collect()
+    }
+
+    /// Evaluates the committed polynomial "in the exponent" at `x`, giving `f(x) * G`.
+    pub fn evaluateCommitments(commitments: &[ProjectivePoint], x: &FieldElement) -> ProjectivePoint {
+        let x = secp256k1::to_scalar(x);
+        commitments.iter().rev().fold(ProjectivePoint::IDENTITY, |sum, commitment| sum * x + commitment)
+    }
+
+    /// Checks that the share `(x, y)` lies on the committed polynomial.
+    pub fn verify(x: &FieldElement, y: &FieldElement, commitments: &[ProjectivePoint]) -> bool {
+        if commitments.is_empty() || x.is_zero() {
+            return false;
+        }
+        Self::evaluateCommitments(commitments, x) == secp256k1::mul_base(y)
+    }
+
+    /// Checks that the committed secret is the private key of `public_key`.
+    pub fn verifyPublicKey(commitments: &[ProjectivePoint], public_key: &ProjectivePoint) -> bool {
+        commitments.first() == Some(public_key)
+    }
+
+    pub fn encodeCommitments(commitments: &[ProjectivePoint]) -> Vec<String> {
+        commitments.iter().map(secp256k1::point_to_hex).collect()
+    }
+
+    pub fn decodeCommitments(commitments: &[String]) -> Option<Vec<ProjectivePoint>> {
+        commitments.iter().map(|c| secp256k1::point_from_hex(c)).collect()
+    }
+}
+
+impl DkgGroup for FeldmanVSS {
+    type Scalar = FieldElement;
+    type Point = ProjectivePoint;
+
+    fn mulBase(value: &FieldElement) -> ProjectivePoint {
+        secp256k1::mul_base(value)
+    }
+
+    fn blindingBase() -> ProjectivePoint {
+        *PEDERSEN_H
+    }
+
+    fn mul(point: &ProjectivePoint, value: &FieldElement) -> ProjectivePoint {
+        *point * secp256k1::to_scalar(value)
+    }
+
+    fn add(a: &ProjectivePoint, b: &ProjectivePoint) -> ProjectivePoint {
+        a + b
+    }
+
+    fn identity() -> ProjectivePoint {
+        ProjectivePoint::IDENTITY
+    }
+
+    fn encodePoint(point: &ProjectivePoint) -> String {
+        secp256k1::point_to_hex(point)
+    }
+
+    fn decodePoint(encoded: &str) -> Option<ProjectivePoint> {
+        secp256k1::point_from_hex(encoded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::shamir::ShamirAlgorithm;
+
+    #[test]
+    fn test_shares_verify_against_commitments() {
+        let secret = FieldElement::random();
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(secret);
+        let commitments = FeldmanVSS::decodeCommitments(&FeldmanVSS::encodeCommitments(&FeldmanVSS::commit(&polynomial))).unwrap();
+        for x in 1..=5 {
+            let x = FieldElement::from(x);
+            let y = polynomial.evaluate_at(x);
+            assert!(FeldmanVSS::verify(&x, &y, &commitments));
+            assert!(!FeldmanVSS::verify(&x, &(y + FieldElement::one()), &commitments));
+        }
+        assert!(FeldmanVSS::verifyPublicKey(&commitments, &secp256k1::mul_base(&secret)));
+        assert!(!FeldmanVSS::verifyPublicKey(&commitments, &secp256k1::mul_base(&FieldElement::random())));
+    }
+}
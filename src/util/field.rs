@@ -0,0 +1,231 @@
+use std::{fmt, ops::{Add, Mul, Neg, Sub}, str::FromStr};
+
+use lazy_static::lazy_static;
+use primitive_types::{U256, U512};
+use rand::RngCore;
+
+lazy_static! {
+    /// Order `n` of the secp256k1 generator. Shares are computed modulo `n` so that any quorum combines back into a valid private key.
+    pub static ref SECP256K1_ORDER: U256 = U256::from_str_radix("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141", 16).unwrap();
+}
+
+/// Arithmetic shared by every coefficient domain `Polynomial` and `ShamirAlgorithm` work over.
+/// Addition, multiplication and negation come from the `std::ops` supertraits.
+pub trait Field: Sized + Clone + PartialEq + fmt::Debug + fmt::Display + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self> {
+    fn zero() -> Self;
+
+    fn one() -> Self;
+
+    /// Multiplicative inverse, `None` when the element has none (zero, or a zero divisor in a ring).
+    fn inverse(&self) -> Option<Self>;
+
+    /// Uniformly random element, used for polynomial coefficients.
+    fn random() -> Self;
+
+    /// Embeds a small integer, used for share x-coordinates.
+    fn from_u64(value: u64) -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+}
+
+/// An element of the prime field `Z/nZ`, where `n` is the secp256k1 group order.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FieldElement {
+    /// Canonical representative, always strictly lower than `SECP256K1_ORDER`.
+    pub value: U256,
+}
+
+impl fmt::Debug for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FieldElement({})", self.value)
+    }
+}
+
+impl fmt::Display for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl FromStr for FieldElement {
+    type Err = String;
+
+    /// Parses the decimal representation produced by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = U256::from_dec_str(s).map_err(|_| format!("{} is not a decimal field element", s))?;
+        if value >= *SECP256K1_ORDER {
+            return Err(format!("{} is not lower than the field order", s));
+        }
+        Ok(Self { value })
+    }
+}
+
+impl From<i64> for FieldElement {
+    fn from(value: i64) -> Self {
+        let magnitude = Self::new(U256::from(value.unsigned_abs()));
+        if value < 0 { -magnitude } else { magnitude }
+    }
+}
+
+#[allow(dead_code)]
+impl FieldElement {
+    /// Returns the element congruent to `value` modulo the field order.
+    pub fn new(value: U256) -> Self {
+        Self { value: value % *SECP256K1_ORDER }
+    }
+
+    /// Parses a big-endian hex string (with or without `0x`). Returns `None` when the string is not hex or is not a canonical element.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim_start_matches("0x");
+        let value = U256::from_str_radix(hex, 16).ok()?;
+        if value >= *SECP256K1_ORDER {
+            return None;
+        }
+        Some(Self { value })
+    }
+
+    /// Returns the element as 64 lowercase hex characters, the usual encoding of a secp256k1 private key.
+    pub fn to_hex(self) -> String {
+        self.to_bytes().iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    pub fn from_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let value = U256::from_big_endian(bytes);
+        if value >= *SECP256K1_ORDER {
+            return None;
+        }
+        Some(Self { value })
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        self.value.to_big_endian(&mut bytes);
+        bytes
+    }
+
+    /// Square-and-multiply exponentiation.
+    pub fn pow(&self, exponent: U256) -> Self {
+        let mut result = Self::one();
+        for i in (0..exponent.bits()).rev() {
+            result = result * result;
+            if exponent.bit(i) {
+                result = result * *self;
+            }
+        }
+        result
+    }
+}
+
+impl Field for FieldElement {
+    fn zero() -> Self {
+        Self { value: U256::zero() }
+    }
+
+    fn one() -> Self {
+        Self { value: U256::one() }
+    }
+
+    /// Multiplicative inverse through Fermat's little theorem, `a^(n-2)`. Returns `None` for zero.
+    fn inverse(&self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(self.pow(*SECP256K1_ORDER - U256::from(2)))
+    }
+
+    /// Draws a uniformly random element by rejection sampling 32 random bytes.
+    fn random() -> Self {
+        let mut bytes = [0u8; 32];
+        loop {
+            rand::thread_rng().fill_bytes(&mut bytes);
+            let value = U256::from_big_endian(&bytes);
+            if value < *SECP256K1_ORDER {
+                return Self { value };
+            }
+        }
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self::new(U256::from(value))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.value.is_zero()
+    }
+}
+
+impl Add for FieldElement {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (sum, overflow) = self.value.overflowing_add(other.value);
+        if overflow || sum >= *SECP256K1_ORDER {
+            Self { value: sum.overflowing_sub(*SECP256K1_ORDER).0 }
+        } else {
+            Self { value: sum }
+        }
+    }
+}
+
+impl Sub for FieldElement {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + (-other)
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        if self.is_zero() {
+            self
+        } else {
+            Self { value: *SECP256K1_ORDER - self.value }
+        }
+    }
+}
+
+impl Mul for FieldElement {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, other: Self) -> Self {
+        let product: U512 = self.value.full_mul(other.value) % U512::from(*SECP256K1_ORDER);
+        Self { value: U256::try_from(product).unwrap() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negative_wraps_around_order() {
+        assert_eq!(FieldElement::from(-1).value, *SECP256K1_ORDER - U256::one());
+        assert_eq!(FieldElement::from(-5) + FieldElement::from(5), FieldElement::zero());
+    }
+
+    #[test]
+    fn test_add_does_not_overflow() {
+        let big = FieldElement::from(-1);
+        assert_eq!(big + big, FieldElement::from(-2));
+    }
+
+    #[test]
+    fn test_inverse() {
+        let a = FieldElement::random();
+        assert_eq!(a * a.inverse().unwrap(), FieldElement::one());
+        assert_eq!(FieldElement::zero().inverse(), None);
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let a = FieldElement::random();
+        assert_eq!(FieldElement::from_hex(&a.to_hex()), Some(a));
+        assert_eq!(FieldElement::from_hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141"), None);
+    }
+}
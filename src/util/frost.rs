@@ -0,0 +1,342 @@
+use k256::{elliptic_curve::{bigint::U512, ops::Reduce, point::AffineCoordinates, sec1::ToEncodedPoint}, schnorr, ProjectivePoint, Scalar};
+use k256::ecdsa::signature::hazmat::PrehashVerifier;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use super::{feldman::FeldmanVSS, field::{Field, FieldElement}, messaging::MessageBus, secp256k1, shamir::{ShamirAlgorithm, ShamirError}};
+
+/// Context string of the ciphersuite, FROST(secp256k1, SHA-256) with BIP-340 challenges.
+const CONTEXT: &[u8] = b"FROST-secp256k1-SHA256-TR-v1";
+
+/// BIP-340 tagged hash, `SHA256(SHA256(tag) || SHA256(tag) || data)`.
+pub fn taggedHash(tag: &str, parts: &[&[u8]]) -> [u8; 32] {
+    let tag = Sha256::digest(tag.as_bytes());
+    let mut hasher = Sha256::new().chain_update(tag).chain_update(tag);
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+/// `expand_message_xmd` with SHA-256 (RFC 9380, section 5.3.1), followed by a reduction of 48 bytes modulo the group order.
+fn hashToScalar(label: &[u8], parts: &[&[u8]]) -> FieldElement {
+    let dst = [CONTEXT, label].concat();
+    let dst_prime = [dst.as_slice(), &[dst.len() as u8]].concat();
+    let mut hasher = Sha256::new().chain_update([0u8; 64]);
+    for part in parts {
+        hasher.update(part);
+    }
+    let b0 = hasher.chain_update([0u8, 48, 0]).chain_update(&dst_prime).finalize();
+    let b1 = Sha256::new().chain_update(b0).chain_update([1u8]).chain_update(&dst_prime).finalize();
+    let mixed: Vec<u8> = b0.iter().zip(b1.iter()).map(|(a, b)| a ^ b).collect();
+    let b2 = Sha256::new().chain_update(mixed).chain_update([2u8]).chain_update(&dst_prime).finalize();
+
+    let mut wide = [0u8; 64];
+    wide[16..48].copy_from_slice(&b1);
+    wide[48..].copy_from_slice(&b2[..16]);
+    secp256k1::from_scalar(&<Scalar as Reduce<U512>>::reduce_bytes(&wide.into()))
+}
+
+fn compressed(point: &ProjectivePoint) -> Vec<u8> {
+    point.to_affine().to_encoded_point(true).as_bytes().to_vec()
+}
+
+fn hasOddY(point: &ProjectivePoint) -> bool {
+    point.to_affine().y_is_odd().into()
+}
+
+fn xOnly(point: &ProjectivePoint) -> [u8; 32] {
+    point.to_affine().x().into()
+}
+
+/// A FROST group key prepared for a key-path Taproot spend (BIP-341): the internal key `P` is the committed secret with
+/// its y-coordinate made even, and the output key is `Q = P + t * G` with `t = hash_TapTweak(P || merkle_root)`.
+pub struct TaprootKey {
+    /// Feldman commitments of the group key, `commitments[0]` is the internal key as generated.
+    pub commitments: Vec<ProjectivePoint>,
+    pub output_key: ProjectivePoint,
+    tweak: FieldElement,
+}
+
+#[allow(dead_code)]
+impl TaprootKey {
+    /// `merkle_root` is the root of the script tree, or `None` for a key-path only output as in BIP-86.
+    pub fn new(commitments: Vec<ProjectivePoint>, merkle_root: Option<&[u8; 32]>) -> Self {
+        let internal_key = if hasOddY(&commitments[0]) { -commitments[0] } else { commitments[0] };
+        let tweak = secp256k1::reduce_bytes(&taggedHash("TapTweak", &[&xOnly(&internal_key), merkle_root.map(|root| root.as_slice()).unwrap_or(&[])]));
+        Self { output_key: internal_key + secp256k1::mul_base(&tweak), commitments, tweak }
+    }
+
+    /// The 32 byte x-only output key, what a P2TR output commits to.
+    pub fn outputKeyBytes(&self) -> [u8; 32] {
+        xOnly(&self.output_key)
+    }
+
+    /// Maps a holder's share of the generated key to its share of the key behind the even-y output key. The tweak can be
+    /// added to every share because the Lagrange coefficients of any quorum add up to one.
+    fn signingShare(&self, y: &FieldElement) -> FieldElement {
+        let share = if hasOddY(&self.commitments[0]) { -*y } else { *y };
+        let tweaked = share + self.tweak;
+        if hasOddY(&self.output_key) { -tweaked } else { tweaked }
+    }
+
+    /// The public counterpart of `signingShare` for the holder at `x`.
+    fn verifyingShare(&self, x: &FieldElement) -> ProjectivePoint {
+        let share = FeldmanVSS::evaluateCommitments(&self.commitments, x);
+        let share = if hasOddY(&self.commitments[0]) { -share } else { share };
+        let tweaked = share + secp256k1::mul_base(&self.tweak);
+        if hasOddY(&self.output_key) { -tweaked } else { tweaked }
+    }
+}
+
+/// Round one output of a signer, `D = d * G` and `E = e * G` for its hiding and binding nonces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SigningCommitment {
+    pub identifier: FieldElement,
+    pub hiding: ProjectivePoint,
+    pub binding: ProjectivePoint,
+}
+
+#[derive(Clone, Debug)]
+pub enum FrostMessage {
+    /// Round one, broadcast.
+    Commitment(Box<SigningCommitment>),
+    /// Round two, broadcast: the signer's `z_i`.
+    SignatureShare(FieldElement),
+}
+
+/// A BIP-340 signature, the x-coordinate of `R` followed by `z`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrostSignature {
+    pub r: [u8; 32],
+    pub z: FieldElement,
+}
+
+impl FrostSignature {
+    pub fn to_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0u8; 64];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32..].copy_from_slice(&self.z.to_bytes());
+        bytes
+    }
+}
+
+/// Values every signer derives from the commitment list: `R`, the binding factors and the challenge.
+struct SigningPackage {
+    binding_factors: Vec<FieldElement>,
+    group_commitment: ProjectivePoint,
+    challenge: FieldElement,
+}
+
+impl SigningPackage {
+    /// `commitments` must be sorted by identifier.
+    fn new(key: &TaprootKey, commitments: &[SigningCommitment], message: &[u8; 32]) -> Self {
+        let encoded: Vec<u8> = commitments.iter()
+            .flat_map(|commitment| [commitment.identifier.to_bytes().to_vec(), compressed(&commitment.hiding), compressed(&commitment.binding)].concat())
+            .collect();
+        let message_hash = Sha256::new().chain_update(CONTEXT).chain_update(b"msg").chain_update(message).finalize();
+        let commitment_hash = Sha256::new().chain_update(CONTEXT).chain_update(b"com").chain_update(&encoded).finalize();
+        let prefix = [key.outputKeyBytes().as_slice(), &message_hash, &commitment_hash].concat();
+
+        let binding_factors: Vec<FieldElement> = commitments.iter()
+            .map(|commitment| hashToScalar(b"rho", &[&prefix, &commitment.identifier.to_bytes()]))
+            .collect();
+        let group_commitment = commitments.iter()
+            .zip(binding_factors.iter())
+            .fold(ProjectivePoint::IDENTITY, |sum, (commitment, rho)| sum + commitment.hiding + commitment.binding * secp256k1::to_scalar(rho));
+        let challenge = secp256k1::reduce_bytes(&taggedHash("BIP0340/challenge", &[&xOnly(&group_commitment), &key.outputKeyBytes(), message]));
+        Self { binding_factors, group_commitment, challenge }
+    }
+
+    /// BIP-340 only knows even-y nonces, so when `R` has an odd y every signer negates its nonces.
+    fn nonceSign(&self) -> FieldElement {
+        if hasOddY(&self.group_commitment) { -FieldElement::one() } else { FieldElement::one() }
+    }
+}
+
+/// A key holder taking part in a FROST signing session.
+pub struct FrostSigner {
+    pub index: usize,
+    pub x: FieldElement,
+    share: FieldElement,
+    nonces: Option<(FieldElement, FieldElement)>,
+    commitments: Vec<SigningCommitment>,
+}
+
+#[allow(dead_code)]
+impl FrostSigner {
+    /// The `index`-th signer of a session, holding the `[x, f(x)]` pair `share`.
+    pub fn new(index: usize, share: &[FieldElement]) -> Result<Self, ShamirError> {
+        match share {
+            [x, share, ..] => Ok(Self { index, x: *x, share: *share, nonces: None, commitments: vec![] }),
+            _ => Err(ShamirError::MalformedShare(index)),
+        }
+    }
+
+    /// RFC 9591 `nonce_generate`, fresh randomness hashed together with the secret share.
+    fn generateNonce(&self) -> FieldElement {
+        let mut random = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut random);
+        hashToScalar(b"nonce", &[&random, &self.share.to_bytes()])
+    }
+
+    /// Round one: samples the hiding and binding nonces and broadcasts their commitments.
+    pub fn commit(&mut self, bus: &mut impl MessageBus<FrostMessage>) {
+        let (hiding, binding) = (self.generateNonce(), self.generateNonce());
+        self.nonces = Some((hiding, binding));
+        bus.broadcast(self.index, FrostMessage::Commitment(Box::new(SigningCommitment {
+            identifier: self.x,
+            hiding: secp256k1::mul_base(&hiding),
+            binding: secp256k1::mul_base(&binding),
+        })));
+    }
+
+    pub fn receiveCommitments(&mut self, bus: &mut impl MessageBus<FrostMessage>) {
+        self.commitments = bus.receive(self.index)
+            .into_iter()
+            .filter_map(|envelope| match envelope.payload {
+                FrostMessage::Commitment(commitment) => Some(*commitment),
+                _ => None,
+            })
+            .collect();
+        self.commitments.sort_by_key(|commitment| commitment.identifier.value);
+    }
+
+    /// Round two: broadcasts `z_i = d_i + rho_i * e_i + lambda_i * s_i * c`. The nonces are used up, so a second call fails.
+    pub fn sign(&mut self, key: &TaprootKey, message: &[u8; 32], bus: &mut impl MessageBus<FrostMessage>) -> Result<(), ShamirError> {
+        let (hiding, binding) = self.nonces.take().ok_or(ShamirError::InvalidContribution { from: self.x.to_string(), to: "coordinator".to_string() })?;
+        let position = self.commitments.iter().position(|commitment| commitment.identifier == self.x).ok_or(ShamirError::MalformedShare(self.index))?;
+        let package = SigningPackage::new(key, &self.commitments, message);
+        let xs: Vec<FieldElement> = self.commitments.iter().map(|commitment| commitment.identifier).collect();
+        let lagrange = ShamirAlgorithm::lagrangeCoefficients(&xs, &FieldElement::zero())?[position];
+
+        let nonce = package.nonceSign() * (hiding + package.binding_factors[position] * binding);
+        let share = nonce + lagrange * key.signingShare(&self.share) * package.challenge;
+        bus.broadcast(self.index, FrostMessage::SignatureShare(share));
+        Ok(())
+    }
+}
+
+/// FROST threshold Schnorr signatures (RFC 9591) producing BIP-340 signatures for Taproot key-path spends. Any `t + 1`
+/// holders of a degree `t` sharing sign in two rounds; the result is one ordinary Schnorr signature under the output key.
+pub struct Frost;
+
+#[allow(dead_code)]
+impl Frost {
+    /// Signs the 32 byte `message` (a Taproot sighash) with every holder of `shares`, the i-th share being party `i` on the bus.
+    /// Party 0 acts as the coordinator and checks every signature share before aggregating.
+    pub fn sign(degree: u8, key: &TaprootKey, shares: &[Vec<FieldElement>], message: &[u8; 32], bus: &mut impl MessageBus<FrostMessage>) -> Result<FrostSignature, ShamirError> {
+        ShamirAlgorithm::new(Some(degree)).validateShares(shares)?;
+        let mut signers = shares.iter().enumerate().map(|(index, share)| FrostSigner::new(index, share)).collect::<Result<Vec<_>, _>>()?;
+        for signer in signers.iter_mut() {
+            signer.commit(bus);
+        }
+        for signer in signers.iter_mut() {
+            signer.receiveCommitments(bus);
+        }
+        for signer in signers.iter_mut() {
+            signer.sign(key, message, bus)?;
+        }
+
+        let mut signature_shares: Vec<(FieldElement, FieldElement)> = bus.receive(0)
+            .into_iter()
+            .filter_map(|envelope| match envelope.payload {
+                FrostMessage::SignatureShare(z) => Some((shares[envelope.from][0], z)),
+                _ => None,
+            })
+            .collect();
+        for other in 1..bus.parties() {
+            bus.receive(other);
+        }
+        signature_shares.sort_by_key(|(x, _)| x.value);
+        Self::aggregate(key, &signers[0].commitments, &signature_shares, message)
+    }
+
+    /// Checks every `(x, z_i)` share against the signer's commitments and verifying share, then adds them up.
+    pub fn aggregate(key: &TaprootKey, commitments: &[SigningCommitment], signature_shares: &[(FieldElement, FieldElement)], message: &[u8; 32]) -> Result<FrostSignature, ShamirError> {
+        let package = SigningPackage::new(key, commitments, message);
+        let xs: Vec<FieldElement> = commitments.iter().map(|commitment| commitment.identifier).collect();
+        let lagrange = ShamirAlgorithm::lagrangeCoefficients(&xs, &FieldElement::zero())?;
+        if signature_shares.len() != commitments.len() {
+            return Err(ShamirError::NotEnoughShares { required: commitments.len(), given: signature_shares.len() });
+        }
+
+        let mut z = FieldElement::zero();
+        for (i, ((x, share), commitment)) in signature_shares.iter().zip(commitments).enumerate() {
+            let nonce = (commitment.hiding + commitment.binding * secp256k1::to_scalar(&package.binding_factors[i])) * secp256k1::to_scalar(&package.nonceSign());
+            let expected = nonce + key.verifyingShare(x) * secp256k1::to_scalar(&(lagrange[i] * package.challenge));
+            if *x != commitment.identifier || secp256k1::mul_base(share) != expected {
+                return Err(ShamirError::InvalidContribution { from: x.to_string(), to: "coordinator".to_string() });
+            }
+            z = z + *share;
+        }
+
+        let signature = FrostSignature { r: xOnly(&package.group_commitment), z };
+        let verifying_key = schnorr::VerifyingKey::from_bytes(&key.outputKeyBytes()).map_err(|_| ShamirError::InvalidSignature)?;
+        let encoded = schnorr::Signature::try_from(signature.to_bytes().as_slice()).map_err(|_| ShamirError::InvalidSignature)?;
+        verifying_key.verify_prehash(message, &encoded).map_err(|_| ShamirError::InvalidSignature)?;
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{dkg::DistributedKeyGeneration, messaging::InMemoryBus};
+
+    #[test]
+    fn test_tagged_hash_matches_bip340() {
+        let expected = Sha256::new()
+            .chain_update(Sha256::digest(b"TapTweak"))
+            .chain_update(Sha256::digest(b"TapTweak"))
+            .chain_update([1u8, 2, 3])
+            .finalize();
+        assert_eq!(taggedHash("TapTweak", &[&[1u8], &[2, 3]]), <[u8; 32]>::from(expected));
+    }
+
+    #[tokio::test]
+    async fn test_any_quorum_signs_for_the_output_key() {
+        let output = DistributedKeyGeneration::runLocally::<FeldmanVSS>(2, &[1, 2, 3, 4, 5]).await.unwrap();
+        let shares: Vec<Vec<FieldElement>> = output.iter().map(|share| share.share.clone()).collect();
+        let message: [u8; 32] = Sha256::digest(b"taproot sighash").into();
+
+        for merkle_root in [None, Some([9u8; 32])] {
+            let key = TaprootKey::new(output[0].commitments.clone(), merkle_root.as_ref());
+            for quorum in [&shares[..3], &shares[2..]] {
+                let mut bus = InMemoryBus::new(quorum.len());
+                let signature = Frost::sign(2, &key, quorum, &message, &mut bus).unwrap();
+                let verifying_key = schnorr::VerifyingKey::from_bytes(&key.outputKeyBytes()).unwrap();
+                let encoded = schnorr::Signature::try_from(signature.to_bytes().as_slice()).unwrap();
+                assert!(verifying_key.verify_prehash(&message, &encoded).is_ok());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_aggregate_blames_a_bad_share() {
+        let xs: Vec<FieldElement> = (1..=3).map(FieldElement::from).collect();
+        let output = DistributedKeyGeneration::runLocally::<FeldmanVSS>(2, &[1, 2, 3]).await.unwrap();
+        let key = TaprootKey::new(output[0].commitments.clone(), None);
+        let message = [3u8; 32];
+        let mut bus = InMemoryBus::new(3);
+        let mut signers: Vec<FrostSigner> = output.iter().map(|share| &share.share).enumerate().map(|(index, share)| FrostSigner::new(index, share).unwrap()).collect();
+        signers.iter_mut().for_each(|signer| signer.commit(&mut bus));
+        signers.iter_mut().for_each(|signer| signer.receiveCommitments(&mut bus));
+        signers.iter_mut().for_each(|signer| signer.sign(&key, &message, &mut bus).unwrap());
+        assert!(signers[0].sign(&key, &message, &mut bus).is_err());
+
+        let mut shares: Vec<(FieldElement, FieldElement)> = bus.receive(0)
+            .into_iter()
+            .filter_map(|envelope| match envelope.payload {
+                FrostMessage::SignatureShare(z) => Some((xs[envelope.from], z)),
+                _ => None,
+            })
+            .collect();
+        assert!(Frost::aggregate(&key, &signers[0].commitments, &shares, &message).is_ok());
+        shares[1].1 = shares[1].1 + FieldElement::one();
+        assert_eq!(
+            Frost::aggregate(&key, &signers[0].commitments, &shares, &message),
+            Err(ShamirError::InvalidContribution { from: "2".to_string(), to: "coordinator".to_string() })
+        );
+    }
+}
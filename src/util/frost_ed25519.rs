@@ -0,0 +1,220 @@
+use curve25519_dalek::{edwards::EdwardsPoint, traits::Identity};
+use ed25519_dalek::{Signature, VerifyingKey};
+use rand::RngCore;
+use sha2::{Digest, Sha512};
+
+use super::{ed25519::{self, Ed25519FeldmanVSS, Ed25519Scalar}, field::Field, messaging::MessageBus, shamir::{ShamirAlgorithm, ShamirError}};
+
+/// Context string of the RFC 9591 ciphersuite FROST(Ed25519, SHA-512).
+const CONTEXT: &[u8] = b"FROST-ED25519-SHA512-v1";
+
+/// Round one output of a signer, `D = d * B` and `E = e * B`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ed25519SigningCommitment {
+    pub identifier: Ed25519Scalar,
+    pub hiding: EdwardsPoint,
+    pub binding: EdwardsPoint,
+}
+
+#[derive(Clone, Debug)]
+pub enum Ed25519FrostMessage {
+    /// Round one, broadcast.
+    Commitment(Box<Ed25519SigningCommitment>),
+    /// Round two, broadcast: the signer's `z_i`.
+    SignatureShare(Ed25519Scalar),
+}
+
+/// Values every signer derives from the commitment list: `R`, the binding factors and the challenge.
+struct Ed25519SigningPackage {
+    binding_factors: Vec<Ed25519Scalar>,
+    group_commitment: EdwardsPoint,
+    challenge: Ed25519Scalar,
+}
+
+impl Ed25519SigningPackage {
+    /// `commitments` must be sorted by identifier.
+    fn new(public_key: &EdwardsPoint, commitments: &[Ed25519SigningCommitment], message: &[u8]) -> Self {
+        let encoded: Vec<u8> = commitments.iter()
+            .flat_map(|commitment| [
+                ed25519::scalar_to_bytes(&commitment.identifier).to_vec(),
+                commitment.hiding.compress().as_bytes().to_vec(),
+                commitment.binding.compress().as_bytes().to_vec(),
+            ].concat())
+            .collect();
+        let message_hash = Sha512::new().chain_update(CONTEXT).chain_update(b"msg").chain_update(message).finalize();
+        let commitment_hash = Sha512::new().chain_update(CONTEXT).chain_update(b"com").chain_update(&encoded).finalize();
+        let public_key = public_key.compress();
+
+        let binding_factors: Vec<Ed25519Scalar> = commitments.iter()
+            .map(|commitment| ed25519::hash_to_scalar(&[CONTEXT, b"rho", public_key.as_bytes(), &message_hash, &commitment_hash, &ed25519::scalar_to_bytes(&commitment.identifier)]))
+            .collect();
+        let group_commitment = commitments.iter()
+            .zip(binding_factors.iter())
+            .fold(EdwardsPoint::identity(), |sum, (commitment, rho)| sum + commitment.hiding + commitment.binding * ed25519::to_scalar(rho));
+        let challenge = ed25519::hash_to_scalar(&[group_commitment.compress().as_bytes(), public_key.as_bytes(), message]);
+        Self { binding_factors, group_commitment, challenge }
+    }
+}
+
+/// A key holder taking part in a FROST(Ed25519, SHA-512) signing session.
+pub struct Ed25519FrostSigner {
+    pub index: usize,
+    pub x: Ed25519Scalar,
+    share: Ed25519Scalar,
+    nonces: Option<(Ed25519Scalar, Ed25519Scalar)>,
+    commitments: Vec<Ed25519SigningCommitment>,
+}
+
+#[allow(dead_code)]
+impl Ed25519FrostSigner {
+    /// The `index`-th signer of a session, holding the `[x, f(x)]` pair `share`.
+    pub fn new(index: usize, share: &[Ed25519Scalar]) -> Result<Self, ShamirError> {
+        match share {
+            [x, share, ..] => Ok(Self { index, x: x.clone(), share: share.clone(), nonces: None, commitments: vec![] }),
+            _ => Err(ShamirError::MalformedShare(index)),
+        }
+    }
+
+    /// RFC 9591 `nonce_generate`.
+    fn generateNonce(&self) -> Ed25519Scalar {
+        let mut random = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut random);
+        ed25519::hash_to_scalar(&[CONTEXT, b"nonce", &random, &ed25519::scalar_to_bytes(&self.share)])
+    }
+
+    /// Round one: samples the hiding and binding nonces and broadcasts their commitments.
+    pub fn commit(&mut self, bus: &mut impl MessageBus<Ed25519FrostMessage>) {
+        let (hiding, binding) = (self.generateNonce(), self.generateNonce());
+        let commitment = Ed25519SigningCommitment { identifier: self.x.clone(), hiding: ed25519::mul_base(&hiding), binding: ed25519::mul_base(&binding) };
+        self.nonces = Some((hiding, binding));
+        bus.broadcast(self.index, Ed25519FrostMessage::Commitment(Box::new(commitment)));
+    }
+
+    pub fn receiveCommitments(&mut self, bus: &mut impl MessageBus<Ed25519FrostMessage>) {
+        self.commitments = bus.receive(self.index)
+            .into_iter()
+            .filter_map(|envelope| match envelope.payload {
+                Ed25519FrostMessage::Commitment(commitment) => Some(*commitment),
+                _ => None,
+            })
+            .collect();
+        self.commitments.sort_by(|a, b| a.identifier.value.cmp(&b.identifier.value));
+    }
+
+    /// Round two: broadcasts `z_i = d_i + rho_i * e_i + lambda_i * s_i * c`. The nonces are used up, so a second call fails.
+    pub fn sign(&mut self, public_key: &EdwardsPoint, message: &[u8], bus: &mut impl MessageBus<Ed25519FrostMessage>) -> Result<(), ShamirError> {
+        let (hiding, binding) = self.nonces.take().ok_or(ShamirError::InvalidContribution { from: self.x.to_string(), to: "coordinator".to_string() })?;
+        let position = self.commitments.iter().position(|commitment| commitment.identifier == self.x).ok_or(ShamirError::MalformedShare(self.index))?;
+        let package = Ed25519SigningPackage::new(public_key, &self.commitments, message);
+        let xs: Vec<Ed25519Scalar> = self.commitments.iter().map(|commitment| commitment.identifier.clone()).collect();
+        let lagrange = ShamirAlgorithm::lagrangeCoefficients(&xs, &Ed25519Scalar::zero())?[position].clone();
+
+        let share = hiding + package.binding_factors[position].clone() * binding + lagrange * self.share.clone() * package.challenge;
+        bus.broadcast(self.index, Ed25519FrostMessage::SignatureShare(share));
+        Ok(())
+    }
+}
+
+/// FROST threshold signatures over edwards25519 (RFC 9591, FROST(Ed25519, SHA-512)). Any `t + 1` holders of a degree `t`
+/// sharing sign in two rounds, and the result is a plain Ed25519 signature under the group public key.
+pub struct FrostEd25519;
+
+#[allow(dead_code)]
+impl FrostEd25519 {
+    /// Signs `message` with every holder of `shares`, the i-th share being party `i` on the bus. `commitments` are the Feldman
+    /// commitments of the key; party 0 acts as the coordinator. Returns the 64 byte `R || z` signature.
+    pub fn sign(degree: u8, commitments: &[EdwardsPoint], shares: &[Vec<Ed25519Scalar>], message: &[u8], bus: &mut impl MessageBus<Ed25519FrostMessage>) -> Result<[u8; 64], ShamirError> {
+        ShamirAlgorithm::new(Some(degree)).validateShares(shares)?;
+        let public_key = commitments.first().ok_or(ShamirError::InvalidCommitments)?;
+        let mut signers = shares.iter().enumerate().map(|(index, share)| Ed25519FrostSigner::new(index, share)).collect::<Result<Vec<_>, _>>()?;
+        for signer in signers.iter_mut() {
+            signer.commit(bus);
+        }
+        for signer in signers.iter_mut() {
+            signer.receiveCommitments(bus);
+        }
+        for signer in signers.iter_mut() {
+            signer.sign(public_key, message, bus)?;
+        }
+
+        let mut signature_shares: Vec<(Ed25519Scalar, Ed25519Scalar)> = bus.receive(0)
+            .into_iter()
+            .filter_map(|envelope| match envelope.payload {
+                Ed25519FrostMessage::SignatureShare(z) => Some((shares[envelope.from][0].clone(), z)),
+                _ => None,
+            })
+            .collect();
+        for other in 1..bus.parties() {
+            bus.receive(other);
+        }
+        signature_shares.sort_by(|a, b| a.0.value.cmp(&b.0.value));
+        Self::aggregate(commitments, &signers[0].commitments, &signature_shares, message)
+    }
+
+    /// Checks every `(x, z_i)` share against the signer's commitments and verifying share, adds them up and verifies the
+    /// result as a standard Ed25519 signature.
+    pub fn aggregate(key_commitments: &[EdwardsPoint], commitments: &[Ed25519SigningCommitment], signature_shares: &[(Ed25519Scalar, Ed25519Scalar)], message: &[u8]) -> Result<[u8; 64], ShamirError> {
+        let public_key = key_commitments.first().ok_or(ShamirError::InvalidCommitments)?;
+        let package = Ed25519SigningPackage::new(public_key, commitments, message);
+        let xs: Vec<Ed25519Scalar> = commitments.iter().map(|commitment| commitment.identifier.clone()).collect();
+        let lagrange = ShamirAlgorithm::lagrangeCoefficients(&xs, &Ed25519Scalar::zero())?;
+        if signature_shares.len() != commitments.len() {
+            return Err(ShamirError::NotEnoughShares { required: commitments.len(), given: signature_shares.len() });
+        }
+
+        let mut z = Ed25519Scalar::zero();
+        for (i, ((x, share), commitment)) in signature_shares.iter().zip(commitments).enumerate() {
+            let verifying_share = Ed25519FeldmanVSS::evaluateCommitments(key_commitments, x);
+            let expected = commitment.hiding + commitment.binding * ed25519::to_scalar(&package.binding_factors[i])
+                + verifying_share * ed25519::to_scalar(&(lagrange[i].clone() * package.challenge.clone()));
+            if *x != commitment.identifier || ed25519::mul_base(share) != expected {
+                return Err(ShamirError::InvalidContribution { from: x.to_string(), to: "coordinator".to_string() });
+            }
+            z = z + share.clone();
+        }
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(package.group_commitment.compress().as_bytes());
+        signature[32..].copy_from_slice(&ed25519::scalar_to_bytes(&z));
+        let verifying_key = VerifyingKey::from_bytes(public_key.compress().as_bytes()).map_err(|_| ShamirError::InvalidSignature)?;
+        verifying_key.verify_strict(message, &Signature::from_bytes(&signature)).map_err(|_| ShamirError::InvalidSignature)?;
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{dkg::DistributedKeyGeneration, messaging::InMemoryBus};
+    use ed25519_dalek::Verifier;
+
+    #[tokio::test]
+    async fn test_threshold_signature_verifies_with_ed25519_dalek() {
+        let (shares, commitments) = DistributedKeyGeneration::localFixture::<Ed25519FeldmanVSS>(2, &[7, 13, 21, 34, 55]).await;
+        let verifying_key = VerifyingKey::from_bytes(commitments[0].compress().as_bytes()).unwrap();
+        let message = b"transfer 1 SOL";
+
+        for quorum in [&shares[..3], &shares[1..], &[shares[4].clone(), shares[0].clone(), shares[2].clone()][..]] {
+            let mut bus = InMemoryBus::new(quorum.len());
+            let signature = FrostEd25519::sign(2, &commitments, quorum, message, &mut bus).unwrap();
+            assert!(verifying_key.verify(message, &Signature::from_bytes(&signature)).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_too_few_signers_are_rejected() {
+        let (shares, commitments) = DistributedKeyGeneration::localFixture::<Ed25519FeldmanVSS>(2, &[1, 2, 3]).await;
+        let mut bus = InMemoryBus::new(2);
+        assert_eq!(
+            FrostEd25519::sign(2, &commitments, &shares[..2], b"message", &mut bus),
+            Err(ShamirError::NotEnoughShares { required: 3, given: 2 })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_a_share_without_its_value_is_rejected() {
+        let (shares, _) = DistributedKeyGeneration::localFixture::<Ed25519FeldmanVSS>(2, &[1, 2, 3]).await;
+        assert!(Ed25519FrostSigner::new(0, &shares[0]).is_ok());
+        assert_eq!(Ed25519FrostSigner::new(1, &shares[1][..1]).err(), Some(ShamirError::MalformedShare(1)));
+    }
+}
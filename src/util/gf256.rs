@@ -0,0 +1,129 @@
+use std::{fmt, ops::{Add, Mul, Neg, Sub}};
+
+use rand::Rng;
+
+use super::field::Field;
+
+/// An element of GF(2^8) with the AES reduction polynomial `x^8 + x^4 + x^3 + x + 1`.
+/// Multiplication and inversion run in constant time without lookup tables.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct GF256(pub u8);
+
+impl fmt::Debug for GF256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "GF256({:#04x})", self.0)
+    }
+}
+
+impl fmt::Display for GF256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Add for GF256 {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, other: Self) -> Self {
+        GF256(self.0 ^ other.0)
+    }
+}
+
+impl Sub for GF256 {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, other: Self) -> Self {
+        GF256(self.0 ^ other.0)
+    }
+}
+
+impl Neg for GF256 {
+    type Output = Self;
+
+    /// Every element is its own additive inverse in characteristic 2.
+    fn neg(self) -> Self {
+        self
+    }
+}
+
+impl Mul for GF256 {
+    type Output = Self;
+
+    /// Russian peasant multiplication with masks instead of branches.
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn mul(self, other: Self) -> Self {
+        let (a, b) = (self.0, other.0);
+        let mut r: u8 = 0;
+        for i in (0..8).rev() {
+            r = ((b >> i & 1).wrapping_neg() & a) ^ ((r >> 7).wrapping_neg() & 0x1b) ^ r.wrapping_add(r);
+        }
+        GF256(r)
+    }
+}
+
+#[allow(dead_code)]
+impl GF256 {
+    /// Raises to the power 254, which is the inverse of every non-zero element and maps zero to zero.
+    pub fn pow254(self) -> Self {
+        let a = self;
+        let mut b = a * a;
+        let mut c = a * b;
+        b = c * c;
+        b = b * b;
+        c = b * c;
+        b = b * b;
+        b = b * b;
+        b = b * c;
+        b = b * b;
+        b = a * b;
+        b * b
+    }
+}
+
+impl Field for GF256 {
+    fn zero() -> Self {
+        GF256(0)
+    }
+
+    fn one() -> Self {
+        GF256(1)
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        if self.0 == 0 {
+            return None;
+        }
+        Some(self.pow254())
+    }
+
+    fn random() -> Self {
+        GF256(rand::thread_rng().gen())
+    }
+
+    /// Keeps the low byte, x-coordinates in GF(2^8) are limited to `1..=255`.
+    fn from_u64(value: u64) -> Self {
+        GF256(value as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multiply() {
+        // FIPS-197 section 4.2 example
+        assert_eq!(GF256(0x57) * GF256(0x83), GF256(0xc1));
+        assert_eq!(GF256(0x57) * GF256(0x13), GF256(0xfe));
+    }
+
+    #[test]
+    fn test_inverse_of_every_element() {
+        for a in 1..=255u8 {
+            assert_eq!(GF256(a) * GF256(a).inverse().unwrap(), GF256::one());
+        }
+        assert_eq!(GF256(0).inverse(), None);
+    }
+}
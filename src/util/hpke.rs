@@ -0,0 +1,197 @@
+use std::fmt;
+
+use chacha20poly1305::{aead::{Aead, KeyInit, Payload}, ChaCha20Poly1305};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+/// `info` every share is sealed under, binds the ciphertext to its use.
+pub const SHARE_INFO: &[u8] = b"node-rpc-rust share envelope";
+pub const KEY_LENGTH: usize = 32;
+
+const KEM_ID: u16 = 0x0020;
+const KDF_ID: u16 = 0x0001;
+const AEAD_ID: u16 = 0x0003;
+const MODE_BASE: u8 = 0x00;
+const NONCE_LENGTH: usize = 12;
+
+/// HPKE (RFC 9180) in base mode with DHKEM(X25519, HKDF-SHA256), HKDF-SHA256 and ChaCha20-Poly1305, single shot.
+/// A sealed message is `enc (32) | ciphertext | tag (16)`, which any RFC 9180 implementation with this suite opens.
+pub struct Hpke;
+
+fn extract(salt: &[u8], ikm: &[u8]) -> [u8; 32] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(salt).expect("HMAC takes keys of any length");
+    mac.update(ikm);
+    mac.finalize().into_bytes().into()
+}
+
+fn expand(prk: &[u8], info: &[u8], length: usize) -> Vec<u8> {
+    let mut okm = Vec::with_capacity(length);
+    let mut block: Vec<u8> = Vec::new();
+    let mut counter = 1u8;
+    while okm.len() < length {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(prk).expect("HMAC takes keys of any length");
+        mac.update(&block);
+        mac.update(info);
+        mac.update(&[counter]);
+        block = mac.finalize().into_bytes().to_vec();
+        okm.extend_from_slice(&block);
+        counter += 1;
+    }
+    okm.truncate(length);
+    okm
+}
+
+fn labeledExtract(suite_id: &[u8], salt: &[u8], label: &[u8], ikm: &[u8]) -> [u8; 32] {
+    extract(salt, &[b"HPKE-v1", suite_id, label, ikm].concat())
+}
+
+fn labeledExpand(suite_id: &[u8], prk: &[u8], label: &[u8], info: &[u8], length: usize) -> Vec<u8> {
+    expand(prk, &[&(length as u16).to_be_bytes()[..], b"HPKE-v1", suite_id, label, info].concat(), length)
+}
+
+fn kemSuiteId() -> Vec<u8> {
+    [&b"KEM"[..], &KEM_ID.to_be_bytes()].concat()
+}
+
+fn hpkeSuiteId() -> Vec<u8> {
+    [&b"HPKE"[..], &KEM_ID.to_be_bytes(), &KDF_ID.to_be_bytes(), &AEAD_ID.to_be_bytes()].concat()
+}
+
+/// A share can not be sealed to, or opened with, the given key, the string says how.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CiphertextError(pub String);
+
+impl fmt::Display for CiphertextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Sealing or opening the share failed: {}", self.0)
+    }
+}
+
+fn ciphertextError(reason: &str) -> CiphertextError {
+    CiphertextError(reason.to_string())
+}
+
+#[allow(dead_code)]
+impl Hpke {
+    /// Returns `(private key, public key)` of a fresh X25519 key pair.
+    pub fn generateKeyPair() -> ([u8; KEY_LENGTH], [u8; KEY_LENGTH]) {
+        let mut private_key = [0u8; KEY_LENGTH];
+        rand::thread_rng().fill_bytes(&mut private_key);
+        let public_key = PublicKey::from(&StaticSecret::from(private_key));
+        (private_key, public_key.to_bytes())
+    }
+
+    /// Parses a hex encoded X25519 public key.
+    pub fn parsePublicKey(public_key: &str) -> Option<[u8; KEY_LENGTH]> {
+        hex::decode(public_key).ok()?.try_into().ok()
+    }
+
+    /// Encrypts `plaintext` to the holder of `recipient`, authenticating `aad` along with it.
+    pub fn seal(recipient: &[u8; KEY_LENGTH], info: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, CiphertextError> {
+        let mut ephemeral = [0u8; KEY_LENGTH];
+        rand::thread_rng().fill_bytes(&mut ephemeral);
+        Self::sealWith(&ephemeral, recipient, info, aad, plaintext)
+    }
+
+    /// Decrypts a message sealed to the public key of `private_key`.
+    pub fn open(private_key: &[u8; KEY_LENGTH], info: &[u8], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, CiphertextError> {
+        if sealed.len() < KEY_LENGTH {
+            return Err(ciphertextError("shorter than the encapsulated key"));
+        }
+        let enc: [u8; KEY_LENGTH] = sealed[..KEY_LENGTH].try_into().unwrap();
+        let secret = StaticSecret::from(*private_key);
+        let recipient = PublicKey::from(&secret).to_bytes();
+        let dh = secret.diffie_hellman(&PublicKey::from(enc));
+        let shared_secret = Self::sharedSecret(dh.as_bytes(), &enc, &recipient)?;
+        let (key, nonce) = Self::keySchedule(&shared_secret, info);
+        ChaCha20Poly1305::new(key.as_slice().into())
+            .decrypt(nonce.as_slice().into(), Payload { msg: &sealed[KEY_LENGTH..], aad })
+            .map_err(|_| ciphertextError("authentication failed"))
+    }
+
+    fn sealWith(ephemeral: &[u8; KEY_LENGTH], recipient: &[u8; KEY_LENGTH], info: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, CiphertextError> {
+        let (enc, shared_secret) = Self::encap(ephemeral, recipient)?;
+        let (key, nonce) = Self::keySchedule(&shared_secret, info);
+        let ciphertext = ChaCha20Poly1305::new(key.as_slice().into())
+            .encrypt(nonce.as_slice().into(), Payload { msg: plaintext, aad })
+            .map_err(|_| ciphertextError("encryption failed"))?;
+        Ok([&enc[..], &ciphertext].concat())
+    }
+
+    /// DHKEM `Encap` with the given ephemeral private key, returns `(enc, shared_secret)`.
+    fn encap(ephemeral: &[u8; KEY_LENGTH], recipient: &[u8; KEY_LENGTH]) -> Result<([u8; KEY_LENGTH], Vec<u8>), CiphertextError> {
+        let secret = StaticSecret::from(*ephemeral);
+        let enc = PublicKey::from(&secret).to_bytes();
+        let dh = secret.diffie_hellman(&PublicKey::from(*recipient));
+        Ok((enc, Self::sharedSecret(dh.as_bytes(), &enc, recipient)?))
+    }
+
+    fn sharedSecret(dh: &[u8; KEY_LENGTH], enc: &[u8; KEY_LENGTH], recipient: &[u8; KEY_LENGTH]) -> Result<Vec<u8>, CiphertextError> {
+        if dh.iter().all(|byte| *byte == 0) {
+            return Err(ciphertextError("low order public key"));
+        }
+        let suite_id = kemSuiteId();
+        let eae_prk = labeledExtract(&suite_id, b"", b"eae_prk", dh);
+        Ok(labeledExpand(&suite_id, &eae_prk, b"shared_secret", &[&enc[..], recipient].concat(), KEY_LENGTH))
+    }
+
+    /// Base mode key schedule, returns `(key, base_nonce)`. A single message is sealed, so its nonce is the base nonce.
+    fn keySchedule(shared_secret: &[u8], info: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let suite_id = hpkeSuiteId();
+        let psk_id_hash = labeledExtract(&suite_id, b"", b"psk_id_hash", b"");
+        let info_hash = labeledExtract(&suite_id, b"", b"info_hash", info);
+        let context = [&[MODE_BASE][..], &psk_id_hash, &info_hash].concat();
+        let secret = labeledExtract(&suite_id, shared_secret, b"secret", b"");
+        (
+            labeledExpand(&suite_id, &secret, b"key", &context, KEY_LENGTH),
+            labeledExpand(&suite_id, &secret, b"base_nonce", &context, NONCE_LENGTH),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bytes32(value: &str) -> [u8; 32] {
+        hex::decode(value).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn test_dhkem_x25519_vector() {
+        // RFC 9180 A.1.1, the KEM is shared by every suite with DHKEM(X25519, HKDF-SHA256).
+        let ephemeral = bytes32("52c4a758a802cd8b936eceea314432798d5baf2d7e9235dc084ab1b9cfa2f736");
+        let recipient = bytes32("3948cfe0ad1ddb695d780e59077195da6c56506b027329794ab02bca80815c4d");
+        let (enc, shared_secret) = Hpke::encap(&ephemeral, &recipient).unwrap();
+        assert_eq!(hex::encode(enc), "37fda3567bdbd628e88668c3c8d7e97d1d1253b6d4ea6d44c150f741f1bf4431");
+        assert_eq!(hex::encode(shared_secret), "fe0e18c9f024ce43799ae393c7e8fe8fce9d218875e8227b0187c04e7d2ea1fc");
+    }
+
+    #[test]
+    fn test_chacha20poly1305_base_vector() {
+        // RFC 9180 A.2.1, the first encryption of the base mode ChaCha20-Poly1305 suite.
+        let ephemeral = bytes32("f4ec9b33b792c372c1d2c2063507b684ef925b8c75a42dbcbf57d63ccd381600");
+        let recipient_key = bytes32("8057991eef8f1f1af18f4a9491d16a1ce333f695d4db8e38da75975c4478e0fb");
+        let recipient = bytes32("4310ee97d88cc1f088a5576c77ab0cf5c3ac797f3d95139c6c84b5429c59662a");
+        let info = hex::decode("4f6465206f6e2061204772656369616e2055726e").unwrap();
+        let sealed = Hpke::sealWith(&ephemeral, &recipient, &info, b"Count-0", b"Beauty is truth, truth beauty").unwrap();
+        assert_eq!(hex::encode(&sealed[..KEY_LENGTH]), "1afa08d3dec047a643885163f1180476fa7ddb54c6a8029ea33f95796bf2ac4a");
+        assert_eq!(hex::encode(&sealed[KEY_LENGTH..]), "1c5250d8034ec2b784ba2cfd69dbdb8af406cfe3ff938e131f0def8c8b60b4db21993c62ce81883d2dd1b51a28");
+        assert_eq!(Hpke::open(&recipient_key, &info, b"Count-0", &sealed).unwrap(), b"Beauty is truth, truth beauty");
+    }
+
+    #[test]
+    fn test_seal_open() {
+        let (private_key, public_key) = Hpke::generateKeyPair();
+        let sealed = Hpke::seal(&public_key, SHARE_INFO, b"02abcd", b"share1qq...").unwrap();
+        assert_eq!(Hpke::open(&private_key, SHARE_INFO, b"02abcd", &sealed).unwrap(), b"share1qq...");
+        assert!(Hpke::open(&private_key, SHARE_INFO, b"02abce", &sealed).is_err());
+        let (other, _) = Hpke::generateKeyPair();
+        assert!(Hpke::open(&other, SHARE_INFO, b"02abcd", &sealed).is_err());
+        let mut tampered = sealed.clone();
+        tampered[40] ^= 1;
+        assert!(Hpke::open(&private_key, SHARE_INFO, b"02abcd", &tampered).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::VecDeque;
+
+/// A protocol message together with the parties it travels between.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Envelope<M> {
+    pub from: usize,
+    pub to: usize,
+    pub payload: M,
+}
+
+/// How the parties of a multi-party protocol talk to each other. Parties are numbered `0..parties()` and only exchange
+/// messages through the bus, so the same round functions run in one process or with every party on its own node.
+pub trait MessageBus<M: Clone> {
+    fn parties(&self) -> usize;
+
+    fn send(&mut self, from: usize, to: usize, payload: M);
+
+    /// Takes every message waiting for `party`, oldest first.
+    fn receive(&mut self, party: usize) -> Vec<Envelope<M>>;
+
+    /// Sends `payload` to every party, the sender included.
+    fn broadcast(&mut self, from: usize, payload: M) {
+        for to in 0..self.parties() {
+            self.send(from, to, payload.clone());
+        }
+    }
+}
+
+/// Delivers messages between parties running in the same process.
+pub struct InMemoryBus<M> {
+    inboxes: Vec<VecDeque<Envelope<M>>>,
+}
+
+impl<M> InMemoryBus<M> {
+    pub fn new(parties: usize) -> Self {
+        Self { inboxes: (0..parties).map(|_| VecDeque::new()).collect() }
+    }
+}
+
+impl<M: Clone> MessageBus<M> for InMemoryBus<M> {
+    fn parties(&self) -> usize {
+        self.inboxes.len()
+    }
+
+    fn send(&mut self, from: usize, to: usize, payload: M) {
+        self.inboxes[to].push_back(Envelope { from, to, payload });
+    }
+
+    fn receive(&mut self, party: usize) -> Vec<Envelope<M>> {
+        self.inboxes[party].drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_bus_delivers_in_order() {
+        let mut bus: InMemoryBus<u32> = InMemoryBus::new(3);
+        bus.send(0, 2, 7);
+        bus.broadcast(1, 9);
+        assert_eq!(bus.receive(2), vec![Envelope { from: 0, to: 2, payload: 7 }, Envelope { from: 1, to: 2, payload: 9 }]);
+        assert!(bus.receive(2).is_empty());
+        assert_eq!(bus.receive(1).len(), 1);
+    }
+}
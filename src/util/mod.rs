@@ -1,2 +1,31 @@
 pub mod shamir;
-pub mod polynomials;
\ No newline at end of file
+pub mod polynomials;
+pub mod field;
+pub mod gf256;
+pub mod modular;
+pub mod secp256k1;
+pub mod feldman;
+pub mod pedersen;
+pub mod refresh;
+pub mod reshare;
+pub mod repair;
+pub mod dkg;
+pub mod messaging;
+pub mod ecdsa;
+pub mod frost;
+pub mod ed25519;
+pub mod frost_ed25519;
+pub mod bls;
+pub mod byte_shamir;
+pub mod slip39;
+pub mod slip39_wordlist;
+pub mod bip39;
+pub mod bip39_wordlist;
+pub mod bip32;
+pub mod envelope;
+pub mod hpke;
+pub mod cluster;
+pub mod noise;
+pub mod transport;
+pub mod session;
+pub mod broadcast;
\ No newline at end of file
@@ -0,0 +1,188 @@
+use std::{fmt, marker::PhantomData, ops::{Add, Mul, Neg, Sub}, str::FromStr};
+
+use bigdecimal::num_bigint::{BigInt, BigUint, Sign};
+use rand::RngCore;
+
+use super::field::Field;
+
+/// Supplies the modulus of a `ModInt`. Each modulus is its own marker type so elements of different rings never mix.
+pub trait Modulus: Clone + PartialEq + fmt::Debug {
+    fn modulus() -> &'static BigUint;
+}
+
+/// An integer modulo `M::modulus()` of arbitrary size. This is a field when the modulus is prime and a ring otherwise,
+/// in which case `inverse` returns `None` for the zero divisors.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ModInt<M: Modulus> {
+    pub value: BigUint,
+    modulus: PhantomData<M>,
+}
+
+impl<M: Modulus> fmt::Debug for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ModInt({} mod {})", self.value, M::modulus())
+    }
+}
+
+impl<M: Modulus> fmt::Display for ModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl<M: Modulus> FromStr for ModInt<M> {
+    type Err = String;
+
+    /// Parses the decimal representation produced by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let value: BigUint = s.parse().map_err(|_| format!("{} is not a decimal integer", s))?;
+        if value >= *M::modulus() {
+            return Err(format!("{} is not lower than the modulus", s));
+        }
+        Ok(Self::new(value))
+    }
+}
+
+#[allow(dead_code)]
+impl<M: Modulus> ModInt<M> {
+    /// Returns the element congruent to `value` modulo `M`.
+    pub fn new(value: BigUint) -> Self {
+        Self { value: value % M::modulus(), modulus: PhantomData }
+    }
+
+    pub fn from_bytes_be(bytes: &[u8]) -> Self {
+        Self::new(BigUint::from_bytes_be(bytes))
+    }
+
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        self.value.to_bytes_be()
+    }
+}
+
+impl<M: Modulus> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.value + other.value)
+    }
+}
+
+impl<M: Modulus> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + (-other)
+    }
+}
+
+impl<M: Modulus> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        if self.is_zero() {
+            self
+        } else {
+            Self::new(M::modulus() - self.value)
+        }
+    }
+}
+
+impl<M: Modulus> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(self.value * other.value)
+    }
+}
+
+impl<M: Modulus> Field for ModInt<M> {
+    fn zero() -> Self {
+        Self::new(BigUint::from(0u8))
+    }
+
+    fn one() -> Self {
+        Self::new(BigUint::from(1u8))
+    }
+
+    /// Extended Euclid, so composite moduli are supported as long as the element is a unit.
+    fn inverse(&self) -> Option<Self> {
+        let modulus = BigInt::from_biguint(Sign::Plus, M::modulus().clone());
+        let (mut old_r, mut r) = (BigInt::from_biguint(Sign::Plus, self.value.clone()), modulus.clone());
+        let (mut old_s, mut s) = (BigInt::from(1), BigInt::from(0));
+        while r != BigInt::from(0) {
+            let quotient = &old_r / &r;
+            let next_r = &old_r - &quotient * &r;
+            old_r = std::mem::replace(&mut r, next_r);
+            let next_s = &old_s - &quotient * &s;
+            old_s = std::mem::replace(&mut s, next_s);
+        }
+        if old_r != BigInt::from(1) {
+            return None;
+        }
+        let inverse = ((old_s % &modulus) + &modulus) % &modulus;
+        Some(Self::new(inverse.to_biguint().unwrap()))
+    }
+
+    /// Reduces 16 bytes more than the modulus length, which keeps the bias below 2^-128.
+    fn random() -> Self {
+        let mut bytes = vec![0u8; (M::modulus().bits() as usize).div_ceil(8) + 16];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        Self::from_bytes_be(&bytes)
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self::new(BigUint::from(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lazy_static::lazy_static;
+
+    use super::*;
+    use crate::util::polynomials::Polynomial;
+
+    lazy_static! {
+        static ref P7919: BigUint = BigUint::from(7919u32);
+        static ref N15: BigUint = BigUint::from(15u32);
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct Prime7919;
+
+    impl Modulus for Prime7919 {
+        fn modulus() -> &'static BigUint {
+            &P7919
+        }
+    }
+
+    #[derive(Clone, PartialEq, Eq, Debug)]
+    struct Composite15;
+
+    impl Modulus for Composite15 {
+        fn modulus() -> &'static BigUint {
+            &N15
+        }
+    }
+
+    #[test]
+    fn test_inverse_in_prime_field() {
+        for a in 1..7919u64 {
+            let a = ModInt::<Prime7919>::from_u64(a);
+            assert_eq!(a.clone() * a.inverse().unwrap(), ModInt::one());
+        }
+    }
+
+    #[test]
+    fn test_zero_divisors_have_no_inverse() {
+        assert_eq!(ModInt::<Composite15>::from_u64(3).inverse(), None);
+        assert_eq!(ModInt::<Composite15>::from_u64(7).inverse(), Some(ModInt::from_u64(13)));
+    }
+
+    #[test]
+    fn test_polynomial_over_ring() {
+        let polynomial = Polynomial::new(vec![ModInt::<Composite15>::from_u64(1), ModInt::from_u64(2), ModInt::from_u64(3)], 'x');
+        assert_eq!(polynomial.evaluate_at(ModInt::from_u64(4)), ModInt::from_u64(57 % 15));
+        assert_eq!(polynomial.evaluate_at(-ModInt::from_u64(1)), ModInt::from_u64(2));
+    }
+}
@@ -0,0 +1,83 @@
+use k256::{elliptic_curve::sec1::FromEncodedPoint, AffinePoint, EncodedPoint, ProjectivePoint};
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+
+use super::{feldman::FeldmanVSS, field::{Field, FieldElement}, polynomials::Polynomial, secp256k1};
+
+lazy_static! {
+    /// Second generator `H` with an unknown discrete log relative to `G`. It is the first valid x-coordinate (with even y)
+    /// found by hashing a fixed domain separation tag with an increasing counter, so nobody can know `log_G(H)`.
+    pub static ref PEDERSEN_H: ProjectivePoint = PedersenVSS::hashToPoint(b"rust_mpc/pedersen-vss/H");
+}
+
+/// Pedersen verifiable secret sharing over secp256k1. Besides the secret polynomial `f` the dealer samples a blinding
+/// polynomial `g` and publishes `C_i = a_i * G + b_i * H`. Holders receive `(x, f(x), g(x))` and can check their share,
+/// while the commitments are perfectly hiding, `C_0` reveals nothing about the secret.
+pub struct PedersenVSS;
+
+#[allow(dead_code)]
+impl PedersenVSS {
+    pub const SCHEME: &'static str = "pedersen";
+
+    /// Try-and-increment hashing onto the curve.
+    pub fn hashToPoint(tag: &[u8]) -> ProjectivePoint {
+        let mut counter: u32 = 0;
+        loop {
+            let digest = Sha256::new().chain_update(tag).chain_update(counter.to_be_bytes()).finalize();
+            let mut compressed = vec![0x02];
+            compressed.extend_from_slice(&digest);
+            if let Ok(encoded) = EncodedPoint::from_bytes(&compressed) {
+                let point: Option<AffinePoint> = AffinePoint::from_encoded_point(&encoded).into();
+                if let Some(point) = point {
+                    return ProjectivePoint::from(point);
+                }
+            }
+            counter += 1;
+        }
+    }
+
+    /// Commits to the coefficients of `polynomial` blinded by those of `blinding`, lowest degree first.
+    pub fn commit(polynomial: &Polynomial<FieldElement>, blinding: &Polynomial<FieldElement>) -> Vec<ProjectivePoint> {
+        let degree = polynomial.coefficients.len().max(blinding.coefficients.len());
+        (0..degree)
+            .map(|i| {
+                let a = polynomial.coefficients.get(i).copied().unwrap_or_else(FieldElement::zero);
+                let b = blinding.coefficients.get(i).copied().unwrap_or_else(FieldElement::zero);
+                secp256k1::mul_base(&a) + *PEDERSEN_H * secp256k1::to_scalar(&b)
+            })
+            .collect()
+    }
+
+    /// Checks that `(x, y, r)` lies on the committed pair of polynomials, i.e. `y * G + r * H == sum(x^i * C_i)`.
+    pub fn verify(x: &FieldElement, y: &FieldElement, r: &FieldElement, commitments: &[ProjectivePoint]) -> bool {
+        if commitments.is_empty() || x.is_zero() {
+            return false;
+        }
+        FeldmanVSS::evaluateCommitments(commitments, x) == secp256k1::mul_base(y) + *PEDERSEN_H * secp256k1::to_scalar(r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::shamir::ShamirAlgorithm;
+
+    #[test]
+    fn test_shares_verify_against_commitments() {
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(FieldElement::random());
+        let blinding = ShamirAlgorithm::new(Some(2)).polynomialGenerator(FieldElement::random());
+        let commitments = PedersenVSS::commit(&polynomial, &blinding);
+        for x in 1..=5 {
+            let x = FieldElement::from(x);
+            let (y, r) = (polynomial.evaluate_at(x), blinding.evaluate_at(x));
+            assert!(PedersenVSS::verify(&x, &y, &r, &commitments));
+            assert!(!PedersenVSS::verify(&x, &y, &(r + FieldElement::one()), &commitments));
+        }
+    }
+
+    #[test]
+    fn test_generator_is_independent() {
+        assert_ne!(*PEDERSEN_H, ProjectivePoint::GENERATOR);
+        assert_eq!(*PEDERSEN_H, PedersenVSS::hashToPoint(b"rust_mpc/pedersen-vss/H"));
+    }
+}
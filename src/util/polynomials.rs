@@ -1,17 +1,17 @@
-use std::{fmt, str::FromStr};
+use std::fmt;
 
-use bigdecimal::{BigDecimal};
-use primitive_types::U256;
+use super::field::{Field, FieldElement};
 
-/// A simple polynomial representation with `coefficients` and an `indeterminate`. 
-pub struct Polynomial {
+/// A simple polynomial representation with `coefficients` over a `Field` and an `indeterminate`. 
+#[derive(Clone)]
+pub struct Polynomial<F: Field> {
     /// Coefficients of Polynomial. The index of each coefficient indicates its degree, for example in `vec![1, 2]`, the first value is explicitly `1x^0`, the second is `2x^1`, etc.
-    pub coefficients: Vec<BigDecimal>,
+    pub coefficients: Vec<F>,
     /// The `char` representation of the indeterminate, eg. _f(**x**) = 1 + 2x_
     pub indeterminate: char,
 }
 
-impl fmt::Debug for Polynomial {
+impl<F: Field> fmt::Debug for Polynomial<F> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
@@ -24,8 +24,8 @@ impl fmt::Debug for Polynomial {
 }
 
 #[allow(dead_code)]
-impl Polynomial {
-    fn strip_from_end<T: PartialEq + Clone + Default>(list: Vec<T>, object: T) -> Vec<T> {
+impl<F: Field> Polynomial<F> {
+    fn strip_from_end<T: PartialEq + Clone>(list: Vec<T>, object: T) -> Vec<T> {
         let mut new_list = list.clone();
         let mut strip_amount: usize = 0;
         for item in list.iter().rev() {
@@ -35,8 +35,7 @@ impl Polynomial {
                 break;
             }
         }
-        let default: T = Default::default();
-        new_list.resize(list.len() - strip_amount, default);
+        new_list.truncate(list.len() - strip_amount);
         new_list
     }
 
@@ -45,15 +44,15 @@ impl Polynomial {
     /// ```
     /// use polynom::polynomial::Polynomial;
     ///
-    /// let polynomial = Polynomial::new(vec![1BigDecimal, 2BigDecimal, 3BigDecimal], 'x');
-    /// assert_eq!(polynomial.coefficients, vec![1BigDecimal, 2BigDecimal, 3BigDecimal]);
+    /// let polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
+    /// assert_eq!(polynomial.coefficients, vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)]);
     /// ```
-    pub fn new(coefficients: Vec<BigDecimal>, indeterminate: char) -> Polynomial {
-        let stripped_coefficients = self::Polynomial::strip_from_end(coefficients, BigDecimal::from(0));
+    pub fn new(coefficients: Vec<F>, indeterminate: char) -> Polynomial<F> {
+        let stripped_coefficients = Self::strip_from_end(coefficients, F::zero());
         // Zero degree special case
-        if stripped_coefficients.len() == 0 {
+        if stripped_coefficients.is_empty() {
             return Polynomial {
-                coefficients: vec![BigDecimal::from(0)],
+                coefficients: vec![F::zero()],
                 indeterminate,
             };
         }
@@ -64,32 +63,6 @@ impl Polynomial {
         }
     }
 
-    /// Returns a Polynomial from a vector of integers and an indeterminate
-    /// # Example
-    /// ```
-    /// use polynom::polynomial::Polynomial;
-    ///
-    /// let polynomial = Polynomial::from_ints(vec![1, 2, 3], 'x');
-    /// assert_eq!(polynomial.coefficients, vec![1BigDecimal, 2BigDecimal, 3BigDecimal]);
-    /// ```
-    pub fn from_ints(coefficients: Vec<BigDecimal>, indeterminate: char) -> Polynomial {
-        let stripped_coefficients = self::Polynomial::strip_from_end(coefficients, BigDecimal::from(0));
-        // Zero degree special case
-        if stripped_coefficients.len() == 0 {
-            return Polynomial {
-                coefficients: vec![BigDecimal::from(0)],
-                indeterminate,
-            };
-        }
-
-        let float_coefficients = stripped_coefficients.iter().map(|x| x.to_owned()).collect();
-
-        Polynomial {
-            coefficients: float_coefficients,
-            indeterminate,
-        }
-    }
-
     /// Adds the same-degree coefficients of `other: Polynomial` to the coefficients of `self`, and returns a new Polynomial with the summed coefficients.
     /// # Example
     /// ```
@@ -98,21 +71,21 @@ impl Polynomial {
     /// let a_polynomial = Polynomial::from_ints(vec![1, 2, 3], 'x');
     /// let b_polynomial = Polynomial::from_ints(vec![1, 2, 3], 'x');
     /// 
-    /// assert_eq!(a_polynomial.add(b_polynomial).coefficients, vec![2BigDecimal, 4BigDecimal, 6BigDecimal]);
+    /// assert_eq!(a_polynomial.add(b_polynomial).coefficients, vec![FieldElement::from(2), FieldElement::from(4), FieldElement::from(6)]);
     /// ``` 
-    pub fn add(&self, other: Polynomial) -> Polynomial {
+    pub fn add(&self, other: Polynomial<F>) -> Polynomial<F> {
         let mut a_coefficients = self.coefficients.clone();
         let mut b_coefficients = other.coefficients.clone();
 
         // Resize coeff vectors to the longer size
         if a_coefficients.len() < b_coefficients.len() {
-            a_coefficients.resize(b_coefficients.len(), BigDecimal::from(0))
+            a_coefficients.resize(b_coefficients.len(), F::zero())
         } else {
-            b_coefficients.resize(a_coefficients.len(), BigDecimal::from(0))
+            b_coefficients.resize(a_coefficients.len(), F::zero())
         }
 
-        let new_coefficients: Vec<BigDecimal> = a_coefficients
-            .iter()
+        let new_coefficients: Vec<F> = a_coefficients
+            .into_iter()
             .zip(b_coefficients)
             .map(|pair| pair.0 + pair.1)
             .collect();
@@ -128,14 +101,13 @@ impl Polynomial {
     /// let a_polynomial = Polynomial::from_ints(vec![1, 2], 'x');
     /// let b_polynomial = Polynomial::from_ints(vec![2, 4], 'x');
     /// 
-    /// assert_eq!(a_polynomial.sub(b_polynomial).coefficients, vec![BigDecimal::from(-1), BigDecimal::from(-2)]);
+    /// assert_eq!(a_polynomial.sub(b_polynomial).coefficients, vec![FieldElement::from(-1), FieldElement::from(-2)]);
     /// ```
-
-    pub fn sub(&self, other: Polynomial) -> Polynomial {
-        let negative_coefficients: Vec<BigDecimal> = other
+    pub fn sub(&self, other: Polynomial<F>) -> Polynomial<F> {
+        let negative_coefficients: Vec<F> = other
             .coefficients
-            .iter()
-            .map(|coeff| coeff * BigDecimal::from(-1))
+            .into_iter()
+            .map(|coeff| -coeff)
             .collect();
         let negative = Polynomial::new(negative_coefficients, 'x');
 
@@ -150,38 +122,160 @@ impl Polynomial {
     /// let a_polynomial = Polynomial::from_ints(vec![1, 2], 'x');
     /// let b_polynomial = Polynomial::from_ints(vec![2, 4], 'x');
     /// 
-    /// assert_eq!(a_polynomial.multiply(b_polynomial).coefficients, vec![BigDecimal::from(2), BigDecimal::from(8), BigDecimal::from(8)]);
+    /// assert_eq!(a_polynomial.multiply(b_polynomial).coefficients, vec![FieldElement::from(2), FieldElement::from(8), FieldElement::from(8)]);
     /// ```
-    pub fn multiply(&self, other: Polynomial) -> Polynomial {
-        let mut new_coefficients: Vec<BigDecimal> =
-            vec![BigDecimal::from(0); self.coefficients.len() * other.coefficients.len()];
+    pub fn multiply(&self, other: Polynomial<F>) -> Polynomial<F> {
+        let mut new_coefficients: Vec<F> =
+            vec![F::zero(); self.coefficients.len() * other.coefficients.len()];
 
         for (i, self_coeff) in self.coefficients.iter().enumerate() {
             for (j, other_coeff) in other.coefficients.iter().enumerate() {
-                new_coefficients[i + j] += self_coeff * other_coeff;
+                new_coefficients[i + j] = new_coefficients[i + j].clone() + self_coeff.clone() * other_coeff.clone();
             }
         }
 
         Polynomial::new(new_coefficients, 'x')
     }
 
+    /// Long division of `self` by `other`, returns the quotient and the remainder. Panics on division by the zero polynomial.
+    /// # Example
+    /// ```
+    /// use polynom::polynomial::Polynomial;
+    ///
+    /// let a_polynomial = Polynomial::from_ints(vec![-1, 0, 1], 'x');
+    /// let b_polynomial = Polynomial::from_ints(vec![1, 1], 'x');
+    ///
+    /// let (quotient, remainder) = a_polynomial.divide(b_polynomial);
+    /// assert_eq!(quotient.coefficients, vec![FieldElement::from(-1), FieldElement::from(1)]);
+    /// assert_eq!(remainder.coefficients, vec![FieldElement::from(0)]);
+    /// ```
+    pub fn divide(&self, other: Polynomial<F>) -> (Polynomial<F>, Polynomial<F>) {
+        assert!(other.degree() >= 0, "Division by the zero polynomial");
+        let divisor_degree = other.coefficients.len() - 1;
+        let leading_inverse = other.coefficients[divisor_degree].inverse().expect("Leading coefficient must be invertible");
+        let mut remainder = self.coefficients.clone();
+        if remainder.len() <= divisor_degree {
+            return (Polynomial::new(vec![F::zero()], 'x'), self.clone());
+        }
+        let mut quotient = vec![F::zero(); remainder.len() - divisor_degree];
+        for i in (0..quotient.len()).rev() {
+            let factor = remainder[i + divisor_degree].clone() * leading_inverse.clone();
+            for (j, coeff) in other.coefficients.iter().enumerate() {
+                remainder[i + j] = remainder[i + j].clone() - factor.clone() * coeff.clone();
+            }
+            quotient[i] = factor;
+        }
+        (Polynomial::new(quotient, 'x'), Polynomial::new(remainder, 'x'))
+    }
+
+    /// Berlekamp-Welch decoding of the Reed-Solomon code the `[x, y]` `points` form. Finds the polynomial of at most `degree`
+    /// that agrees with all but at most `(n - degree - 1) / 2` of them, together with the indices of the points it disagrees with.
+    /// Up to that bound the answer is the dealt polynomial. Past it, decoding either returns `None` or silently settles on
+    /// another polynomial that happens to lie close enough to the wrong points, and the two cases can not be told apart here.
+    /// # Example
+    /// ```
+    /// use polynom::polynomial::Polynomial;
+    ///
+    /// let points = vec![vec![FieldElement::from(1), FieldElement::from(3)], vec![FieldElement::from(2), FieldElement::from(5)], vec![FieldElement::from(3), FieldElement::from(9)], vec![FieldElement::from(4), FieldElement::from(9)]];
+    /// let (polynomial, errors) = Polynomial::berlekamp_welch(&points, 1).unwrap();
+    /// assert_eq!(polynomial.coefficients, vec![FieldElement::from(1), FieldElement::from(2)]);
+    /// assert_eq!(errors, vec![2]);
+    /// ```
+    pub fn berlekamp_welch(points: &[Vec<F>], degree: usize) -> Option<(Polynomial<F>, Vec<usize>)> {
+        if points.len() <= degree {
+            return None;
+        }
+        let max_errors = (points.len() - degree - 1) / 2;
+        // Unknowns are the coefficients of Q (degree + max_errors) followed by those of the monic error locator E but its leading one,
+        // and every point gives Q(x_i) - y_i * E(x_i) = 0 with the leading term of E moved to the right hand side.
+        let q_size = degree + max_errors + 1;
+        let mut matrix: Vec<Vec<F>> = vec![];
+        for point in points.iter() {
+            let (x, y) = (point[0].clone(), point[1].clone());
+            let mut row = vec![F::zero(); q_size + max_errors + 1];
+            let mut power = F::one();
+            for j in 0..q_size.max(max_errors + 1) {
+                if j < q_size {
+                    row[j] = power.clone();
+                }
+                if j < max_errors {
+                    row[q_size + j] = -(y.clone() * power.clone());
+                }
+                if j == max_errors {
+                    row[q_size + max_errors] = y.clone() * power.clone();
+                }
+                power = power * x.clone();
+            }
+            matrix.push(row);
+        }
+        let solution = Self::solve_linear_system(matrix)?;
+
+        let q = Polynomial::new(solution[..q_size].to_vec(), 'x');
+        let mut e_coefficients = solution[q_size..].to_vec();
+        e_coefficients.push(F::one());
+        let (polynomial, remainder) = q.divide(Polynomial::new(e_coefficients, 'x'));
+        if remainder.degree() >= 0 || polynomial.degree() > degree as isize {
+            return None;
+        }
+        let errors: Vec<usize> = points.iter()
+            .enumerate()
+            .filter(|(_, point)| polynomial.evaluate_at(point[0].clone()) != point[1])
+            .map(|(i, _)| i)
+            .collect();
+        if errors.len() > max_errors {
+            return None;
+        }
+        Some((polynomial, errors))
+    }
+
+    /// Gauss-Jordan elimination of an augmented matrix, the last column being the right hand side. Free variables are set
+    /// to zero; `None` if the system is inconsistent.
+    fn solve_linear_system(mut matrix: Vec<Vec<F>>) -> Option<Vec<F>> {
+        let unknowns = matrix.first().map(|row| row.len() - 1).unwrap_or(0);
+        let mut pivots: Vec<usize> = vec![];
+        let mut row = 0;
+        for column in 0..unknowns {
+            let Some(pivot) = (row..matrix.len()).find(|&r| !matrix[r][column].is_zero()) else {
+                continue;
+            };
+            matrix.swap(row, pivot);
+            let inverse = matrix[row][column].inverse()?;
+            matrix[row] = matrix[row].iter().map(|value| value.clone() * inverse.clone()).collect();
+            for other in 0..matrix.len() {
+                if other != row && !matrix[other][column].is_zero() {
+                    let factor = matrix[other][column].clone();
+                    let pivot_row = matrix[row].clone();
+                    for (value, pivot_value) in matrix[other].iter_mut().zip(pivot_row) {
+                        *value = value.clone() - factor.clone() * pivot_value;
+                    }
+                }
+            }
+            pivots.push(column);
+            row += 1;
+        }
+        if matrix[row..].iter().any(|r| !r[unknowns].is_zero()) {
+            return None;
+        }
+        let mut solution = vec![F::zero(); unknowns];
+        for (r, column) in pivots.into_iter().enumerate() {
+            solution[column] = matrix[r][unknowns].clone();
+        }
+        Some(solution)
+    }
+
     /// Return the result of evaluating a Polynomial at value `determinate`
     /// # Example
     /// ```
     /// use polynom::polynomial::Polynomial;
     ///
-    /// let polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)], 'x');
-    /// assert_eq!(polynomial.evaluate_at(BigDecimal::from(1)), BigDecimal::from(6))
+    /// let polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
+    /// assert_eq!(polynomial.evaluate_at(FieldElement::from(1)), FieldElement::from(6))
     /// ```
-    pub fn evaluate_at(&self, determinate: BigDecimal) -> BigDecimal {
-        let mut sum = BigDecimal::from(0);
-        for (degree, coeff) in self.coefficients.iter().enumerate() {
-            let str_determinate = determinate.to_string();
-            let str_coeff =  coeff.to_string();
-            let tmp = U256::from_str_radix(&str_determinate, 10).unwrap();
-            let tmp2 = U256::from_str_radix(&str_coeff, 10).unwrap();
-            let add = tmp.pow(U256::from(degree)) * tmp2;
-            sum += BigDecimal::from_str(add.to_string().as_str()).unwrap();
+    pub fn evaluate_at(&self, determinate: F) -> F {
+        // Horner's scheme, every step stays inside the field so nothing overflows
+        let mut sum = F::zero();
+        for coeff in self.coefficients.iter().rev() {
+            sum = sum * determinate.clone() + coeff.clone();
         }
         sum
     }
@@ -191,7 +285,7 @@ impl Polynomial {
     /// ```
     /// use polynom::polynomial::Polynomial;
     ///
-    /// let polynomial = Polynomial::new(vec![1BigDecimal, 2BigDecimal, 3BigDecimal], 'x');
+    /// let polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
     /// assert_eq!(polynomial.as_string(), String::from("f(x) = 1 + 2x + 3x^2"))
     /// ```
     pub fn as_string(&self) -> String {
@@ -207,7 +301,7 @@ impl Polynomial {
                 continue;
             }
 
-            if *coeff == BigDecimal::from(0) {
+            if coeff.is_zero() {
                 continue;
             }
 
@@ -222,12 +316,12 @@ impl Polynomial {
     /// ```
     /// use polynom::polynomial::Polynomial;
     ///
-    /// let polynomial = Polynomial::new(vec![1BigDecimal, 2BigDecimal, 3BigDecimal], 'x');
+    /// let polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
     /// assert_eq!(polynomial.degree(), 2)
     /// ```
     pub fn degree(&self) -> isize {
         // Special case zero polynomial
-        if self.coefficients == vec![BigDecimal::from(0)] {
+        if self.coefficients == vec![F::zero()] {
             return -1;
         }
 
@@ -235,6 +329,22 @@ impl Polynomial {
     }
 }
 
+#[allow(dead_code)]
+impl Polynomial<FieldElement> {
+    /// Returns a Polynomial from a vector of integers and an indeterminate
+    /// # Example
+    /// ```
+    /// use polynom::polynomial::Polynomial;
+    ///
+    /// let polynomial = Polynomial::from_ints(vec![1, 2, 3], 'x');
+    /// assert_eq!(polynomial.coefficients, vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)]);
+    /// ```
+    pub fn from_ints(coefficients: Vec<i64>, indeterminate: char) -> Polynomial<FieldElement> {
+        let field_coefficients = coefficients.into_iter().map(FieldElement::from).collect();
+        Polynomial::new(field_coefficients, indeterminate)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,147 +352,192 @@ mod tests {
     #[test]
     fn test_strip_from_end() {
         assert_eq!(
-            self::Polynomial::strip_from_end(vec![1, 2, 0, 3, 0, 0, 0], 0),
+            Polynomial::<FieldElement>::strip_from_end(vec![1, 2, 0, 3, 0, 0, 0], 0),
             vec![1, 2, 0, 3]
         );
     }
     #[test]
     fn test_strip_from_end_on_polynomial() {
-        let polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(0), BigDecimal::from(3), BigDecimal::from(0)], 'x');
-        assert_eq!(polynomial.coefficients, vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(0), BigDecimal::from(3)]);
+        let polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(0), FieldElement::from(3), FieldElement::from(0)], 'x');
+        assert_eq!(polynomial.coefficients, vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(0), FieldElement::from(3)]);
     }
 
     #[test]
     fn test_zero_polynomial() {
-        let polynomial = Polynomial::new(vec![BigDecimal::from(0)], 'x');
-        assert_eq!(polynomial.coefficients, vec![BigDecimal::from(0)]);
+        let polynomial = Polynomial::new(vec![FieldElement::from(0)], 'x');
+        assert_eq!(polynomial.coefficients, vec![FieldElement::from(0)]);
     }
 
     #[test]
     fn test_zero_special_case_degree() {
-        let polynomial = Polynomial::new(vec![BigDecimal::from(0)], 'x');
+        let polynomial = Polynomial::new(vec![FieldElement::from(0)], 'x');
         assert_eq!(polynomial.degree(), -1)
     }
 
     #[test]
     fn test_degree() {
-        let polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(0), BigDecimal::from(3)], 'x');
+        let polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(0), FieldElement::from(3)], 'x');
         assert_eq!(polynomial.degree(), 3)
     }
 
     #[test]
     fn test_string_representation() {
-        let polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(0), BigDecimal::from(3)], 'x');
+        let polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(0), FieldElement::from(3)], 'x');
         assert_eq!(polynomial.as_string(), String::from("f(x) = 1 + 2x + 3x^3"))
     }
 
     #[test]
     fn test_add() {
-        let a_polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(0), BigDecimal::from(3)], 'x');
-        let b_polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(0), BigDecimal::from(3), BigDecimal::from(4)], 'x');
+        let a_polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(0), FieldElement::from(3)], 'x');
+        let b_polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(0), FieldElement::from(3), FieldElement::from(4)], 'x');
 
         assert_eq!(
             a_polynomial.add(b_polynomial).coefficients,
-            vec![BigDecimal::from(2), BigDecimal::from(4), BigDecimal::from(0), BigDecimal::from(6), BigDecimal::from(4)]
+            vec![FieldElement::from(2), FieldElement::from(4), FieldElement::from(0), FieldElement::from(6), FieldElement::from(4)]
         )
     }
 
     #[test]
     fn test_add_negative_coefficients() {
-        let a_polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(0), BigDecimal::from(3)], 'x');
-        let b_polynomial = Polynomial::new(vec![BigDecimal::from(-1), BigDecimal::from(-2), BigDecimal::from(0), BigDecimal::from(-3)], 'x');
+        let a_polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(0), FieldElement::from(3)], 'x');
+        let b_polynomial = Polynomial::new(vec![FieldElement::from(-1), FieldElement::from(-2), FieldElement::from(0), FieldElement::from(-3)], 'x');
 
-        assert_eq!(a_polynomial.add(b_polynomial).coefficients, vec![BigDecimal::from(0)])
+        assert_eq!(a_polynomial.add(b_polynomial).coefficients, vec![FieldElement::from(0)])
     }
 
     #[test]
     fn test_multiply_simple() {
-        let a_polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)], 'x');
-        let b_polynomial = Polynomial::new(vec![BigDecimal::from(1)], 'x');
+        let a_polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
+        let b_polynomial = Polynomial::new(vec![FieldElement::from(1)], 'x');
 
         assert_eq!(
             a_polynomial.multiply(b_polynomial).coefficients,
-            vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)]
+            vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)]
         );
     }
 
     #[test]
     fn test_multiply() {
-        let a_polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)], 'x');
-        let b_polynomial = Polynomial::new(vec![BigDecimal::from(3), BigDecimal::from(2), BigDecimal::from(1)], 'x');
+        let a_polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
+        let b_polynomial = Polynomial::new(vec![FieldElement::from(3), FieldElement::from(2), FieldElement::from(1)], 'x');
 
         assert_eq!(
             a_polynomial.multiply(b_polynomial).coefficients,
-            vec![BigDecimal::from(3), BigDecimal::from(8), BigDecimal::from(14), BigDecimal::from(8), BigDecimal::from(3)]
+            vec![FieldElement::from(3), FieldElement::from(8), FieldElement::from(14), FieldElement::from(8), FieldElement::from(3)]
         )
     }
 
     #[test]
     fn test_multiply_negative() {
-        let a_polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)], 'x');
-        let b_polynomial = Polynomial::new(vec![BigDecimal::from(-3), BigDecimal::from(-2), BigDecimal::from(-1)], 'x');
+        let a_polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
+        let b_polynomial = Polynomial::new(vec![FieldElement::from(-3), FieldElement::from(-2), FieldElement::from(-1)], 'x');
 
         assert_eq!(
             a_polynomial.multiply(b_polynomial).coefficients,
-            vec![BigDecimal::from(-3), BigDecimal::from(-8), BigDecimal::from(-14), BigDecimal::from(-8), BigDecimal::from(-3)]
+            vec![FieldElement::from(-3), FieldElement::from(-8), FieldElement::from(-14), FieldElement::from(-8), FieldElement::from(-3)]
         )
     }
 
     #[test]
     fn test_evaluate_at_zero() {
-        let polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)], 'x');
+        let polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
 
-        assert_eq!(polynomial.evaluate_at(BigDecimal::from(0)), BigDecimal::from(1))
+        assert_eq!(polynomial.evaluate_at(FieldElement::from(0)), FieldElement::from(1))
     }
 
     #[test]
     fn test_evaluate_at_five() {
-        let polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3), BigDecimal::from(4)], 'x');
+        let polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3), FieldElement::from(4)], 'x');
 
-        assert_eq!(polynomial.evaluate_at(BigDecimal::from(5)), BigDecimal::from(586))
+        assert_eq!(polynomial.evaluate_at(FieldElement::from(5)), FieldElement::from(586))
     }
 
     #[test]
     fn test_evaluate_at_negative() {
-        let polynomial = Polynomial::new(vec![BigDecimal::from(-1), BigDecimal::from(2), BigDecimal::from(-3), BigDecimal::from(4)], 'x');
+        let polynomial = Polynomial::new(vec![FieldElement::from(-1), FieldElement::from(2), FieldElement::from(-3), FieldElement::from(4)], 'x');
 
-        assert_eq!(polynomial.evaluate_at(BigDecimal::from(-5)), BigDecimal::from(-586))
+        assert_eq!(polynomial.evaluate_at(FieldElement::from(-5)), FieldElement::from(-586))
     }
 
     #[test]
     fn test_subtract() {
-        let a_polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)], 'x');
-        let b_polynomial = Polynomial::new(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)], 'x');
+        let a_polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
+        let b_polynomial = Polynomial::new(vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)], 'x');
 
-        assert_eq!(a_polynomial.sub(b_polynomial).coefficients, vec![BigDecimal::from(0)])
+        assert_eq!(a_polynomial.sub(b_polynomial).coefficients, vec![FieldElement::from(0)])
     }
 
     #[test]
     fn test_double_negative_subtract() {
-        let a_polynomial = Polynomial::new(vec![BigDecimal::from(-1), BigDecimal::from(-2), BigDecimal::from(-3)], 'x');
-        let b_polynomial = Polynomial::new(vec![BigDecimal::from(-3), BigDecimal::from(-2), BigDecimal::from(-1)], 'x');
+        let a_polynomial = Polynomial::new(vec![FieldElement::from(-1), FieldElement::from(-2), FieldElement::from(-3)], 'x');
+        let b_polynomial = Polynomial::new(vec![FieldElement::from(-3), FieldElement::from(-2), FieldElement::from(-1)], 'x');
 
         assert_eq!(
             a_polynomial.sub(b_polynomial).coefficients,
-            vec![BigDecimal::from(2), BigDecimal::from(0), BigDecimal::from(-2)]
+            vec![FieldElement::from(2), FieldElement::from(0), FieldElement::from(-2)]
         )
     }
 
     #[test]
     fn test_negative_subtract() {
-        let a_polynomial = Polynomial::new(vec![BigDecimal::from(-1), BigDecimal::from(-2), BigDecimal::from(-3)], 'x');
-        let b_polynomial = Polynomial::new(vec![BigDecimal::from(3), BigDecimal::from(2), BigDecimal::from(1)], 'x');
+        let a_polynomial = Polynomial::new(vec![FieldElement::from(-1), FieldElement::from(-2), FieldElement::from(-3)], 'x');
+        let b_polynomial = Polynomial::new(vec![FieldElement::from(3), FieldElement::from(2), FieldElement::from(1)], 'x');
 
         assert_eq!(
             a_polynomial.sub(b_polynomial).coefficients,
-            vec![BigDecimal::from(-4), BigDecimal::from(-4), BigDecimal::from(-4)]
+            vec![FieldElement::from(-4), FieldElement::from(-4), FieldElement::from(-4)]
         )
     }
     #[test]
     fn test_new_polynomial_from_ints() {
-        let polynomial = Polynomial::from_ints(vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)], 'x');
+        let polynomial = Polynomial::from_ints(vec![1, 2, 3], 'x');
+
+        assert_eq!(polynomial.coefficients, vec![FieldElement::from(1), FieldElement::from(2), FieldElement::from(3)]);
+    }
+
+    #[test]
+    fn test_divide() {
+        let a_polynomial = Polynomial::from_ints(vec![3, 9, 14, 8, 3], 'x');
+        let b_polynomial = Polynomial::from_ints(vec![1, 2, 3], 'x');
+        let (quotient, remainder) = a_polynomial.divide(b_polynomial);
+
+        assert_eq!(quotient.coefficients, vec![FieldElement::from(3), FieldElement::from(2), FieldElement::from(1)]);
+        assert_eq!(remainder.coefficients, vec![FieldElement::from(0), FieldElement::from(1)]);
+    }
+
+    #[test]
+    fn test_berlekamp_welch_corrects_errors() {
+        let polynomial = Polynomial::from_ints(vec![42, 7, 1], 'x');
+        let mut points: Vec<Vec<FieldElement>> = (1..=7)
+            .map(|x| vec![FieldElement::from(x), polynomial.evaluate_at(FieldElement::from(x))])
+            .collect();
+        points[1][1] = FieldElement::from(5);
+        points[5][1] = points[5][1] + FieldElement::from(1);
+
+        let (decoded, errors) = Polynomial::berlekamp_welch(&points, 2).unwrap();
+        assert_eq!(decoded.coefficients, polynomial.coefficients);
+        assert_eq!(errors, vec![1, 5]);
+
+        points[3][1] = FieldElement::from(0);
+        assert!(Polynomial::berlekamp_welch(&points, 2).is_none());
+    }
+
+    #[test]
+    fn test_berlekamp_welch_miscorrects_past_the_bound() {
+        // Five of the seven points moved onto another polynomial, which then looks like the dealt one with two errors
+        let polynomial = Polynomial::from_ints(vec![42, 7, 1], 'x');
+        let forged = Polynomial::from_ints(vec![5, 3, 2], 'x');
+        let points: Vec<Vec<FieldElement>> = (1..=7)
+            .map(|x| {
+                let x = FieldElement::from(x);
+                let y = if x == FieldElement::from(1) || x == FieldElement::from(4) { polynomial.evaluate_at(x) } else { forged.evaluate_at(x) };
+                vec![x, y]
+            })
+            .collect();
 
-        assert_eq!(polynomial.coefficients, vec![BigDecimal::from(1), BigDecimal::from(2), BigDecimal::from(3)]);
+        let (decoded, errors) = Polynomial::berlekamp_welch(&points, 2).unwrap();
+        assert_eq!(decoded.coefficients, forged.coefficients);
+        assert_eq!(errors, vec![0, 3]);
     }
 
 }
@@ -0,0 +1,121 @@
+use k256::ProjectivePoint;
+
+use super::{feldman::FeldmanVSS, field::{Field, FieldElement}, pedersen::PedersenVSS, shamir::{ShamirAlgorithm, ShamirError}};
+
+/// One holder's contribution to a refresh: a random polynomial with a zero constant term (plus a blinding one for Pedersen),
+/// evaluated at every holder's x-coordinate, together with commitments the receivers verify before applying it.
+pub struct RefreshDealing {
+    /// `[delta(x_j)]` or `[delta(x_j), epsilon(x_j)]` for every holder `j`, in the order of the x-coordinates given to `deal`.
+    pub subshares: Vec<Vec<FieldElement>>,
+    pub commitments: Vec<ProjectivePoint>,
+}
+
+/// Proactive share refresh (Herzberg et al.). Every holder deals shares of zero and every holder adds what it receives to its
+/// own share. The shared secret and public key stay the same, but shares from different epochs no longer combine.
+pub struct ShareRefresh;
+
+#[allow(dead_code)]
+impl ShareRefresh {
+    /// Deals shares of zero to the holders at `xs`. `blinded` selects Pedersen instead of Feldman commitments.
+    pub fn deal(degree: u8, xs: &[FieldElement], blinded: bool) -> RefreshDealing {
+        let delta = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(FieldElement::zero());
+        if !blinded {
+            return RefreshDealing {
+                subshares: xs.iter().map(|x| vec![delta.evaluate_at(*x)]).collect(),
+                commitments: FeldmanVSS::commit(&delta),
+            };
+        }
+        let epsilon = ShamirAlgorithm::new(Some(degree)).polynomialGenerator(FieldElement::zero());
+        RefreshDealing {
+            subshares: xs.iter().map(|x| vec![delta.evaluate_at(*x), epsilon.evaluate_at(*x)]).collect(),
+            commitments: PedersenVSS::commit(&delta, &epsilon),
+        }
+    }
+
+    /// A receiver's check: the dealing shares zero and the sub-share lies on the committed polynomial.
+    pub fn verifySubshare(x: &FieldElement, subshare: &[FieldElement], commitments: &[ProjectivePoint]) -> bool {
+        if commitments.first() != Some(&ProjectivePoint::IDENTITY) {
+            return false;
+        }
+        match subshare {
+            [y] => FeldmanVSS::verify(x, y, commitments),
+            [y, r] => PedersenVSS::verify(x, y, r, commitments),
+            _ => false,
+        }
+    }
+
+    /// Runs a refresh round between the holders of `shares` (`[x, y]` or `[x, y, r]` each) and returns their new shares
+    /// with the updated commitments. Every holder acts as a dealer, and every sub-share is verified before being added.
+    pub fn refresh(degree: u8, shares: &[Vec<FieldElement>], commitments: &[ProjectivePoint]) -> Result<(Vec<Vec<FieldElement>>, Vec<ProjectivePoint>), ShamirError> {
+        ShamirAlgorithm::new(Some(degree)).validateShares(shares)?;
+        let blinded = shares[0].len() == 3;
+        let xs: Vec<FieldElement> = shares.iter().map(|share| share[0]).collect();
+        let dealings: Vec<RefreshDealing> = xs.iter().map(|_| Self::deal(degree, &xs, blinded)).collect();
+
+        let mut refreshed: Vec<Vec<FieldElement>> = shares.to_vec();
+        for (dealer, dealing) in dealings.iter().enumerate() {
+            for (holder, share) in refreshed.iter_mut().enumerate() {
+                let subshare = &dealing.subshares[holder];
+                if !Self::verifySubshare(&share[0], subshare, &dealing.commitments) {
+                    return Err(ShamirError::InvalidContribution { from: xs[dealer].to_string(), to: xs[holder].to_string() });
+                }
+                for (value, delta) in share[1..].iter_mut().zip(subshare) {
+                    *value = *value + *delta;
+                }
+            }
+        }
+        Ok((refreshed, Self::updateCommitments(commitments, &dealings)))
+    }
+
+    /// Adds the dealings' commitments to the current ones. Each dealing commits to zero, so `C_0` is unchanged.
+    pub fn updateCommitments(commitments: &[ProjectivePoint], dealings: &[RefreshDealing]) -> Vec<ProjectivePoint> {
+        commitments.iter()
+            .enumerate()
+            .map(|(i, commitment)| dealings.iter().fold(*commitment, |sum, dealing| sum + dealing.commitments.get(i).copied().unwrap_or(ProjectivePoint::IDENTITY)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_keeps_secret_and_invalidates_old_shares() {
+        let secret = FieldElement::random();
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(secret);
+        let commitments = FeldmanVSS::commit(&polynomial);
+        let shares: Vec<Vec<FieldElement>> = (1..=5)
+            .map(|x| vec![FieldElement::from(x), polynomial.evaluate_at(FieldElement::from(x))])
+            .collect();
+
+        let (refreshed, new_commitments) = ShareRefresh::refresh(2, &shares, &commitments).unwrap();
+        let shamir = ShamirAlgorithm::new(Some(2));
+        assert_eq!(shamir.combine(&refreshed[..3]), Ok(secret));
+        assert_eq!(new_commitments[0], commitments[0]);
+        for share in refreshed.iter() {
+            assert!(FeldmanVSS::verify(&share[0], &share[1], &new_commitments));
+        }
+        let mixed = vec![shares[0].clone(), shares[1].clone(), refreshed[2].clone()];
+        assert_ne!(shamir.combine(&mixed), Ok(secret));
+    }
+
+    #[test]
+    fn test_refresh_pedersen_shares() {
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(FieldElement::random());
+        let blinding = ShamirAlgorithm::new(Some(2)).polynomialGenerator(FieldElement::random());
+        let commitments = PedersenVSS::commit(&polynomial, &blinding);
+        let shares: Vec<Vec<FieldElement>> = (1..=4)
+            .map(|x| {
+                let x = FieldElement::from(x);
+                vec![x, polynomial.evaluate_at(x), blinding.evaluate_at(x)]
+            })
+            .collect();
+
+        let (refreshed, new_commitments) = ShareRefresh::refresh(2, &shares, &commitments).unwrap();
+        for share in refreshed.iter() {
+            assert!(PedersenVSS::verify(&share[0], &share[1], &share[2], &new_commitments));
+        }
+        assert_eq!(new_commitments[0], commitments[0]);
+    }
+}
@@ -0,0 +1,108 @@
+use k256::ProjectivePoint;
+
+use super::{feldman::FeldmanVSS, field::{Field, FieldElement}, pedersen::PedersenVSS, shamir::{ShamirAlgorithm, ShamirError}};
+
+/// Share repair for a single lost holder (Laing-Stinson enrollment). Each of `degree + 1` helpers splits its Lagrange term
+/// `l_i(x_lost) * y_i` into random additive pieces, one per helper. Every helper only forwards the sum of the pieces it
+/// received, and those sums add up to `f(x_lost)`. The masks hide each term, so nobody learns another share or the secret.
+pub struct ShareRepair;
+
+#[allow(dead_code)]
+impl ShareRepair {
+    /// A helper's first round: splits `l_i(lost_x) * share[k]` for every value `k` of its share (`y`, and `r` for Pedersen)
+    /// into one random piece per helper at `helper_xs`. Piece `j` is sent to helper `j`.
+    pub fn contribute(share: &[FieldElement], helper_xs: &[FieldElement], lost_x: &FieldElement) -> Result<Vec<Vec<FieldElement>>, ShamirError> {
+        let index = helper_xs.iter().position(|x| *x == share[0]).ok_or(ShamirError::MalformedShare(0))?;
+        let lagrange = ShamirAlgorithm::lagrangeCoefficients(helper_xs, lost_x)?[index];
+        let mut pieces: Vec<Vec<FieldElement>> = vec![vec![]; helper_xs.len()];
+        for value in share[1..].iter() {
+            let mut rest = lagrange * *value;
+            for piece in pieces.iter_mut().skip(1) {
+                let mask = FieldElement::random();
+                piece.push(mask);
+                rest = rest - mask;
+            }
+            pieces[0].push(rest);
+        }
+        Ok(pieces)
+    }
+
+    /// A helper's second round: the sum of the pieces it received, which is all it sends to the lost holder.
+    pub fn aggregate(pieces: &[Vec<FieldElement>]) -> Vec<FieldElement> {
+        let width = pieces.first().map(|piece| piece.len()).unwrap_or(0);
+        (0..width)
+            .map(|k| pieces.iter().fold(FieldElement::zero(), |sum, piece| sum + piece[k]))
+            .collect()
+    }
+
+    /// The lost holder's step: adds up the helpers' sums into its share and checks it against the commitments.
+    pub fn recover(lost_x: &FieldElement, sums: &[Vec<FieldElement>], commitments: &[ProjectivePoint]) -> Result<Vec<FieldElement>, ShamirError> {
+        let mut share = vec![*lost_x];
+        share.extend(Self::aggregate(sums));
+        let valid = match &share[1..] {
+            [y] => FeldmanVSS::verify(lost_x, y, commitments),
+            [y, r] => PedersenVSS::verify(lost_x, y, r, commitments),
+            _ => false,
+        };
+        if !valid {
+            return Err(ShamirError::InvalidContribution { from: "helpers".to_string(), to: lost_x.to_string() });
+        }
+        Ok(share)
+    }
+
+    /// Runs the repair between the first `degree + 1` of `helpers` (`[x, y]` or `[x, y, r]` shares) for the holder at `lost_x`.
+    pub fn repair(degree: u8, helpers: &[Vec<FieldElement>], commitments: &[ProjectivePoint], lost_x: &FieldElement) -> Result<Vec<FieldElement>, ShamirError> {
+        let shamir = ShamirAlgorithm::new(Some(degree));
+        shamir.validateShares(helpers)?;
+        let quorum = &helpers[..=degree as usize];
+        let helper_xs: Vec<FieldElement> = quorum.iter().map(|share| share[0]).collect();
+        let mut xs = helper_xs.clone();
+        xs.push(*lost_x);
+        shamir.validateCoordinates(&xs)?;
+
+        let contributions = quorum.iter()
+            .map(|share| Self::contribute(share, &helper_xs, lost_x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let sums: Vec<Vec<FieldElement>> = (0..quorum.len())
+            .map(|j| Self::aggregate(&contributions.iter().map(|pieces| pieces[j].clone()).collect::<Vec<_>>()))
+            .collect();
+        Self::recover(lost_x, &sums, commitments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repair_lost_share() {
+        let secret = FieldElement::random();
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(secret);
+        let commitments = FeldmanVSS::commit(&polynomial);
+        let shares: Vec<Vec<FieldElement>> = [5, 11, 42, 97, 200]
+            .iter()
+            .map(|x| vec![FieldElement::from(*x), polynomial.evaluate_at(FieldElement::from(*x))])
+            .collect();
+
+        let repaired = ShareRepair::repair(2, &shares[..2].iter().chain(&shares[3..]).cloned().collect::<Vec<_>>(), &commitments, &shares[2][0]).unwrap();
+        assert_eq!(repaired, shares[2]);
+        assert_eq!(ShareRepair::repair(2, &shares[1..], &commitments, &shares[1][0]), Err(ShamirError::DuplicateCoordinate("11".to_string())));
+    }
+
+    #[test]
+    fn test_repair_pedersen_share_and_reject_bad_helper() {
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(FieldElement::random());
+        let blinding = ShamirAlgorithm::new(Some(2)).polynomialGenerator(FieldElement::random());
+        let commitments = PedersenVSS::commit(&polynomial, &blinding);
+        let mut shares: Vec<Vec<FieldElement>> = (1..=4)
+            .map(|x| {
+                let x = FieldElement::from(x);
+                vec![x, polynomial.evaluate_at(x), blinding.evaluate_at(x)]
+            })
+            .collect();
+
+        assert_eq!(ShareRepair::repair(2, &shares[1..], &commitments, &shares[0][0]), Ok(shares[0].clone()));
+        shares[2][1] = shares[2][1] + FieldElement::one();
+        assert!(ShareRepair::repair(2, &shares[1..], &commitments, &shares[0][0]).is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use k256::ProjectivePoint;
+
+use super::{feldman::FeldmanVSS, field::{Field, FieldElement}, secp256k1, shamir::{ShamirAlgorithm, ShamirError}};
+
+/// One old holder's contribution to a resharing: its own share dealt with a fresh polynomial of the new degree.
+pub struct ResharingDealing {
+    /// Sub-share for every new holder, in the order of the new x-coordinates.
+    pub subshares: Vec<FieldElement>,
+    /// Feldman commitments to the dealing polynomial, the first one is `y_i * G`.
+    pub commitments: Vec<ProjectivePoint>,
+}
+
+/// Resharing to a new committee and threshold (Desmedt-Jajodia, with Feldman checks as in Wong-Wang-Wing).
+/// A quorum of old holders each Shamir-share their own share to the new holders, and every new holder takes the
+/// Lagrange combination of what it receives. The secret is never reconstructed and `C_0` stays the same.
+pub struct ShareResharing;
+
+#[allow(dead_code)]
+impl ShareResharing {
+    /// Deals the share `y` of an old holder with a random polynomial of `new_degree` to the holders at `new_xs`.
+    pub fn deal(y: &FieldElement, new_degree: u8, new_xs: &[FieldElement]) -> ResharingDealing {
+        let polynomial = ShamirAlgorithm::new(Some(new_degree)).polynomialGenerator(*y);
+        ResharingDealing {
+            subshares: new_xs.iter().map(|x| polynomial.evaluate_at(*x)).collect(),
+            commitments: FeldmanVSS::commit(&polynomial),
+        }
+    }
+
+    /// A new holder's check: the dealing really shares the old holder's committed share, and the sub-share lies on it.
+    pub fn verifySubshare(old_x: &FieldElement, old_commitments: &[ProjectivePoint], dealing_commitments: &[ProjectivePoint], new_x: &FieldElement, subshare: &FieldElement) -> bool {
+        dealing_commitments.first() == Some(&FeldmanVSS::evaluateCommitments(old_commitments, old_x))
+            && FeldmanVSS::verify(new_x, subshare, dealing_commitments)
+    }
+
+    /// Moves the secret shared by `old_shares` (degree `old_degree`, committed to by `old_commitments`) to the holders at
+    /// `new_xs` with a polynomial of `new_degree`. Only the first `old_degree + 1` old shares take part.
+    /// Returns the new `[x, y]` shares and their commitments.
+    pub fn reshare(old_degree: u8, old_shares: &[Vec<FieldElement>], old_commitments: &[ProjectivePoint], new_degree: u8, new_xs: &[FieldElement]) -> Result<(Vec<Vec<FieldElement>>, Vec<ProjectivePoint>), ShamirError> {
+        ShamirAlgorithm::new(Some(old_degree)).validateShares(old_shares)?;
+        ShamirAlgorithm::new(Some(new_degree)).validateCoordinates(new_xs)?;
+
+        let quorum = &old_shares[..=old_degree as usize];
+        let old_xs: Vec<FieldElement> = quorum.iter().map(|share| share[0]).collect();
+        let lagrange = ShamirAlgorithm::lagrangeCoefficients(&old_xs, &FieldElement::zero())?;
+        let dealings: Vec<ResharingDealing> = quorum.iter().map(|share| Self::deal(&share[1], new_degree, new_xs)).collect();
+
+        let mut new_shares: Vec<Vec<FieldElement>> = vec![];
+        for (j, new_x) in new_xs.iter().enumerate() {
+            let mut y = FieldElement::zero();
+            for (i, dealing) in dealings.iter().enumerate() {
+                if !Self::verifySubshare(&old_xs[i], old_commitments, &dealing.commitments, new_x, &dealing.subshares[j]) {
+                    return Err(ShamirError::InvalidContribution { from: old_xs[i].to_string(), to: new_x.to_string() });
+                }
+                y = y + lagrange[i] * dealing.subshares[j];
+            }
+            new_shares.push(vec![*new_x, y]);
+        }
+
+        let new_commitments = (0..=new_degree as usize)
+            .map(|k| dealings.iter().zip(lagrange.iter()).fold(ProjectivePoint::IDENTITY, |sum, (dealing, coefficient)| {
+                let commitment = dealing.commitments.get(k).copied().unwrap_or(ProjectivePoint::IDENTITY);
+                sum + commitment * secp256k1::to_scalar(coefficient)
+            }))
+            .collect();
+        Ok((new_shares, new_commitments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reshare_three_of_five_to_four_of_seven() {
+        let secret = FieldElement::random();
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(secret);
+        let commitments = FeldmanVSS::commit(&polynomial);
+        let old_shares: Vec<Vec<FieldElement>> = [3, 9, 27, 81, 243]
+            .iter()
+            .map(|x| vec![FieldElement::from(*x), polynomial.evaluate_at(FieldElement::from(*x))])
+            .collect();
+        let new_xs: Vec<FieldElement> = (1..=7).map(FieldElement::from).collect();
+
+        let (new_shares, new_commitments) = ShareResharing::reshare(2, &old_shares[2..], &commitments, 3, &new_xs).unwrap();
+        assert_eq!(new_commitments.len(), 4);
+        assert_eq!(new_commitments[0], commitments[0]);
+        for share in new_shares.iter() {
+            assert!(FeldmanVSS::verify(&share[0], &share[1], &new_commitments));
+        }
+        assert_eq!(ShamirAlgorithm::new(Some(3)).combine(&new_shares[3..]), Ok(secret));
+        assert_eq!(ShamirAlgorithm::new(Some(3)).combine(&new_shares[..3]), Err(ShamirError::NotEnoughShares { required: 4, given: 3 }));
+    }
+
+    #[test]
+    fn test_reshare_rejects_share_not_on_commitments() {
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(FieldElement::random());
+        let commitments = FeldmanVSS::commit(&polynomial);
+        let mut old_shares: Vec<Vec<FieldElement>> = (1..=3)
+            .map(|x| vec![FieldElement::from(x), polynomial.evaluate_at(FieldElement::from(x))])
+            .collect();
+        old_shares[1][1] = old_shares[1][1] + FieldElement::one();
+        let new_xs: Vec<FieldElement> = (4..=6).map(FieldElement::from).collect();
+
+        assert_eq!(
+            ShareResharing::reshare(2, &old_shares, &commitments, 2, &new_xs).err(),
+            Some(ShamirError::InvalidContribution { from: "2".to_string(), to: "4".to_string() })
+        );
+    }
+}
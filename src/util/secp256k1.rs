@@ -0,0 +1,60 @@
+use k256::{elliptic_curve::{ops::Reduce, sec1::{FromEncodedPoint, ToEncodedPoint}, PrimeField}, AffinePoint, EncodedPoint, ProjectivePoint, Scalar, U256};
+
+use super::field::FieldElement;
+
+pub const CURVE: &str = "secp256k1";
+
+/// Converts a share or coefficient into a k256 scalar. Both live modulo the group order so this never fails.
+pub fn to_scalar(value: &FieldElement) -> Scalar {
+    Scalar::from_repr(value.to_bytes().into()).unwrap()
+}
+
+pub fn from_scalar(scalar: &Scalar) -> FieldElement {
+    FieldElement::from_bytes(&scalar.to_bytes().into()).unwrap()
+}
+
+/// Interprets 32 big-endian bytes, e.g. a message hash or a point's x-coordinate, as an integer modulo the group order.
+pub fn reduce_bytes(bytes: &[u8; 32]) -> FieldElement {
+    from_scalar(&<Scalar as Reduce<U256>>::reduce_bytes(&(*bytes).into()))
+}
+
+/// Returns `value * G`.
+pub fn mul_base(value: &FieldElement) -> ProjectivePoint {
+    ProjectivePoint::GENERATOR * to_scalar(value)
+}
+
+/// Compressed SEC1 hex encoding of a point.
+pub fn point_to_hex(point: &ProjectivePoint) -> String {
+    hex::encode(point.to_affine().to_encoded_point(true).as_bytes())
+}
+
+/// Uncompressed `X || Y` hex without the SEC1 prefix, the form Ethereum wallets keep in `Wallet.pub_key`.
+pub fn point_to_raw_hex(point: &ProjectivePoint) -> String {
+    hex::encode(&point.to_affine().to_encoded_point(false).as_bytes()[1..])
+}
+
+/// Parses a point from hex. Accepts compressed or uncompressed SEC1 as well as the bare 64 byte `X || Y` form
+/// `WalletService::createEthWallet` stores in `Wallet.pub_key`.
+pub fn point_from_hex(encoded: &str) -> Option<ProjectivePoint> {
+    let mut bytes = hex::decode(encoded.trim_start_matches("0x")).ok()?;
+    if bytes.len() == 64 {
+        bytes.insert(0, 0x04);
+    }
+    let encoded = EncodedPoint::from_bytes(bytes).ok()?;
+    let affine: Option<AffinePoint> = AffinePoint::from_encoded_point(&encoded).into();
+    affine.map(ProjectivePoint::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::WalletService::WalletService;
+
+    #[test]
+    fn test_eth_wallet_public_key_parses() {
+        let wallet = WalletService::createEthWallet();
+        let private_key = FieldElement::from_hex(&wallet[1]).unwrap();
+        assert_eq!(point_from_hex(&wallet[0]), Some(mul_base(&private_key)));
+        assert_eq!(point_to_raw_hex(&mul_base(&private_key)), wallet[0].trim_start_matches("0x"));
+    }
+}
@@ -0,0 +1,615 @@
+use std::{collections::HashMap, fmt, sync::{Arc, Mutex}, time::{SystemTime, UNIX_EPOCH}};
+
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use super::transport::{Outbox, Transport, TransportError};
+
+/// Protocols whose messages only depend on stored key shares and the messages of earlier rounds, so a node can go on
+/// with them after a restart. Every other protocol keeps secret state such as a dealer's polynomial or signing nonces in
+/// memory only.
+pub const RESUMABLE_PROTOCOLS: &[&str] = &["bls-sign"];
+/// Sessions frames are kept for before this node opens them, so peers that open a session first lose nothing.
+pub const PENDING_SESSIONS: usize = 256;
+/// Frames kept for one session that is not open yet.
+pub const PENDING_FRAMES: usize = 64;
+/// Payload bytes kept for sessions that are not open yet, over all of them.
+pub const PENDING_BYTES: usize = 4 << 20;
+/// Milliseconds the frames of a session that is not open yet are kept, counted from the first one.
+pub const PENDING_TTL: u64 = 60_000;
+/// Frames waiting for a session run in this process to take them, enough for one from every possible participant.
+pub const LIVE_FRAMES: usize = 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// Waiting for the messages of the current round.
+    Running,
+    /// Every round closed.
+    Completed,
+    /// Stopped before the last round closed, see `abort_reason`.
+    Aborted,
+}
+
+/// What became of a message handed to a session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageOutcome {
+    /// Belongs to the current round and was stored.
+    Accepted,
+    /// Belongs to a later round and was stored until that round opens.
+    Buffered,
+    /// The same message was already stored, e.g. a retransmission.
+    Duplicate,
+}
+
+/// The message one participant sent in one round.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RoundMessage {
+    pub from: u8,
+    pub round: u32,
+    /// Hex encoded payload.
+    pub payload: String,
+}
+
+/// One message of a session as it travels over the transport.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SessionFrame {
+    pub session_id: String,
+    pub round: u32,
+    /// Hex encoded payload.
+    pub payload: String,
+}
+
+/// A run of a multi-round protocol between cluster nodes named by their x-coordinates. Every participant sends one
+/// message per round, a round closes once all of them are in and the next one then opens with a fresh deadline.
+/// Messages may arrive in any order: those for later rounds wait for their round, resent copies are dropped, and two
+/// different messages from one participant for the same round are refused.
+///
+/// The whole session, messages included, is stored after every step so a node can pick it up after a restart. Secret
+/// per-session state such as signing nonces is never stored, so sessions of protocols that hold some are not `resumable`,
+/// see `RESUMABLE_PROTOCOLS`, and are aborted on restart instead of being continued with fresh nonces.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(rename = "_id")]
+    pub session_id: String,
+    /// Name of the protocol run, e.g. `dkg` or `frost`.
+    pub protocol: String,
+    /// x-coordinate of this node.
+    pub local: u8,
+    /// x-coordinates of every participant, this node included, in ascending order.
+    pub participants: Vec<u8>,
+    pub rounds: u32,
+    /// Index of the open round, equal to `rounds` once the session completed.
+    pub round: u32,
+    /// Milliseconds every round may take.
+    pub round_timeout: u64,
+    /// Unix time in milliseconds the open round times out at.
+    pub deadline: u64,
+    /// Whether `protocol` is one of `RESUMABLE_PROTOCOLS`.
+    pub resumable: bool,
+    pub messages: Vec<RoundMessage>,
+    pub status: SessionStatus,
+    #[serde(default)]
+    pub abort_reason: Option<String>,
+    /// Raised on every write, so two writers of one session can not overwrite each other.
+    pub version: u64,
+}
+
+/// Reasons a protocol session can not be opened, take a message or move on.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SessionError {
+    /// The session refuses what was asked of it, the string says why.
+    Failed(String),
+    /// No protocol session with this id was opened.
+    Unknown(String),
+    /// The session could not be read or written, the string says how.
+    Storage(String),
+    /// The transport the session runs over failed.
+    Transport(TransportError),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Failed(reason) => write!(f, "Protocol session failed: {}", reason),
+            SessionError::Unknown(session_id) => write!(f, "No protocol session {}", session_id),
+            SessionError::Storage(reason) => write!(f, "Protocol session could not be stored: {}", reason),
+            SessionError::Transport(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<TransportError> for SessionError {
+    fn from(err: TransportError) -> Self {
+        SessionError::Transport(err)
+    }
+}
+
+fn sessionError(reason: String) -> SessionError {
+    SessionError::Failed(reason)
+}
+
+/// Unix time in milliseconds.
+pub fn nowMillis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_millis() as u64).unwrap_or(0)
+}
+
+#[allow(dead_code)]
+impl Session {
+    /// Opens a session at its first round. Without a `session_id` a random one is picked; every participant must open
+    /// the session under the same id.
+    pub fn new(session_id: Option<String>, protocol: &str, local: u8, participants: &[u8], rounds: u32, round_timeout: u64, now: u64) -> Result<Self, SessionError> {
+        let mut sorted = participants.to_vec();
+        sorted.sort();
+        if sorted.contains(&0) {
+            return Err(sessionError("no participant can have the x-coordinate 0".to_string()));
+        }
+        if let Some(pair) = sorted.windows(2).find(|pair| pair[0] == pair[1]) {
+            return Err(sessionError(format!("more than one participant has the x-coordinate {}", pair[0])));
+        }
+        if !sorted.contains(&local) {
+            return Err(sessionError("this node is not a participant".to_string()));
+        }
+        if rounds == 0 {
+            return Err(sessionError("a session needs at least one round".to_string()));
+        }
+        let session_id = session_id.unwrap_or_else(|| {
+            let mut id = [0u8; 16];
+            rand::thread_rng().fill_bytes(&mut id);
+            hex::encode(id)
+        });
+        Ok(Session {
+            session_id,
+            protocol: protocol.to_owned(),
+            local,
+            participants: sorted,
+            rounds,
+            round: 0,
+            round_timeout,
+            deadline: now.saturating_add(round_timeout),
+            resumable: RESUMABLE_PROTOCOLS.contains(&protocol),
+            messages: Vec::new(),
+            status: SessionStatus::Running,
+            abort_reason: None,
+            version: 0,
+        })
+    }
+
+    pub fn isRunning(&self) -> bool {
+        self.status == SessionStatus::Running
+    }
+
+    /// Stores the message `from` sent for `round`. Messages for closed rounds are only taken if they repeat the stored one.
+    pub fn accept(&mut self, from: u8, round: u32, payload: &[u8]) -> Result<MessageOutcome, SessionError> {
+        if !self.participants.contains(&from) {
+            return Err(sessionError(format!("{} is not a participant of session {}", from, self.session_id)));
+        }
+        if round >= self.rounds {
+            return Err(sessionError(format!("session {} has no round {}", self.session_id, round)));
+        }
+        let payload = hex::encode(payload);
+        if let Some(stored) = self.messages.iter().find(|message| message.from == from && message.round == round) {
+            if stored.payload == payload {
+                return Ok(MessageOutcome::Duplicate);
+            }
+            return Err(sessionError(format!("{} sent two different messages for round {}", from, round)));
+        }
+        if !self.isRunning() {
+            return Err(sessionError(format!("session {} is no longer running", self.session_id)));
+        }
+        if round < self.round {
+            return Err(sessionError(format!("round {} already closed without a message from {}", round, from)));
+        }
+        self.messages.push(RoundMessage { from, round, payload });
+        Ok(if round == self.round { MessageOutcome::Accepted } else { MessageOutcome::Buffered })
+    }
+
+    /// The messages of `round` as `(sender, payload)`, in ascending order of sender.
+    pub fn roundMessages(&self, round: u32) -> Vec<(u8, Vec<u8>)> {
+        let mut messages: Vec<(u8, Vec<u8>)> = self.messages.iter()
+            .filter(|message| message.round == round)
+            .map(|message| (message.from, hex::decode(&message.payload).expect("Stored payloads are hex")))
+            .collect();
+        messages.sort_by_key(|(from, _)| *from);
+        messages
+    }
+
+    /// Participants the open round still waits for.
+    pub fn missing(&self) -> Vec<u8> {
+        self.participants.iter()
+            .filter(|x| !self.messages.iter().any(|message| message.round == self.round && message.from == **x))
+            .cloned()
+            .collect()
+    }
+
+    /// Closes every round whose messages are all in, returns how many closed. The session completes with its last round.
+    pub fn advance(&mut self, now: u64) -> u32 {
+        let mut closed = 0;
+        while self.isRunning() && self.missing().is_empty() {
+            self.round += 1;
+            closed += 1;
+            if self.round == self.rounds {
+                self.status = SessionStatus::Completed;
+            } else {
+                self.deadline = now.saturating_add(self.round_timeout);
+            }
+        }
+        closed
+    }
+
+    /// The frames that carry this node's message for `round` to every other participant.
+    pub fn frames(&self, round: u32, payload: &[u8]) -> Vec<(u8, SessionFrame)> {
+        self.participants.iter()
+            .filter(|x| **x != self.local)
+            .map(|x| (*x, SessionFrame::new(&self.session_id, round, payload)))
+            .collect()
+    }
+
+    pub fn abort(&mut self, reason: &str) {
+        if self.isRunning() {
+            self.status = SessionStatus::Aborted;
+            self.abort_reason = Some(reason.to_owned());
+        }
+    }
+
+    /// Aborts the session if its open round is past the deadline, returns whether it did.
+    pub fn expire(&mut self, now: u64) -> bool {
+        if !self.isRunning() || now <= self.deadline {
+            return false;
+        }
+        self.abort(&format!("round {} timed out waiting for {:?}", self.round, self.missing()));
+        true
+    }
+
+    /// Decides what happens to a session found running when the node starts: sessions that hold secret state in
+    /// memory are aborted, the others go on unless their round timed out meanwhile. Returns whether it still runs.
+    pub fn resume(&mut self, now: u64) -> bool {
+        if !self.resumable {
+            self.abort("the node restarted while the session held secret state");
+        }
+        self.expire(now);
+        self.isRunning()
+    }
+}
+
+#[allow(dead_code)]
+impl SessionFrame {
+    pub fn new(session_id: &str, round: u32, payload: &[u8]) -> Self {
+        SessionFrame { session_id: session_id.to_owned(), round, payload: hex::encode(payload) }
+    }
+
+    pub fn toBytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Frames always serialize")
+    }
+
+    pub fn fromBytes(bytes: &[u8]) -> Result<Self, SessionError> {
+        serde_json::from_slice(bytes).map_err(|err| sessionError(format!("malformed frame: {}", err)))
+    }
+
+    pub fn payloadBytes(&self) -> Result<Vec<u8>, SessionError> {
+        hex::decode(&self.payload).map_err(|_| sessionError("the frame payload is not hex encoded".to_string()))
+    }
+}
+
+/// Frames of one session that is not open yet, with the time the first one came in.
+struct PendingFrames {
+    since: u64,
+    frames: Vec<(u8, SessionFrame)>,
+}
+
+#[derive(Default)]
+struct RouterState {
+    pending: HashMap<String, PendingFrames>,
+    pending_bytes: usize,
+    /// Sessions run in this process, see `SessionRouter::join`.
+    live: HashMap<String, mpsc::Sender<(u8, SessionFrame)>>,
+}
+
+/// Carries session frames between this node and its peers. It sends through the transport's `Outbox` while another task
+/// receives, and keeps the frames of sessions this node did not open yet until it does, within `PENDING_SESSIONS`,
+/// `PENDING_FRAMES` and `PENDING_BYTES` and for at most `PENDING_TTL`.
+///
+/// Protocols that keep their state in memory, e.g. a key generation with its dealer's polynomial, `join` a session
+/// instead of storing it and get a `SessionChannel`, a transport of its own that only carries that session's frames.
+#[derive(Clone)]
+pub struct SessionRouter {
+    outbox: Outbox,
+    state: Arc<Mutex<RouterState>>,
+}
+
+/// One session's share of the node transport, for a protocol run in this process. Every message travels as a frame of
+/// the session, so any number of channels run next to each other and next to stored sessions over one transport.
+/// Messages from nodes that are not participants are dropped. The session is left when the channel is dropped.
+pub struct SessionChannel {
+    session_id: String,
+    participants: Vec<u8>,
+    router: SessionRouter,
+    inbox: mpsc::Receiver<(u8, SessionFrame)>,
+}
+
+fn frameSize(frame: &SessionFrame) -> usize {
+    frame.session_id.len() + frame.payload.len()
+}
+
+#[allow(dead_code)]
+impl SessionRouter {
+    pub fn new(outbox: Outbox) -> Self {
+        SessionRouter { outbox, state: Arc::new(Mutex::new(RouterState::default())) }
+    }
+
+    /// x-coordinate of this node.
+    pub fn local(&self) -> u8 {
+        self.outbox.local()
+    }
+
+    pub async fn send(&self, to: u8, frame: &SessionFrame) -> Result<(), TransportError> {
+        self.outbox.send(to, frame.toBytes()).await
+    }
+
+    /// Keeps a frame of a session this node has not opened, returns whether it was kept or dropped for the bounds.
+    pub fn hold(&self, from: u8, frame: SessionFrame, now: u64) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let expired: Vec<String> = state.pending.iter()
+            .filter(|(_, pending)| now.saturating_sub(pending.since) > PENDING_TTL)
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        for session_id in expired {
+            let pending = state.pending.remove(&session_id).unwrap();
+            state.pending_bytes -= pending.frames.iter().map(|(_, frame)| frameSize(frame)).sum::<usize>();
+        }
+        let size = frameSize(&frame);
+        if state.pending_bytes + size > PENDING_BYTES
+            || (!state.pending.contains_key(&frame.session_id) && state.pending.len() >= PENDING_SESSIONS) {
+            return false;
+        }
+        let pending = state.pending.entry(frame.session_id.clone()).or_insert_with(|| PendingFrames { since: now, frames: Vec::new() });
+        if pending.frames.len() >= PENDING_FRAMES {
+            return false;
+        }
+        pending.frames.push((from, frame));
+        state.pending_bytes += size;
+        true
+    }
+
+    /// Takes the frames kept for `session_id`, in the order they came in.
+    pub fn take(&self, session_id: &str) -> Vec<(u8, SessionFrame)> {
+        Self::takeFrom(&mut self.state.lock().unwrap(), session_id)
+    }
+
+    fn takeFrom(state: &mut RouterState, session_id: &str) -> Vec<(u8, SessionFrame)> {
+        match state.pending.remove(session_id) {
+            Some(pending) => {
+                state.pending_bytes -= pending.frames.iter().map(|(_, frame)| frameSize(frame)).sum::<usize>();
+                pending.frames
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Runs the session `session_id` between `participants`, this node included, in this process. Its frames, those kept
+    /// from before included, go to the returned channel from now on. Fails if the session already runs here.
+    pub fn join(&self, session_id: &str, participants: &[u8]) -> Result<SessionChannel, SessionError> {
+        if !participants.contains(&self.local()) {
+            return Err(sessionError("this node is not a participant".to_string()));
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.live.contains_key(session_id) {
+            return Err(sessionError(format!("session {} already runs on this node", session_id)));
+        }
+        let (sender, inbox) = mpsc::channel(LIVE_FRAMES);
+        for held in Self::takeFrom(&mut state, session_id) {
+            // Fewer frames are held per session than the channel takes, so none is lost.
+            let _ = sender.try_send(held);
+        }
+        state.live.insert(session_id.to_owned(), sender);
+        Ok(SessionChannel { session_id: session_id.to_owned(), participants: participants.to_vec(), router: self.clone(), inbox })
+    }
+
+    /// Hands a frame to the session run in this process it belongs to, waiting while that session is behind. Returns the
+    /// frame if no such session runs, for the caller to record in a stored session or `hold`.
+    pub async fn deliver(&self, from: u8, frame: SessionFrame) -> Option<SessionFrame> {
+        let sender = self.state.lock().unwrap().live.get(&frame.session_id).cloned();
+        match sender {
+            Some(sender) => {
+                // A session that ended meanwhile does not want the frame any more.
+                let _ = sender.send((from, frame)).await;
+                None
+            }
+            None => Some(frame),
+        }
+    }
+
+    /// Hands every frame arriving on `transport` to its session run in this process and holds the others, for nodes that
+    /// store no sessions, until the transport stops.
+    pub async fn forward<T: Transport>(self, mut transport: T) {
+        while let Ok((from, bytes)) = transport.receive().await {
+            let frame = match SessionFrame::fromBytes(&bytes) {
+                Ok(frame) => frame,
+                Err(err) => {
+                    log::warn!("Dropped a frame from node {}: {}", from, err);
+                    continue;
+                }
+            };
+            if let Some(frame) = self.deliver(from, frame).await {
+                if !self.hold(from, frame, nowMillis()) {
+                    log::warn!("Dropped a frame from node {} of a session that does not run here", from);
+                }
+            }
+        }
+    }
+}
+
+impl Transport for SessionChannel {
+    fn local(&self) -> u8 {
+        self.router.local()
+    }
+
+    fn peers(&self) -> Vec<u8> {
+        self.participants.iter().filter(|x| **x != self.router.local()).cloned().collect()
+    }
+
+    async fn send(&self, to: u8, payload: Vec<u8>) -> Result<(), TransportError> {
+        if !self.participants.contains(&to) {
+            return Err(TransportError(format!("{} is not a participant of session {}", to, self.session_id)));
+        }
+        self.router.send(to, &SessionFrame::new(&self.session_id, 0, &payload)).await
+    }
+
+    async fn receive(&mut self) -> Result<(u8, Vec<u8>), TransportError> {
+        loop {
+            let (from, frame) = self.inbox.recv().await.ok_or_else(|| TransportError("the node transport stopped".to_string()))?;
+            if !self.participants.contains(&from) {
+                log::warn!("Dropped a frame of session {} from node {}, which is not a participant", self.session_id, from);
+                continue;
+            }
+            match frame.payloadBytes() {
+                Ok(payload) => return Ok((from, payload)),
+                Err(err) => log::warn!("Dropped a frame from node {}: {}", from, err),
+            }
+        }
+    }
+}
+
+impl Drop for SessionChannel {
+    fn drop(&mut self) {
+        self.router.state.lock().unwrap().live.remove(&self.session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    use ed25519_dalek::SigningKey;
+    use tokio::time::Duration;
+
+    use crate::util::{broadcast::BroadcastChannel, transport::InMemoryTransport};
+
+    #[test]
+    fn test_rounds_close_in_order() {
+        let mut session = Session::new(None, "dkg", 1, &[3, 1, 2], 2, 1000, 0).unwrap();
+        assert_eq!(session.participants, vec![1, 2, 3]);
+        assert_eq!(session.accept(2, 1, b"late").unwrap(), MessageOutcome::Buffered);
+        assert_eq!(session.accept(1, 0, b"a").unwrap(), MessageOutcome::Accepted);
+        assert_eq!(session.accept(1, 0, b"a").unwrap(), MessageOutcome::Duplicate);
+        assert!(session.accept(1, 0, b"b").is_err());
+        assert!(session.accept(4, 0, b"a").is_err());
+        assert_eq!(session.advance(10), 0);
+        assert_eq!(session.missing(), vec![2, 3]);
+
+        session.accept(3, 0, b"c").unwrap();
+        session.accept(2, 0, b"b").unwrap();
+        assert_eq!(session.advance(10), 1);
+        assert_eq!((session.round, session.deadline), (1, 1010));
+        assert_eq!(session.roundMessages(0), vec![(1, b"a".to_vec()), (2, b"b".to_vec()), (3, b"c".to_vec())]);
+        assert!(session.accept(1, 0, b"x").is_err());
+
+        session.accept(1, 1, b"d").unwrap();
+        session.accept(3, 1, b"e").unwrap();
+        assert_eq!(session.advance(20), 1);
+        assert_eq!(session.status, SessionStatus::Completed);
+        assert!(session.accept(1, 1, b"d").is_ok());
+    }
+
+    #[test]
+    fn test_timeouts_and_restarts() {
+        let mut session = Session::new(Some("s".to_string()), "frost", 1, &[1, 2], 2, 1000, 0).unwrap();
+        assert!(!session.expire(1000));
+        assert!(session.expire(1001));
+        assert_eq!(session.status, SessionStatus::Aborted);
+        assert!(session.accept(1, 0, b"a").is_err());
+
+        let mut signing = Session::new(None, "frost", 1, &[1, 2], 2, 1000, 0).unwrap();
+        assert!(!signing.resumable);
+        assert!(!signing.resume(10));
+        let mut dkg = Session::new(None, "dkg", 1, &[1, 2], 2, 1000, 0).unwrap();
+        assert!(!dkg.resume(10));
+        let mut bls = Session::new(None, "bls-sign", 1, &[1, 2], 2, 1000, 0).unwrap();
+        assert!(bls.resume(10));
+        assert!(Session::new(None, "bls-sign", 3, &[1, 2], 2, 1000, 0).is_err());
+    }
+
+    #[test]
+    fn test_frame_round_trip() {
+        let frame = SessionFrame::new("abc", 2, &[1, 2, 3]);
+        let parsed = SessionFrame::fromBytes(&frame.toBytes()).unwrap();
+        assert_eq!(parsed, frame);
+        assert_eq!(parsed.payloadBytes().unwrap(), vec![1, 2, 3]);
+        assert!(SessionFrame::fromBytes(b"{}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_two_round_session_completes_over_a_transport() {
+        let mut transports = InMemoryTransport::network(&[1, 2]);
+        let mut sessions: Vec<Session> = transports.iter()
+            .map(|transport| Session::new(Some("s".to_string()), "bls-sign", transport.local(), &[1, 2], 2, 1000, 0).unwrap())
+            .collect();
+        for round in 0..2 {
+            for (session, transport) in sessions.iter_mut().zip(transports.iter()) {
+                let payload = [session.local, round as u8];
+                session.accept(session.local, round, &payload).unwrap();
+                session.advance(10);
+                for (to, frame) in session.frames(round, &payload) {
+                    transport.send(to, frame.toBytes()).await.unwrap();
+                }
+            }
+            for (session, transport) in sessions.iter_mut().zip(transports.iter_mut()) {
+                let (from, bytes) = transport.receive().await.unwrap();
+                let frame = SessionFrame::fromBytes(&bytes).unwrap();
+                assert_eq!(frame.session_id, session.session_id);
+                assert_eq!(session.accept(from, frame.round, &frame.payloadBytes().unwrap()).unwrap(), MessageOutcome::Accepted);
+                assert_eq!(session.advance(20), 1);
+            }
+        }
+        for session in sessions {
+            assert_eq!(session.status, SessionStatus::Completed);
+            assert_eq!(session.roundMessages(1), vec![(1, vec![1, 1]), (2, vec![2, 1])]);
+        }
+    }
+
+    #[test]
+    fn test_router_bounds_frames_of_unopened_sessions() {
+        let transports = InMemoryTransport::network(&[1, 2]);
+        let router = SessionRouter::new(transports[0].outbox());
+        assert!(router.hold(2, SessionFrame::new("early", 0, b"a"), 0));
+        assert!(router.hold(2, SessionFrame::new("early", 1, b"b"), 10));
+        assert_eq!(router.take("early").iter().map(|(_, frame)| frame.round).collect::<Vec<u32>>(), vec![0, 1]);
+        assert!(router.take("early").is_empty());
+
+        for round in 0..PENDING_FRAMES as u32 {
+            assert!(router.hold(2, SessionFrame::new("chatty", round, b"x"), 0));
+        }
+        assert!(!router.hold(2, SessionFrame::new("chatty", 0, b"x"), 0));
+        assert!(!router.hold(2, SessionFrame::new("huge", 0, &vec![0; PENDING_BYTES]), 0));
+        // Past the time limit the kept frames are dropped, which frees room for others.
+        assert!(router.hold(2, SessionFrame::new("later", 0, b"y"), PENDING_TTL + 1));
+        assert!(router.take("chatty").is_empty());
+        assert_eq!(router.take("later").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_runs_over_joined_sessions() {
+        let signing: Vec<SigningKey> = (1..=4).map(|x| SigningKey::from_bytes(&[x; 32])).collect();
+        let keys: BTreeMap<u8, _> = signing.iter().zip(1..=4).map(|(key, x)| (x, key.verifying_key())).collect();
+        let mut parties = Vec::new();
+        for (transport, signing_key) in InMemoryTransport::network(&[1, 2, 3, 4]).into_iter().zip(signing) {
+            let router = SessionRouter::new(transport.outbox());
+            tokio::spawn(router.clone().forward(transport));
+            let keys = keys.clone();
+            parties.push(tokio::spawn(async move {
+                let mut channel = router.join("keygen", &[1, 2, 3, 4]).unwrap();
+                assert!(router.join("keygen", &[1, 2, 3, 4]).is_err());
+                let x = channel.local();
+                let outcome = BroadcastChannel::new(&mut channel, signing_key, keys).round("commit", &[x], Duration::from_secs(5)).await.unwrap();
+                drop(channel);
+                assert!(router.join("keygen", &[1, 2, 3, 4]).is_ok());
+                outcome
+            }));
+        }
+        for party in parties {
+            assert_eq!(party.await.unwrap().delivered, (1..=4).map(|x| (x, vec![x])).collect());
+        }
+    }
+}
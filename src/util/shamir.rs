@@ -1,8 +1,51 @@
-use actix_web::middleware::Logger;
-use bigdecimal::{BigDecimal, num_bigint::ToBigInt};
-use rand::Rng;
+use std::fmt;
 
-use super::polynomials::Polynomial;
+use super::{field::Field, polynomials::Polynomial};
+
+/// Reasons shares can not be dealt, combined or checked.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ShamirError {
+    /// Fewer than `degree + 1` shares were supplied.
+    NotEnoughShares { required: usize, given: usize },
+    /// Two shares claim the same x-coordinate, the string is that coordinate.
+    DuplicateCoordinate(String),
+    /// A share has the x-coordinate zero, which would be the secret itself.
+    ZeroCoordinate,
+    /// A share is not an `[x, y]` pair.
+    MalformedShare(usize),
+    /// A protocol message from the holder at x-coordinate `from` to the one at `to` failed verification.
+    InvalidContribution { from: String, to: String },
+    /// The holder at x-coordinate `to` has no valid protocol message from the one at `from`.
+    MissingContribution { from: String, to: String },
+    /// Stored commitments could not be decoded into curve points.
+    InvalidCommitments,
+    /// Every dealer of a key generation was disqualified.
+    NoQualifiedDealers,
+    /// A combined signature does not verify against the public key.
+    InvalidSignature,
+    /// More shares are wrong than error correction can handle, at most `correctable` of them can be.
+    TooManyFaultyShares { correctable: usize },
+    /// The parameters of a sharing are out of range, the string says which.
+    InvalidSharing(String),
+}
+
+impl fmt::Display for ShamirError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShamirError::NotEnoughShares { required, given } => write!(f, "At least {} shares are required, {} given", required, given),
+            ShamirError::DuplicateCoordinate(x) => write!(f, "More than one share has the x-coordinate {}", x),
+            ShamirError::ZeroCoordinate => write!(f, "Shares can not have the x-coordinate 0"),
+            ShamirError::MalformedShare(index) => write!(f, "Share #{} is not an [x, y] pair", index),
+            ShamirError::InvalidContribution { from, to } => write!(f, "Contribution from holder {} to holder {} does not match its commitments", from, to),
+            ShamirError::MissingContribution { from, to } => write!(f, "Holder {} has no valid contribution from holder {}", to, from),
+            ShamirError::InvalidCommitments => write!(f, "Commitments are not valid curve points"),
+            ShamirError::NoQualifiedDealers => write!(f, "No dealer of the key generation passed verification"),
+            ShamirError::InvalidSignature => write!(f, "Combined signature does not verify against the public key"),
+            ShamirError::TooManyFaultyShares { correctable } => write!(f, "Too many faulty shares, at most {} can be corrected", correctable),
+            ShamirError::InvalidSharing(reason) => write!(f, "Invalid sharing parameters: {}", reason),
+        }
+    }
+}
 
 pub struct ShamirAlgorithm {
     pub degree: u8
@@ -11,43 +54,167 @@ pub struct ShamirAlgorithm {
 #[allow(dead_code)]
 impl ShamirAlgorithm {
     pub fn new(degree: Option<u8>) -> Self {
-        let x = if degree.is_some() {
-            degree.unwrap()
-        } else {
-            2
-        };
-        assert!(!(x.lt(&u8::from(2))), "Degree must be greater than or equal to 2");
+        let x = degree.unwrap_or(2);
+        assert!(x >= 1, "Degree must be greater than or equal to 1");
         Self { degree: x }
     }
 
-    pub fn polynomialGenerator(self, value: BigDecimal) -> Polynomial {
-        let mut polynom: Vec<BigDecimal> = vec![];
-        polynom.push(value);
+    /// Returns a random polynomial of `degree` over `F` whose constant term is `value`.
+    pub fn polynomialGenerator<F: Field>(self, value: F) -> Polynomial<F> {
+        let mut polynom: Vec<F> = vec![value];
         for _i in 1..=self.degree {
-            let c: u128 = rand::thread_rng().gen();
-            polynom.push(c.to_bigint().unwrap().into());
+            polynom.push(F::random());
         }
         Polynomial::new(polynom, 'x')
     }
 
-    pub fn fromValues(self, values: Vec<Vec<BigDecimal>>) -> Polynomial {
-        assert!(values.len() <= self.degree as usize, "Size must be greater than degree {}", self.degree);
-        let mut polynom: Polynomial = Polynomial::new(vec![BigDecimal::from(0)], 'x');
+    /// Rebuilds the dealt polynomial from the first `degree + 1` `[x, y]` pairs by Lagrange interpolation.
+    pub fn fromValues<F: Field>(self, values: Vec<Vec<F>>) -> Polynomial<F> {
+        assert!(values.len() > self.degree as usize, "Size must be greater than degree {}", self.degree);
+        let mut polynom: Polynomial<F> = Polynomial::new(vec![F::zero()], 'x');
         for i in 0..=self.degree as usize {
-            let mut inner_polynom: Option<Polynomial> = None;
+            let mut inner_polynom = Polynomial::new(vec![values[i][1].clone()], 'x');
             for j in 0..=self.degree as usize {
                 if i != j {
-                    let c1 = (BigDecimal::from(-1) * &values[j][0]) / (&values[i][0] - &values[j][0]);
-                    let c2 = (BigDecimal::from(1)) / (&values[i][0] - &values[j][0]);
-                    let coefs = vec![(&values[i][0] * c1), (&values[i][0] * c2)];
-                    let tmp = Polynomial::new(coefs, 'x');
-                    Logger::default().log_target(tmp.as_string());
-                    if inner_polynom.is_none() { inner_polynom = Some(Polynomial::new(vec![BigDecimal::from(1)], 'x')) }
-                    inner_polynom = Some(inner_polynom.unwrap().multiply(tmp));
+                    let inverse = (values[i][0].clone() - values[j][0].clone()).inverse().expect("Shares must have distinct x coordinates");
+                    let tmp = Polynomial::new(vec![-values[j][0].clone() * inverse.clone(), inverse], 'x');
+                    inner_polynom = inner_polynom.multiply(tmp);
                 }
             }
-            if inner_polynom.is_some() { polynom = polynom.add(inner_polynom.unwrap()); }
+            polynom = polynom.add(inner_polynom);
+        }
+        polynom
+    }
+
+    /// Lagrange basis polynomials of the nodes `xs` evaluated at `at`, i.e. `l_i(at) = prod_{j != i} (at - x_j) / (x_i - x_j)`.
+    /// Fails on repeated nodes, since the basis is not defined then.
+    pub fn lagrangeCoefficients<F: Field>(xs: &[F], at: &F) -> Result<Vec<F>, ShamirError> {
+        let mut coefficients: Vec<F> = vec![];
+        for (i, xi) in xs.iter().enumerate() {
+            let mut numerator = F::one();
+            let mut denominator = F::one();
+            for (j, xj) in xs.iter().enumerate() {
+                if i != j {
+                    numerator = numerator * (at.clone() - xj.clone());
+                    denominator = denominator * (xi.clone() - xj.clone());
+                }
+            }
+            let inverse = denominator.inverse().ok_or_else(|| ShamirError::DuplicateCoordinate(xi.to_string()))?;
+            coefficients.push(numerator * inverse);
+        }
+        Ok(coefficients)
+    }
+
+    /// Checks that `values` are at least `degree + 1` well formed `[x, y]` pairs with distinct, non-zero x-coordinates.
+    pub fn validateShares<F: Field>(&self, values: &[Vec<F>]) -> Result<(), ShamirError> {
+        if let Some(i) = values.iter().position(|share| share.len() < 2) {
+            return Err(ShamirError::MalformedShare(i));
+        }
+        let xs: Vec<F> = values.iter().map(|share| share[0].clone()).collect();
+        self.validateCoordinates(&xs)
+    }
+
+    /// Checks that `xs` are at least `degree + 1` distinct, non-zero x-coordinates.
+    pub fn validateCoordinates<F: Field>(&self, xs: &[F]) -> Result<(), ShamirError> {
+        let required = self.degree as usize + 1;
+        if xs.len() < required {
+            return Err(ShamirError::NotEnoughShares { required, given: xs.len() });
+        }
+        for (i, x) in xs.iter().enumerate() {
+            if x.is_zero() {
+                return Err(ShamirError::ZeroCoordinate);
+            }
+            if xs[..i].contains(x) {
+                return Err(ShamirError::DuplicateCoordinate(x.to_string()));
+            }
         }
-        return polynom;
+        Ok(())
+    }
+
+    /// Computes `f(0)` directly from any subset of at least `degree + 1` shares, whatever their x-coordinates are.
+    pub fn combine<F: Field>(&self, values: &[Vec<F>]) -> Result<F, ShamirError> {
+        self.validateShares(values)?;
+        let xs: Vec<F> = values.iter().map(|share| share[0].clone()).collect();
+        let coefficients = Self::lagrangeCoefficients(&xs, &F::zero())?;
+        Ok(values.iter()
+            .zip(coefficients)
+            .fold(F::zero(), |sum, (share, coefficient)| sum + share[1].clone() * coefficient))
+    }
+
+    /// Like `combine`, but treats the shares as a Reed-Solomon codeword and corrects up to `(n - degree - 1) / 2` wrong ones
+    /// with Berlekamp-Welch. Returns `f(0)` and the indices, into `values`, of the shares that were not on the polynomial.
+    pub fn robustCombine<F: Field>(&self, values: &[Vec<F>]) -> Result<(F, Vec<usize>), ShamirError> {
+        self.validateShares(values)?;
+        let correctable = (values.len() - self.degree as usize - 1) / 2;
+        let (polynomial, faulty) = Polynomial::berlekamp_welch(values, self.degree as usize)
+            .ok_or(ShamirError::TooManyFaultyShares { correctable })?;
+        Ok((polynomial.evaluate_at(F::zero()), faulty))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{field::FieldElement, gf256::GF256};
+
+    #[test]
+    fn test_round_trip() {
+        let secret = FieldElement::random();
+        let polynomial = ShamirAlgorithm::new(Some(3)).polynomialGenerator(secret);
+        let values: Vec<Vec<FieldElement>> = (1..=4)
+            .map(|x| vec![FieldElement::from(x), polynomial.evaluate_at(FieldElement::from(x))])
+            .collect();
+        let recovered = ShamirAlgorithm::new(Some(3)).fromValues(values);
+        assert_eq!(recovered.evaluate_at(FieldElement::zero()), secret);
+        assert_eq!(recovered.coefficients, polynomial.coefficients);
+    }
+
+    #[test]
+    fn test_round_trip_over_gf256() {
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(GF256(0x2a));
+        let values: Vec<Vec<GF256>> = (1..=3)
+            .map(|x| vec![GF256(x), polynomial.evaluate_at(GF256(x))])
+            .collect();
+        let recovered = ShamirAlgorithm::new(Some(2)).fromValues(values);
+        assert_eq!(recovered.evaluate_at(GF256(0)), GF256(0x2a));
+    }
+
+    #[test]
+    fn test_combine_any_subset() {
+        let secret = FieldElement::random();
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(secret);
+        let values: Vec<Vec<FieldElement>> = [17, 3, 250, 42, 99]
+            .iter()
+            .map(|x| vec![FieldElement::from(*x), polynomial.evaluate_at(FieldElement::from(*x))])
+            .collect();
+        let shamir = ShamirAlgorithm::new(Some(2));
+        assert_eq!(shamir.combine(&values[2..]), Ok(secret));
+        assert_eq!(shamir.combine(&[values[4].clone(), values[0].clone(), values[2].clone()]), Ok(secret));
+        assert_eq!(shamir.combine(&values), Ok(secret));
+    }
+
+    #[test]
+    fn test_combine_rejects_bad_input() {
+        let shamir = ShamirAlgorithm::new(Some(2));
+        let share = |x: i64, y: i64| vec![FieldElement::from(x), FieldElement::from(y)];
+        assert_eq!(shamir.combine(&[share(1, 5), share(2, 7)]), Err(ShamirError::NotEnoughShares { required: 3, given: 2 }));
+        assert_eq!(shamir.combine(&[share(1, 5), share(2, 7), share(1, 5)]), Err(ShamirError::DuplicateCoordinate("1".to_string())));
+        assert_eq!(shamir.combine(&[share(0, 5), share(2, 7), share(3, 9)]), Err(ShamirError::ZeroCoordinate));
+    }
+
+    #[test]
+    fn test_robust_combine_reports_faulty_shares() {
+        let secret = FieldElement::random();
+        let polynomial = ShamirAlgorithm::new(Some(2)).polynomialGenerator(secret);
+        let mut values: Vec<Vec<FieldElement>> = (1..=7)
+            .map(|x| vec![FieldElement::from(x), polynomial.evaluate_at(FieldElement::from(x))])
+            .collect();
+        let shamir = ShamirAlgorithm::new(Some(2));
+        assert_eq!(shamir.robustCombine(&values), Ok((secret, vec![])));
+
+        values[0][1] = FieldElement::random();
+        values[4][1] = values[4][1] + FieldElement::one();
+        assert_eq!(shamir.robustCombine(&values), Ok((secret, vec![0, 4])));
+        assert_eq!(shamir.robustCombine(&values[..6]), Err(ShamirError::TooManyFaultyShares { correctable: 1 }));
     }
 }
\ No newline at end of file
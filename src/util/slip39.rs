@@ -0,0 +1,467 @@
+use std::{collections::BTreeMap, fmt};
+
+use hmac::{Hmac, Mac};
+use rand::{Rng, RngCore};
+use sha2::Sha256;
+
+use super::{bip39::MnemonicError, field::Field, gf256::GF256, shamir::{ShamirAlgorithm, ShamirError}, slip39_wordlist::WORDLIST};
+
+const RADIX_BITS: usize = 10;
+/// Identifier, extendable flag and iteration exponent take two words, group and member parameters two more.
+const METADATA_WORDS: usize = 4;
+const CHECKSUM_WORDS: usize = 3;
+const MIN_SECRET_BYTES: usize = 16;
+const MAX_SHARE_COUNT: u8 = 16;
+const DIGEST_LENGTH: usize = 4;
+const DIGEST_INDEX: u8 = 254;
+const SECRET_INDEX: u8 = 255;
+const BASE_ITERATION_COUNT: u32 = 10000;
+const ROUND_COUNT: u8 = 4;
+const CUSTOMIZATION: &[u8] = b"shamir";
+const CUSTOMIZATION_EXTENDABLE: &[u8] = b"shamir_extendable";
+const GENERATOR: [u32; 10] = [0xE0E040, 0x1C1C080, 0x3838100, 0x7070200, 0xE0E0009, 0x1C0C2412, 0x38086C24, 0x3090FC48, 0x21B1F890, 0x3F3F120];
+
+/// Reasons SLIP-0039 shares can not be made or combined: a mnemonic that does not decode or fit with the others, or a
+/// sharing that is out of range or short of shares.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Slip39Error {
+    Mnemonic(MnemonicError),
+    Sharing(ShamirError),
+}
+
+impl fmt::Display for Slip39Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Slip39Error::Mnemonic(err) => err.fmt(f),
+            Slip39Error::Sharing(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<MnemonicError> for Slip39Error {
+    fn from(err: MnemonicError) -> Self {
+        Slip39Error::Mnemonic(err)
+    }
+}
+
+impl From<ShamirError> for Slip39Error {
+    fn from(err: ShamirError) -> Self {
+        Slip39Error::Sharing(err)
+    }
+}
+
+/// One SLIP-0039 share, the fields a mnemonic encodes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Slip39Share {
+    /// Random 15 bit identifier common to every share of a secret.
+    pub identifier: u16,
+    /// Extendable backups leave the identifier out of the encryption salt, so more groups can be added later.
+    pub extendable: bool,
+    pub iteration_exponent: u8,
+    pub group_index: u8,
+    pub group_threshold: u8,
+    pub group_count: u8,
+    pub member_index: u8,
+    pub member_threshold: u8,
+    pub value: Vec<u8>,
+}
+
+#[allow(dead_code)]
+impl Slip39Share {
+    /// Encodes the share as space separated words, with the RS1024 checksum at the end.
+    pub fn toMnemonic(&self) -> String {
+        let header = (self.identifier as u32) << 5 | (self.extendable as u32) << 4 | self.iteration_exponent as u32;
+        let parameters = (self.group_index as u32) << 16
+            | ((self.group_threshold - 1) as u32) << 12
+            | ((self.group_count - 1) as u32) << 8
+            | (self.member_index as u32) << 4
+            | (self.member_threshold - 1) as u32;
+        let mut words: Vec<u16> = vec![(header >> 10) as u16, (header & 0x3ff) as u16, (parameters >> 10) as u16, (parameters & 0x3ff) as u16];
+        words.extend(bytesToWords(&self.value));
+        words.extend(createChecksum(customization(self.extendable), &words));
+        words.iter().map(|word| WORDLIST[*word as usize]).collect::<Vec<&str>>().join(" ")
+    }
+
+    /// Parses and checks a mnemonic: known words, a valid checksum, zero padding and consistent thresholds.
+    pub fn fromMnemonic(mnemonic: &str) -> Result<Self, MnemonicError> {
+        let words = mnemonic.split_whitespace()
+            .map(|word| WORDLIST.binary_search(&word.to_lowercase().as_str()).map(|i| i as u16).map_err(|_| MnemonicError(format!("{} is not in the word list", word))))
+            .collect::<Result<Vec<u16>, MnemonicError>>()?;
+        if words.len() < METADATA_WORDS + CHECKSUM_WORDS + (MIN_SECRET_BYTES * 8).div_ceil(RADIX_BITS) {
+            return Err(MnemonicError(format!("{} words are too few", words.len())));
+        }
+        let extendable = words[1] >> 4 & 1 == 1;
+        if polymod(customization(extendable), &words) != 1 {
+            return Err(MnemonicError("checksum does not match".to_string()));
+        }
+
+        let header = (words[0] as u32) << 10 | words[1] as u32;
+        let parameters = (words[2] as u32) << 10 | words[3] as u32;
+        let value = wordsToBytes(&words[METADATA_WORDS..words.len() - CHECKSUM_WORDS])
+            .ok_or_else(|| MnemonicError("share value is not padded correctly".to_string()))?;
+        let share = Slip39Share {
+            identifier: (header >> 5) as u16,
+            extendable,
+            iteration_exponent: (header & 0xf) as u8,
+            group_index: (parameters >> 16) as u8,
+            group_threshold: (parameters >> 12 & 0xf) as u8 + 1,
+            group_count: (parameters >> 8 & 0xf) as u8 + 1,
+            member_index: (parameters >> 4 & 0xf) as u8,
+            member_threshold: (parameters & 0xf) as u8 + 1,
+            value,
+        };
+        if share.group_threshold > share.group_count {
+            return Err(MnemonicError("group threshold is greater than the group count".to_string()));
+        }
+        Ok(share)
+    }
+}
+
+/// SLIP-0039 backups: a master secret is encrypted with a passphrase, split with a two level Shamir scheme over GF(2^8)
+/// (groups, then members within each group) and every share is written down as words.
+pub struct Slip39;
+
+#[allow(dead_code)]
+impl Slip39 {
+    /// Splits `master_secret` into `groups.len()` groups of `(member_threshold, member_count)`, `group_threshold` of which
+    /// recover it. Returns the mnemonics of every group, in group order.
+    pub fn generate(group_threshold: u8, groups: &[(u8, u8)], master_secret: &[u8], passphrase: &[u8], iteration_exponent: u8) -> Result<Vec<Vec<String>>, Slip39Error> {
+        if master_secret.len() < MIN_SECRET_BYTES || !master_secret.len().is_multiple_of(2) {
+            return Err(ShamirError::InvalidSharing(format!("master secret must be an even number of at least {} bytes", MIN_SECRET_BYTES)).into());
+        }
+        if passphrase.iter().any(|c| !(32..=126).contains(c)) {
+            return Err(ShamirError::InvalidSharing("passphrase must be printable ASCII".to_string()).into());
+        }
+        if iteration_exponent > 15 {
+            return Err(ShamirError::InvalidSharing("iteration exponent must be lower than 16".to_string()).into());
+        }
+        if group_threshold == 0 || group_threshold as usize > groups.len() || groups.len() > MAX_SHARE_COUNT as usize {
+            return Err(ShamirError::InvalidSharing(format!("group threshold must be between 1 and the group count, which is at most {}", MAX_SHARE_COUNT)).into());
+        }
+        for (threshold, count) in groups.iter() {
+            if *threshold == 0 || threshold > count || *count > MAX_SHARE_COUNT {
+                return Err(ShamirError::InvalidSharing(format!("member threshold must be between 1 and the member count, which is at most {}", MAX_SHARE_COUNT)).into());
+            }
+            if *threshold == 1 && *count > 1 {
+                return Err(ShamirError::InvalidSharing("a member threshold of 1 only allows a single member".to_string()).into());
+            }
+        }
+
+        let identifier: u16 = rand::thread_rng().gen_range(0..1 << 15);
+        let extendable = true;
+        let encrypted = Self::encrypt(master_secret, passphrase, iteration_exponent, identifier, extendable);
+        let group_secrets = splitSecret(group_threshold, groups.len() as u8, &encrypted)?;
+
+        let mut mnemonics: Vec<Vec<String>> = vec![];
+        for (group_index, ((member_threshold, member_count), group_secret)) in groups.iter().zip(group_secrets).enumerate() {
+            let member_secrets = splitSecret(*member_threshold, *member_count, &group_secret)?;
+            mnemonics.push(member_secrets.into_iter()
+                .enumerate()
+                .map(|(member_index, value)| Slip39Share {
+                    identifier,
+                    extendable,
+                    iteration_exponent,
+                    group_index: group_index as u8,
+                    group_threshold,
+                    group_count: groups.len() as u8,
+                    member_index: member_index as u8,
+                    member_threshold: *member_threshold,
+                    value,
+                }.toMnemonic())
+                .collect());
+        }
+        Ok(mnemonics)
+    }
+
+    /// Recovers the master secret from exactly `group_threshold` groups of exactly `member_threshold` mnemonics each and
+    /// decrypts it with `passphrase`. Extra shares or groups are refused, as SLIP-0039 requires. A wrong passphrase is not
+    /// detected, it gives a different secret.
+    pub fn combine(mnemonics: &[String], passphrase: &[u8]) -> Result<Vec<u8>, Slip39Error> {
+        let shares = mnemonics.iter().map(|mnemonic| Slip39Share::fromMnemonic(mnemonic)).collect::<Result<Vec<Slip39Share>, MnemonicError>>()?;
+        let first = shares.first().ok_or(ShamirError::NotEnoughShares { required: 1, given: 0 })?;
+        if shares.iter().any(|share| (share.identifier, share.extendable, share.iteration_exponent, share.group_threshold, share.group_count)
+            != (first.identifier, first.extendable, first.iteration_exponent, first.group_threshold, first.group_count)) {
+            return Err(MnemonicError("shares belong to different secrets".to_string()).into());
+        }
+
+        let mut groups: BTreeMap<u8, Vec<&Slip39Share>> = BTreeMap::new();
+        for share in shares.iter() {
+            let members = groups.entry(share.group_index).or_default();
+            if members.iter().any(|member| member.member_index == share.member_index) {
+                return Err(ShamirError::DuplicateCoordinate(format!("{}/{}", share.group_index, share.member_index)).into());
+            }
+            if members.first().is_some_and(|member| member.member_threshold != share.member_threshold) {
+                return Err(MnemonicError(format!("shares of group {} disagree on the member threshold", share.group_index)).into());
+            }
+            members.push(share);
+        }
+
+        let required = first.group_threshold as usize;
+        if groups.len() < required {
+            return Err(ShamirError::NotEnoughShares { required, given: groups.len() }.into());
+        }
+        if groups.len() > required {
+            return Err(MnemonicError(format!("{} groups given, the group threshold is {}", groups.len(), required)).into());
+        }
+        let mut group_secrets: Vec<(u8, Vec<u8>)> = vec![];
+        for (group_index, members) in groups.iter() {
+            let threshold = members[0].member_threshold as usize;
+            if members.len() < threshold {
+                return Err(ShamirError::NotEnoughShares { required: threshold, given: members.len() }.into());
+            }
+            if members.len() > threshold {
+                return Err(MnemonicError(format!("{} shares of group {} given, its member threshold is {}", members.len(), group_index, threshold)).into());
+            }
+            let values: Vec<(u8, Vec<u8>)> = members.iter().map(|member| (member.member_index, member.value.clone())).collect();
+            group_secrets.push((*group_index, recoverSecret(threshold as u8, &values)?));
+        }
+        let encrypted = recoverSecret(first.group_threshold, &group_secrets)?;
+        Ok(Self::decrypt(&encrypted, passphrase, first.iteration_exponent, first.identifier, first.extendable))
+    }
+
+    /// Four round Feistel network with PBKDF2-HMAC-SHA256 as the round function.
+    pub fn encrypt(master_secret: &[u8], passphrase: &[u8], iteration_exponent: u8, identifier: u16, extendable: bool) -> Vec<u8> {
+        Self::feistel(master_secret, passphrase, iteration_exponent, identifier, extendable, (0..ROUND_COUNT).collect())
+    }
+
+    pub fn decrypt(encrypted: &[u8], passphrase: &[u8], iteration_exponent: u8, identifier: u16, extendable: bool) -> Vec<u8> {
+        Self::feistel(encrypted, passphrase, iteration_exponent, identifier, extendable, (0..ROUND_COUNT).rev().collect())
+    }
+
+    fn feistel(input: &[u8], passphrase: &[u8], iteration_exponent: u8, identifier: u16, extendable: bool, rounds: Vec<u8>) -> Vec<u8> {
+        let mut salt: Vec<u8> = vec![];
+        if !extendable {
+            salt.extend_from_slice(CUSTOMIZATION);
+            salt.extend_from_slice(&identifier.to_be_bytes());
+        }
+        let iterations = (BASE_ITERATION_COUNT << iteration_exponent) / ROUND_COUNT as u32;
+        let (mut left, mut right) = (input[..input.len() / 2].to_vec(), input[input.len() / 2..].to_vec());
+        for round in rounds {
+            let password = [&[round], passphrase].concat();
+            let mut output = vec![0u8; right.len()];
+            pbkdf2::pbkdf2::<Hmac<Sha256>>(&password, &[salt.as_slice(), &right].concat(), iterations, &mut output);
+            let next: Vec<u8> = left.iter().zip(output).map(|(a, b)| a ^ b).collect();
+            left = right;
+            right = next;
+        }
+        [right, left].concat()
+    }
+}
+
+fn customization(extendable: bool) -> &'static [u8] {
+    if extendable { CUSTOMIZATION_EXTENDABLE } else { CUSTOMIZATION }
+}
+
+fn polymod(customization: &[u8], values: &[u16]) -> u32 {
+    customization.iter().map(|c| *c as u16).chain(values.iter().copied()).fold(1u32, |checksum, value| {
+        let top = checksum >> 20;
+        let mut checksum = (checksum & 0xfffff) << 10 ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if top >> i & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+        checksum
+    })
+}
+
+fn createChecksum(customization: &[u8], words: &[u16]) -> Vec<u16> {
+    let checksum = polymod(customization, &[words, &[0, 0, 0]].concat()) ^ 1;
+    (0..CHECKSUM_WORDS).rev().map(|i| (checksum >> (i * RADIX_BITS) & 0x3ff) as u16).collect()
+}
+
+/// Big endian bits of `bytes` in 10 bit words, zero padded at the front.
+fn bytesToWords(bytes: &[u8]) -> Vec<u16> {
+    let bits = bytes.len() * 8;
+    let padding = bits.div_ceil(RADIX_BITS) * RADIX_BITS - bits;
+    let mut words = vec![0u16; bits.div_ceil(RADIX_BITS)];
+    for i in 0..bits {
+        let position = padding + i;
+        words[position / RADIX_BITS] |= ((bytes[i / 8] >> (7 - i % 8) & 1) as u16) << (RADIX_BITS - 1 - position % RADIX_BITS);
+    }
+    words
+}
+
+/// Inverse of `bytesToWords` for values whose length is a multiple of 16 bits, `None` if the padding is wrong.
+fn wordsToBytes(words: &[u16]) -> Option<Vec<u8>> {
+    let padding = words.len() * RADIX_BITS % 16;
+    if padding > 8 {
+        return None;
+    }
+    let bit = |position: usize| words[position / RADIX_BITS] >> (RADIX_BITS - 1 - position % RADIX_BITS) & 1;
+    if (0..padding).any(|position| bit(position) != 0) {
+        return None;
+    }
+    let mut bytes = vec![0u8; (words.len() * RADIX_BITS - padding) / 8];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = (0..8).fold(0u8, |value, j| value << 1 | bit(padding + i * 8 + j) as u8);
+    }
+    Some(bytes)
+}
+
+fn randomBytes(length: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; length];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn digest(random_part: &[u8], secret: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(random_part).expect("HMAC takes keys of any length");
+    mac.update(secret);
+    mac.finalize().into_bytes()[..DIGEST_LENGTH].to_vec()
+}
+
+/// Evaluates at `x` the polynomials through the `(index, value)` points, byte by byte.
+fn interpolate(points: &[(u8, Vec<u8>)], x: u8) -> Result<Vec<u8>, Slip39Error> {
+    if let Some((_, value)) = points.iter().find(|(index, _)| *index == x) {
+        return Ok(value.clone());
+    }
+    let length = points[0].1.len();
+    if points.iter().any(|(_, value)| value.len() != length) {
+        return Err(MnemonicError("share values have different lengths".to_string()).into());
+    }
+    let xs: Vec<GF256> = points.iter().map(|(index, _)| GF256(*index)).collect();
+    let coefficients = ShamirAlgorithm::lagrangeCoefficients(&xs, &GF256(x))?;
+    Ok((0..length)
+        .map(|i| points.iter()
+            .zip(coefficients.iter())
+            .fold(GF256::zero(), |sum, ((_, value), coefficient)| sum + GF256(value[i]) * *coefficient)
+            .0)
+        .collect())
+}
+
+/// SLIP-0039 sharing of one level: `threshold - 2` random shares, a digest share at 254 and the secret at 255 fix the
+/// polynomials, and the other shares are interpolated from them.
+fn splitSecret(threshold: u8, count: u8, secret: &[u8]) -> Result<Vec<Vec<u8>>, Slip39Error> {
+    if threshold == 1 {
+        return Ok(vec![secret.to_vec(); count as usize]);
+    }
+    let random_count = threshold - 2;
+    let mut points: Vec<(u8, Vec<u8>)> = (0..random_count).map(|index| (index, randomBytes(secret.len()))).collect();
+    let random_part = randomBytes(secret.len() - DIGEST_LENGTH);
+    let base: Vec<(u8, Vec<u8>)> = points.iter()
+        .cloned()
+        .chain([(DIGEST_INDEX, [digest(&random_part, secret), random_part].concat()), (SECRET_INDEX, secret.to_vec())])
+        .collect();
+    for index in random_count..count {
+        points.push((index, interpolate(&base, index)?));
+    }
+    Ok(points.into_iter().map(|(_, value)| value).collect())
+}
+
+/// Recovers a secret shared by `splitSecret` from exactly `threshold` points and checks its digest.
+fn recoverSecret(threshold: u8, points: &[(u8, Vec<u8>)]) -> Result<Vec<u8>, Slip39Error> {
+    if threshold == 1 {
+        return Ok(points[0].1.clone());
+    }
+    let secret = interpolate(points, SECRET_INDEX)?;
+    let digest_share = interpolate(points, DIGEST_INDEX)?;
+    if digest(&digest_share[DIGEST_LENGTH..], &secret) != digest_share[..DIGEST_LENGTH] {
+        return Err(MnemonicError("share digest does not match".to_string()).into());
+    }
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trezor_vector_without_sharing() {
+        let mnemonic = "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard".to_string();
+        assert_eq!(hex::encode(Slip39::combine(std::slice::from_ref(&mnemonic), b"TREZOR").unwrap()), "bb54aac4b89dc868ba37d9cc21b2cece");
+        assert_eq!(Slip39Share::fromMnemonic(&mnemonic).unwrap().toMnemonic(), mnemonic);
+
+        let wrong_checksum = mnemonic.replace("keyboard", "kidney");
+        assert_eq!(Slip39::combine(&[wrong_checksum], b"TREZOR"), Err(Slip39Error::Mnemonic(MnemonicError("checksum does not match".to_string()))));
+    }
+
+    fn combineVector(mnemonics: &[&str]) -> Result<String, Slip39Error> {
+        let mnemonics: Vec<String> = mnemonics.iter().map(|mnemonic| mnemonic.to_string()).collect();
+        Slip39::combine(&mnemonics, b"TREZOR").map(hex::encode)
+    }
+
+    // Vectors from SLIP-0039's vectors.json, all with the passphrase "TREZOR".
+    #[test]
+    fn test_trezor_vectors_with_groups() {
+        let eraser = [
+            "eraser senior decision roster beard treat identify grumpy salt index fake aviation theater cubic bike cause research dragon emphasis counter",
+            "eraser senior ceramic snake clay various huge numb argue hesitate auction category timber browser greatest hanger petition script leaf pickup",
+            "eraser senior ceramic shaft dynamic become junior wrist silver peasant force math alto coal amazing segment yelp velvet image paces",
+            "eraser senior ceramic round column hawk trust auction smug shame alive greatest sheriff living perfect corner chest sled fumes adequate",
+            "eraser senior decision smug corner ruin rescue cubic angel tackle skin skunk program roster trash rumor slush angel flea amazing",
+        ];
+        let shadow = "eraser senior decision shadow artist work morning estate greatest pipeline plan ting petition forget hormone flexible general goat admit surface";
+        assert_eq!(combineVector(&eraser), Ok("7c3397a292a5941682d7a4ae2d898d11".to_string()));
+        assert_eq!(combineVector(&[shadow]), Err(Slip39Error::Sharing(ShamirError::NotEnoughShares { required: 2, given: 1 })));
+        assert_eq!(combineVector(&[shadow, eraser[3]]), Err(Slip39Error::Sharing(ShamirError::NotEnoughShares { required: 3, given: 1 })));
+        assert!(matches!(combineVector(&[&eraser[..], &[shadow]].concat()), Err(Slip39Error::Mnemonic(_))));
+
+        assert_eq!(combineVector(&[
+            "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+            "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+        ]), Ok("b43ceb7e57a0ea8766221624d01b0864".to_string()));
+    }
+
+    #[test]
+    fn test_trezor_extendable_vectors() {
+        assert_eq!(combineVector(&[
+            "testify swimming academic academic column loyalty smear include exotic bedroom exotic wrist lobe cover grief golden smart junior estimate learn",
+        ]), Ok("1679b4516e0ee5954351d288a838f45e".to_string()));
+        assert_eq!(combineVector(&[
+            "enemy favorite academic acid cowboy phrase havoc level response walnut budget painting inside trash adjust froth kitchen learn tidy punish",
+            "enemy favorite academic always academic sniff script carpet romp kind promise scatter center unfair training emphasis evening belong fake enforce",
+        ]), Ok("48b1a4b80b8c209ad42c33672bdaa428".to_string()));
+    }
+
+    #[test]
+    fn test_trezor_invalid_vectors() {
+        let invalid: [&[&str]; 7] = [
+            // Invalid padding.
+            &["duckling enlarge academic academic email result length solution fridge kidney coal piece deal husband erode duke ajar music cargo fitness"],
+            // Different identifiers.
+            &["adequate smoking academic acid debut wine petition glen cluster slow rhyme slow simple epidemic rumor junk tracks treat olympic tolerate", "adequate stay academic agency agency formal party ting frequent learn upstairs remember smear leaf damage anatomy ladle market hush corner"],
+            // Different iteration exponents.
+            &["peasant leaves academic acid desert exact olympic math alive axle trial tackle drug deny decent smear dominant desert bucket remind", "peasant leader academic agency cultural blessing percent network envelope medal junk primary human pumps jacket fragment payroll ticket evoke voice"],
+            // Different group thresholds.
+            &["liberty category beard email beyond should fancy romp founder easel pink holy hairy romp loyalty material victim owner toxic custody", "liberty category academic easy being hazard crush diminish oral lizard reaction cluster force dilemma deploy force club veteran expect photo"],
+            // Group threshold greater than the group count.
+            &["music husband acrobat acid artist finance center either graduate swimming object bike medical clothes station aspect spider maiden bulb welcome", "music husband acrobat agency advance hunting bike corner density careful material civil evil tactics remind hawk discuss hobo voice rainbow"],
+            // Different member thresholds.
+            &["hour painting academic academic device formal evoke guitar random modern justice filter withdraw trouble identify mailman insect general cover oven", "hour painting academic agency artist again daisy capital beaver fiber much enjoy suitable symbolic identify photo editor romp float echo"],
+            // Invalid digest.
+            &["guilt walnut academic acid deliver remove equip listen vampire tactics nylon rhythm failure husband fatigue alive blind enemy teaspoon rebound", "guilt walnut academic agency brave hamster hobo declare herd taste alpha slim criminal mild arcade formal romp branch pink ambition"],
+        ];
+        for mnemonics in invalid {
+            assert!(matches!(combineVector(mnemonics), Err(Slip39Error::Mnemonic(_))), "{:?}", mnemonics);
+        }
+        // Duplicate member indices.
+        assert_eq!(combineVector(&[
+            "device stay academic always dive coal antenna adult black exceed stadium herald advance soldier busy dryer daughter evaluate minister laser",
+            "device stay academic always dwarf afraid robin gravity crunch adjust soul branch walnut coastal dream costume scholar mortgage mountain pumps",
+        ]), Err(Slip39Error::Sharing(ShamirError::DuplicateCoordinate("0/2".to_string()))));
+    }
+
+    #[test]
+    fn test_generate_and_combine_groups() {
+        let secret = randomBytes(32);
+        let groups = Slip39::generate(2, &[(1, 1), (2, 3), (3, 5)], &secret, b"passphrase", 0).unwrap();
+        assert_eq!(groups.iter().map(|group| group.len()).collect::<Vec<usize>>(), vec![1, 3, 5]);
+
+        let mnemonics = vec![groups[1][2].clone(), groups[2][4].clone(), groups[2][0].clone(), groups[1][0].clone(), groups[2][1].clone()];
+        assert_eq!(Slip39::combine(&mnemonics, b"passphrase"), Ok(secret.clone()));
+        assert_eq!(Slip39::combine(&[groups[0][0].clone(), groups[2][3].clone(), groups[2][1].clone(), groups[2][2].clone()], b"passphrase"), Ok(secret.clone()));
+        assert_ne!(Slip39::combine(&mnemonics, b"other"), Ok(secret));
+        assert_eq!(Slip39::combine(&mnemonics[..4], b"passphrase"), Err(Slip39Error::Sharing(ShamirError::NotEnoughShares { required: 3, given: 2 })));
+        assert!(matches!(Slip39::combine(&[&mnemonics[..], &[groups[1][1].clone()]].concat(), b"passphrase"), Err(Slip39Error::Mnemonic(_))));
+        assert!(matches!(Slip39::combine(&[&mnemonics[..], &[groups[0][0].clone()]].concat(), b"passphrase"), Err(Slip39Error::Mnemonic(_))));
+    }
+
+    #[test]
+    fn test_generate_rejects_bad_parameters() {
+        let secret = randomBytes(16);
+        assert!(Slip39::generate(1, &[(1, 2)], &secret, b"", 0).is_err());
+        assert!(Slip39::generate(3, &[(1, 1), (2, 3)], &secret, b"", 0).is_err());
+        assert!(Slip39::generate(1, &[(2, 3)], &secret[..15], b"", 0).is_err());
+    }
+}
@@ -0,0 +1,543 @@
+use std::{collections::{HashMap, VecDeque}, fmt, future::Future, sync::Arc, time::Duration};
+
+use tokio::{io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt}, net::{tcp::OwnedReadHalf, TcpListener, TcpStream}, sync::{mpsc, Mutex}, time::{interval, sleep, timeout, Instant}};
+
+use super::{cluster::ClusterConfig, noise::{CipherState, NoiseHandshake, NoiseSession, MAX_MESSAGE_LENGTH, TAG_LENGTH}};
+
+/// Prologue both sides of every handshake mix in, so only nodes speaking this protocol version complete it.
+const PROLOGUE: &[u8] = b"node-rpc-rust transport v1";
+/// Messages waiting per peer, and received messages waiting to be taken, before senders have to wait.
+pub const QUEUE_CAPACITY: usize = 64;
+/// Largest payload of one message, what is left of a Noise message after its tag and the frame header.
+pub const MAX_PAYLOAD_LENGTH: usize = MAX_MESSAGE_LENGTH - TAG_LENGTH - FRAME_HEADER_LENGTH;
+/// Kind and sequence number in front of every message.
+const FRAME_HEADER_LENGTH: usize = 9;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(50);
+const MAX_BACKOFF: Duration = Duration::from_secs(5);
+/// Time a new connection has to complete its handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+/// Silence after which a connection is given up, a connection with nothing to send pings every `PING_INTERVAL`.
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A channel between nodes failed or a peer could not be authenticated, the string says how.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TransportError(pub String);
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Transport failed: {}", self.0)
+    }
+}
+
+fn transportError(reason: &str) -> TransportError {
+    TransportError(reason.to_string())
+}
+
+/// Authenticated, ordered delivery of byte messages between the nodes of a cluster, nodes named by their x-coordinate.
+/// Sending waits while the peer's queue is full, so a slow peer slows its senders down instead of growing memory.
+#[allow(dead_code)]
+pub trait Transport {
+    /// x-coordinate of this node.
+    fn local(&self) -> u8;
+
+    /// x-coordinates of every other node.
+    fn peers(&self) -> Vec<u8>;
+
+    /// Queues `payload` for the node `to`.
+    fn send(&self, to: u8, payload: Vec<u8>) -> impl Future<Output = Result<(), TransportError>> + Send;
+
+    /// Waits for the next message from any peer, returns its sender and payload.
+    fn receive(&mut self) -> impl Future<Output = Result<(u8, Vec<u8>), TransportError>> + Send;
+
+    /// Queues `payload` for every other node.
+    fn broadcast(&self, payload: Vec<u8>) -> impl Future<Output = Result<(), TransportError>> + Send where Self: Sync {
+        async move {
+            for to in self.peers() {
+                self.send(to, payload.clone()).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// The sending side of a transport, which can be cloned into every task that sends while another one receives.
+/// Every peer has a bounded queue of `(sender, payload)` pairs.
+#[derive(Clone)]
+pub struct Outbox {
+    local: u8,
+    queues: HashMap<u8, mpsc::Sender<(u8, Vec<u8>)>>,
+}
+
+#[allow(dead_code)]
+impl Outbox {
+    pub fn local(&self) -> u8 {
+        self.local
+    }
+
+    pub fn peers(&self) -> Vec<u8> {
+        let mut peers: Vec<u8> = self.queues.keys().cloned().collect();
+        peers.sort();
+        peers
+    }
+
+    /// Queues `payload` for the node `to`, waiting while its queue is full.
+    pub async fn send(&self, to: u8, payload: Vec<u8>) -> Result<(), TransportError> {
+        if payload.len() > MAX_PAYLOAD_LENGTH {
+            return Err(transportError("message is too long for one frame"));
+        }
+        let queue = self.queues.get(&to).ok_or_else(|| transportError("no such peer"))?;
+        queue.send((self.local, payload)).await.map_err(|_| transportError("peer is gone"))
+    }
+}
+
+/// Delivers messages between nodes running in the same process, through bounded channels.
+pub struct InMemoryTransport {
+    outbox: Outbox,
+    inbox: mpsc::Receiver<(u8, Vec<u8>)>,
+}
+
+#[allow(dead_code)]
+impl InMemoryTransport {
+    /// One connected transport per x-coordinate in `xs`, in the same order.
+    pub fn network(xs: &[u8]) -> Vec<Self> {
+        let channels: Vec<_> = xs.iter().map(|_| mpsc::channel(QUEUE_CAPACITY)).collect();
+        let senders: HashMap<u8, mpsc::Sender<(u8, Vec<u8>)>> = xs.iter().zip(channels.iter()).map(|(x, (sender, _))| (*x, sender.clone())).collect();
+        xs.iter().zip(channels)
+            .map(|(x, (_, inbox))| InMemoryTransport {
+                outbox: Outbox {
+                    local: *x,
+                    queues: senders.iter().filter(|(to, _)| *to != x).map(|(to, sender)| (*to, sender.clone())).collect(),
+                },
+                inbox,
+            })
+            .collect()
+    }
+
+    pub fn outbox(&self) -> Outbox {
+        self.outbox.clone()
+    }
+}
+
+impl Transport for InMemoryTransport {
+    fn local(&self) -> u8 {
+        self.outbox.local()
+    }
+
+    fn peers(&self) -> Vec<u8> {
+        self.outbox.peers()
+    }
+
+    async fn send(&self, to: u8, payload: Vec<u8>) -> Result<(), TransportError> {
+        self.outbox.send(to, payload).await
+    }
+
+    async fn receive(&mut self) -> Result<(u8, Vec<u8>), TransportError> {
+        self.inbox.recv().await.ok_or_else(|| transportError("every peer is gone"))
+    }
+}
+
+async fn writeFrame<W: AsyncWrite + Unpin>(stream: &mut W, frame: &[u8]) -> std::io::Result<()> {
+    stream.write_all(&(frame.len() as u16).to_be_bytes()).await?;
+    stream.write_all(frame).await
+}
+
+async fn readFrame<R: AsyncRead + Unpin>(stream: &mut R) -> std::io::Result<Vec<u8>> {
+    let mut length = [0u8; 2];
+    stream.read_exact(&mut length).await?;
+    let mut frame = vec![0u8; u16::from_be_bytes(length) as usize];
+    stream.read_exact(&mut frame).await?;
+    Ok(frame)
+}
+
+/// Runs a Noise XX handshake over `stream` and returns the session keys once the peer proved its static key.
+/// Gives up after `HANDSHAKE_TIMEOUT`, so a connection that never authenticates does not hold on to its task.
+async fn handshake(stream: &mut TcpStream, static_key: [u8; 32], initiator: bool) -> Result<NoiseSession, TransportError> {
+    let io = |err: std::io::Error| transportError(&err.to_string());
+    let exchange = async {
+        let mut noise = NoiseHandshake::new(initiator, static_key, PROLOGUE);
+        while !noise.isFinished() {
+            if noise.isMyTurn() {
+                let message = noise.writeMessage(&[])?;
+                writeFrame(stream, &message).await.map_err(io)?;
+            } else {
+                let message = readFrame(stream).await.map_err(io)?;
+                noise.readMessage(&message)?;
+            }
+        }
+        noise.intoSession()
+    };
+    timeout(HANDSHAKE_TIMEOUT, exchange).await.map_err(|_| transportError("handshake timed out"))?
+}
+
+/// What one Noise transport message carries, the first byte naming the kind.
+#[derive(Debug, PartialEq)]
+enum Frame {
+    /// Opens every connection, `stream` names the dialing transport so its sequence numbers are told apart from
+    /// those of an earlier run of the same node.
+    Hello { stream: u64 },
+    Data { sequence: u64, payload: Vec<u8> },
+    /// Every message up to `sequence` is delivered.
+    Ack { sequence: u64 },
+    Ping,
+}
+
+impl Frame {
+    fn toBytes(&self) -> Vec<u8> {
+        match self {
+            Frame::Hello { stream } => [&[0u8][..], &stream.to_be_bytes()].concat(),
+            Frame::Data { sequence, payload } => [&[1u8][..], &sequence.to_be_bytes(), payload].concat(),
+            Frame::Ack { sequence } => [&[2u8][..], &sequence.to_be_bytes()].concat(),
+            Frame::Ping => vec![3],
+        }
+    }
+
+    fn fromBytes(bytes: &[u8]) -> Result<Self, TransportError> {
+        let number = || -> Result<u64, TransportError> {
+            bytes.get(1..9)
+                .and_then(|number| number.try_into().ok())
+                .map(u64::from_be_bytes)
+                .ok_or_else(|| transportError("frame is too short"))
+        };
+        match bytes.first() {
+            Some(0) if bytes.len() == 9 => Ok(Frame::Hello { stream: number()? }),
+            Some(1) => Ok(Frame::Data { sequence: number()?, payload: bytes[9..].to_vec() }),
+            Some(2) if bytes.len() == 9 => Ok(Frame::Ack { sequence: number()? }),
+            Some(3) if bytes.len() == 1 => Ok(Frame::Ping),
+            _ => Err(transportError("unknown frame")),
+        }
+    }
+}
+
+/// The last message delivered from each peer, by x-coordinate, as the stream it came on and its sequence number.
+type Delivered = Arc<Mutex<HashMap<u8, (u64, u64)>>>;
+
+/// Node to node transport over TCP. Every node dials each peer once and only writes messages on that connection, and
+/// reads what its peers write on the connections they dial in, so a pair of nodes never has to agree on one connection.
+/// Frames are a 2 byte big-endian length followed by a Noise transport message, authenticated with the node's transport
+/// key, which is not its HPKE encryption key.
+///
+/// Messages are numbered and the reading side acknowledges each one it delivered. A message stays with the dialing side
+/// until it is acknowledged and is written again when the connection is dialed anew, the reading side drops the ones it
+/// already delivered, so a dropped connection neither loses nor repeats messages. Messages still unacknowledged when the
+/// sending node stops are lost, which the session timeouts above account for.
+#[allow(dead_code)]
+pub struct TcpTransport {
+    outbox: Outbox,
+    inbox: mpsc::Receiver<(u8, Vec<u8>)>,
+}
+
+#[allow(dead_code)]
+impl TcpTransport {
+    /// Accepts peers on `listener` and starts dialing every peer with a transport address and key. Must run inside a
+    /// tokio runtime. Fails if the node has no transport key.
+    pub fn start(config: &ClusterConfig, listener: TcpListener) -> Result<Self, TransportError> {
+        let static_key = config.staticKey().ok_or_else(|| transportError("the node has no transport key"))?;
+        let config = Arc::new(config.clone());
+        let (inbox_sender, inbox) = mpsc::channel(QUEUE_CAPACITY);
+        tokio::spawn(Self::accept(config.clone(), listener, inbox_sender.clone()));
+        let mut queues = HashMap::new();
+        for peer in config.peers.iter() {
+            let (sender, queue) = mpsc::channel(QUEUE_CAPACITY);
+            if let (Some(address), Some(remote_key)) = (peer.transport_address.clone(), peer.transport_key) {
+                tokio::spawn(Self::dial(static_key, address, remote_key, queue, inbox_sender.clone()));
+            }
+            queues.insert(peer.x, sender);
+        }
+        Ok(TcpTransport { outbox: Outbox { local: config.x, queues }, inbox })
+    }
+
+    pub fn outbox(&self) -> Outbox {
+        self.outbox.clone()
+    }
+
+    pub async fn bind(config: &ClusterConfig, address: &str) -> Result<Self, TransportError> {
+        let listener = TcpListener::bind(address).await.map_err(|err| transportError(&err.to_string()))?;
+        Self::start(config, listener)
+    }
+
+    /// Accepts connections until the transport is dropped.
+    async fn accept(config: Arc<ClusterConfig>, listener: TcpListener, inbox: mpsc::Sender<(u8, Vec<u8>)>) {
+        let delivered: Delivered = Arc::new(Mutex::new(HashMap::new()));
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => {
+                        tokio::spawn(Self::serve(config.clone(), stream, inbox.clone(), delivered.clone()));
+                    }
+                    Err(_) => return,
+                },
+                _ = inbox.closed() => return,
+            }
+        }
+    }
+
+    /// Reads the frames of one inbound connection until it closes or stays silent for `READ_TIMEOUT`, and acknowledges
+    /// every message. A peer that does not authenticate as a known node in time is dropped. Waiting for room in the inbox
+    /// stops reading, which in turn fills the peer's socket buffer.
+    async fn serve(config: Arc<ClusterConfig>, mut stream: TcpStream, inbox: mpsc::Sender<(u8, Vec<u8>)>, delivered: Delivered) {
+        let mut session = match handshake(&mut stream, config.staticKey().unwrap(), false).await {
+            Ok(session) => session,
+            Err(_) => return,
+        };
+        let from = match config.peerWithKey(&session.remote_static) {
+            Some(peer) => peer.x,
+            None => return,
+        };
+        let mut stream_id = None;
+        while let Ok(Ok(frame)) = timeout(READ_TIMEOUT, readFrame(&mut stream)).await {
+            let frame = match session.decrypt(&frame).and_then(|plaintext| Frame::fromBytes(&plaintext)) {
+                Ok(frame) => frame,
+                Err(_) => return,
+            };
+            let (sender, sequence, payload) = match (frame, stream_id) {
+                (Frame::Hello { stream }, None) => {
+                    stream_id = Some(stream);
+                    continue;
+                }
+                (Frame::Ping, _) => continue,
+                (Frame::Data { sequence, payload }, Some(sender)) => (sender, sequence, payload),
+                _ => return,
+            };
+            // Held while the message is handed over, so a connection dialed again after this one cannot deliver the
+            // same message before this one is done with it.
+            let mut delivered = delivered.lock().await;
+            let duplicate = matches!(delivered.get(&from), Some((last_stream, last)) if *last_stream == sender && *last >= sequence);
+            if !duplicate {
+                if inbox.send((from, payload)).await.is_err() {
+                    return;
+                }
+                delivered.insert(from, (sender, sequence));
+            }
+            drop(delivered);
+            let ack = match session.encrypt(&Frame::Ack { sequence }.toBytes()) {
+                Ok(ack) => ack,
+                Err(_) => return,
+            };
+            if writeFrame(&mut stream, &ack).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    async fn connect(static_key: [u8; 32], address: &str, remote_key: [u8; 32]) -> Result<(TcpStream, NoiseSession), TransportError> {
+        let mut stream = TcpStream::connect(address).await.map_err(|err| transportError(&err.to_string()))?;
+        let session = handshake(&mut stream, static_key, true).await?;
+        if session.remote_static != remote_key {
+            return Err(transportError("peer authenticated with another static key"));
+        }
+        Ok((stream, session))
+    }
+
+    /// Passes the acknowledgements the peer writes back on to the dialing side, ends with the connection.
+    async fn readAcks(mut reader: OwnedReadHalf, mut receiver: CipherState, acks: mpsc::Sender<u64>) {
+        while let Ok(frame) = readFrame(&mut reader).await {
+            let sequence = match receiver.decryptWithAd(&[], &frame).and_then(|plaintext| Frame::fromBytes(&plaintext)) {
+                Ok(Frame::Ack { sequence }) => sequence,
+                _ => return,
+            };
+            if acks.send(sequence).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Keeps a connection to one peer and writes its queued messages, reconnecting with exponential backoff when the
+    /// connection fails or the peer stops acknowledging. At most `QUEUE_CAPACITY` messages wait for an acknowledgement,
+    /// every one of them is written again on a new connection. Ends when the transport is dropped, which `inbox` tells
+    /// while the peer is unreachable.
+    async fn dial(static_key: [u8; 32], address: String, remote_key: [u8; 32], mut queue: mpsc::Receiver<(u8, Vec<u8>)>, inbox: mpsc::Sender<(u8, Vec<u8>)>) {
+        let stream_id: u64 = rand::random();
+        let mut unacked: VecDeque<(u64, Vec<u8>)> = VecDeque::new();
+        let mut next_sequence = 1;
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            let (stream, session) = match Self::connect(static_key, &address, remote_key).await {
+                Ok(connection) => connection,
+                Err(_) if inbox.is_closed() => return,
+                Err(_) => {
+                    sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF);
+                    continue;
+                }
+            };
+            backoff = INITIAL_BACKOFF;
+            let (reader, mut writer) = stream.into_split();
+            let (mut sender, receiver) = session.split();
+            let (ack_sender, mut acks) = mpsc::channel(QUEUE_CAPACITY);
+            let reader = tokio::spawn(Self::readAcks(reader, receiver, ack_sender));
+            let mut write = |frame: Frame| sender.encryptWithAd(&[], &frame.toBytes());
+
+            let mut frames = vec![write(Frame::Hello { stream: stream_id })];
+            frames.extend(unacked.iter().map(|(sequence, payload)| write(Frame::Data { sequence: *sequence, payload: payload.clone() })));
+            let mut connected = true;
+            for frame in frames {
+                connected = connected && match frame {
+                    Ok(frame) => writeFrame(&mut writer, &frame).await.is_ok(),
+                    Err(_) => false,
+                };
+            }
+            let mut keepalive = interval(PING_INTERVAL);
+            let mut progress = Instant::now();
+            while connected {
+                let frame = tokio::select! {
+                    ack = acks.recv() => match ack {
+                        Some(sequence) => {
+                            while unacked.front().is_some_and(|(first, _)| *first <= sequence) {
+                                unacked.pop_front();
+                            }
+                            progress = Instant::now();
+                            continue;
+                        }
+                        None => break,
+                    },
+                    payload = queue.recv(), if unacked.len() < QUEUE_CAPACITY => match payload {
+                        Some((_, payload)) => {
+                            if unacked.is_empty() {
+                                progress = Instant::now();
+                            }
+                            let sequence = next_sequence;
+                            next_sequence += 1;
+                            unacked.push_back((sequence, payload.clone()));
+                            Frame::Data { sequence, payload }
+                        }
+                        None => {
+                            reader.abort();
+                            return;
+                        }
+                    },
+                    _ = keepalive.tick() => {
+                        if !unacked.is_empty() && progress.elapsed() > READ_TIMEOUT {
+                            break;
+                        }
+                        Frame::Ping
+                    }
+                };
+                connected = match write(frame) {
+                    Ok(frame) => writeFrame(&mut writer, &frame).await.is_ok(),
+                    Err(_) => false,
+                };
+            }
+            reader.abort();
+        }
+    }
+}
+
+impl Transport for TcpTransport {
+    fn local(&self) -> u8 {
+        self.outbox.local()
+    }
+
+    fn peers(&self) -> Vec<u8> {
+        self.outbox.peers()
+    }
+
+    async fn send(&self, to: u8, payload: Vec<u8>) -> Result<(), TransportError> {
+        self.outbox.send(to, payload).await
+    }
+
+    async fn receive(&mut self) -> Result<(u8, Vec<u8>), TransportError> {
+        self.inbox.recv().await.ok_or_else(|| transportError("the listener stopped"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{cluster::Peer, hpke::Hpke};
+    use ed25519_dalek::SigningKey;
+
+    fn configs(addresses: &[String]) -> Vec<ClusterConfig> {
+        let keys: Vec<([u8; 32], [u8; 32])> = addresses.iter().map(|_| Hpke::generateKeyPair()).collect();
+        let transport_keys: Vec<([u8; 32], [u8; 32])> = addresses.iter().map(|_| Hpke::generateKeyPair()).collect();
+        let peers: Vec<Peer> = addresses.iter().enumerate()
+            .map(|(i, address)| Peer::parse(&format!(
+                "{}@127.0.0.1:1@{}@{}@{}@{}", i + 1, hex::encode(SigningKey::from_bytes(&[i as u8; 32]).verifying_key().to_bytes()), hex::encode(keys[i].1), address, hex::encode(transport_keys[i].1)
+            )).unwrap())
+            .collect();
+        (0..addresses.len())
+            .map(|i| ClusterConfig::new(i as u8 + 1, [i as u8; 32], keys[i].0, Some(transport_keys[i].0), peers.iter().filter(|peer| peer.x != i as u8 + 1).cloned().collect()).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_transport() {
+        let mut nodes = InMemoryTransport::network(&[1, 2, 3]);
+        assert_eq!(nodes[0].peers(), vec![2, 3]);
+        nodes[0].broadcast(b"hello".to_vec()).await.unwrap();
+        nodes[2].send(2, b"from 3".to_vec()).await.unwrap();
+        assert_eq!(nodes[1].receive().await.unwrap(), (1, b"hello".to_vec()));
+        assert_eq!(nodes[1].receive().await.unwrap(), (3, b"from 3".to_vec()));
+        assert_eq!(nodes[2].receive().await.unwrap(), (1, b"hello".to_vec()));
+        assert!(nodes[0].send(1, vec![]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_transport_reconnects() {
+        let first = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let second = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addresses = vec![first.local_addr().unwrap().to_string(), second.local_addr().unwrap().to_string()];
+        let configs = configs(&addresses);
+        // The second node is not listening yet, its messages wait in the queue until it comes up.
+        drop(second);
+        let mut node1 = TcpTransport::start(&configs[0], first).unwrap();
+        node1.send(2, b"queued".to_vec()).await.unwrap();
+        node1.send(2, b"in order".to_vec()).await.unwrap();
+        sleep(Duration::from_millis(200)).await;
+        let mut node2 = TcpTransport::bind(&configs[1], &addresses[1]).await.unwrap();
+        assert_eq!(node2.receive().await.unwrap(), (1, b"queued".to_vec()));
+        assert_eq!(node2.receive().await.unwrap(), (1, b"in order".to_vec()));
+        node2.send(1, b"reply".to_vec()).await.unwrap();
+        assert_eq!(node1.receive().await.unwrap(), (2, b"reply".to_vec()));
+    }
+
+    #[tokio::test]
+    async fn test_tcp_transport_resends_unacknowledged_messages() {
+        let first = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let second = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addresses = vec![first.local_addr().unwrap().to_string(), second.local_addr().unwrap().to_string()];
+        let configs = configs(&addresses);
+        let node1 = TcpTransport::start(&configs[0], first).unwrap();
+        let mut node2 = TcpTransport::start(&configs[1], second).unwrap();
+        node1.send(2, b"delivered".to_vec()).await.unwrap();
+        assert_eq!(node2.receive().await.unwrap(), (1, b"delivered".to_vec()));
+        // The second node goes away with the next message on the wire, unacknowledged, and comes back as a new run.
+        drop(node2);
+        node1.send(2, b"in flight".to_vec()).await.unwrap();
+        sleep(Duration::from_millis(200)).await;
+        let mut node2 = TcpTransport::bind(&configs[1], &addresses[1]).await.unwrap();
+        node1.send(2, b"after".to_vec()).await.unwrap();
+        assert_eq!(node2.receive().await.unwrap(), (1, b"in flight".to_vec()));
+        assert_eq!(node2.receive().await.unwrap(), (1, b"after".to_vec()));
+    }
+
+    #[test]
+    fn test_frames_round_trip() {
+        let frames = vec![
+            Frame::Hello { stream: 7 },
+            Frame::Data { sequence: 1, payload: b"payload".to_vec() },
+            Frame::Data { sequence: u64::MAX, payload: vec![] },
+            Frame::Ack { sequence: 42 },
+            Frame::Ping,
+        ];
+        for frame in frames {
+            assert_eq!(Frame::fromBytes(&frame.toBytes()).unwrap(), frame);
+        }
+        assert!(Frame::fromBytes(&[2, 0]).is_err());
+        assert!(Frame::fromBytes(&[4]).is_err());
+        assert!(Frame::fromBytes(&[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tcp_transport_drops_unknown_nodes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let node = configs(&[address.clone(), "127.0.0.1:1".to_string()]);
+        let _transport = TcpTransport::start(&node[0], listener).unwrap();
+        let (stranger, _) = Hpke::generateKeyPair();
+        let mut stream = TcpStream::connect(&address).await.unwrap();
+        let mut session = handshake(&mut stream, stranger, true).await.unwrap();
+        writeFrame(&mut stream, &session.encrypt(b"let me in").unwrap()).await.unwrap();
+        assert!(readFrame(&mut stream).await.is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{database::{SecretRepository::SecretRepository, UserRepository::UserRepository}, models::Holder::ShareCustody, services::{ClusterService::{ClusterRequest, ClusterService, ClusterTask, Outcome}, SecretService::SecretService}, util::{bls::{BlsFeldmanVSS, BlsScalar}, cluster::{ClusterConfig, ShareDelivery}, ecdsa::ThresholdEcdsa, ed25519::{Ed25519FeldmanVSS, Ed25519Scalar}, envelope::ShareEnvelope, session::{SessionChannel, SessionRouter}, transport::Transport}, views::{SaveSecret::inner_save_secret, User::{rollback_user, store_user_keys}}};
+
+use actix_web::{get, web::Data, HttpResponse};
+use mongodb::bson::{doc, oid::ObjectId};
+
+/// Refuses a request in cluster mode, for handlers that still expect every share of a key on this node.
+pub fn standalone_only(cluster: &Option<ClusterConfig>) -> Result<(), HttpResponse> {
+    match cluster {
+        Some(_) => Err(HttpResponse::Conflict().body("Not supported in cluster mode, this node only holds its own share of every key")),
+        None => Ok(()),
+    }
+}
+
+/// The sessions of the node transport, without which a node of a cluster can not reach its peers.
+pub fn cluster_router(router: Option<&SessionRouter>) -> Result<&SessionRouter, HttpResponse> {
+    router.ok_or_else(|| HttpResponse::Conflict().body("Cluster mode needs the node transport, NODE_TRANSPORT is not set"))
+}
+
+/// Stores the shares of a new key. Standalone, every share is stored here with `inner_save_secret`. In cluster mode this
+/// node keeps only the share at its own x-coordinate and sends every other share, sealed to its node, over the node
+/// transport. The key is kept once every node reported its share stored, otherwise every node deletes it again.
+#[allow(clippy::too_many_arguments)]
+pub async fn inner_deal_secret<F: Display>(cluster: Option<&ClusterConfig>, router: Option<&SessionRouter>, db: Data<SecretRepository>, pub_key: &str, user_id: ObjectId, partial_secret: Vec<Vec<F>>, commitments: Vec<String>, scheme: &str, secret_degree: u8, custody: &[ShareCustody]) -> HttpResponse {
+    let cluster = match cluster {
+        Some(cluster) => cluster,
+        None => return inner_save_secret(db, pub_key, user_id, partial_secret, commitments, scheme, secret_degree, custody).await,
+    };
+    let router = match cluster_router(router) {
+        Ok(router) => router,
+        Err(response) => return response,
+    };
+    let mut own: Vec<Vec<F>> = Vec::new();
+    let mut requests: Vec<(u8, ClusterTask)> = Vec::new();
+    for share in partial_secret {
+        let x: Option<u8> = share[0].to_string().parse().ok();
+        if x == Some(cluster.x) {
+            own.push(share);
+            continue;
+        }
+        let peer = match x.and_then(|x| cluster.peer(x)) {
+            Some(peer) => peer,
+            None => return HttpResponse::InternalServerError().body(format!("No node owns the share at x = {}", share[0])),
+        };
+        let envelope = match SecretService::encodeShare(&share, scheme, secret_degree, pub_key) {
+            Ok(envelope) => envelope,
+            Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+        };
+        match cluster.seal(peer.x, &user_id.to_hex(), pub_key, scheme, secret_degree, &commitments, &envelope) {
+            Ok(delivery) => requests.push((peer.x, ClusterTask::StoreShare(delivery))),
+            Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+        };
+    }
+    if own.len() != 1 {
+        return HttpResponse::InternalServerError().body("This node's x-coordinate must receive exactly one share");
+    }
+    let mut reports = match ClusterService::ask(router, &format!("share/{}", pub_key), requests).await {
+        Ok(reports) => reports,
+        Err(err) => return HttpResponse::BadGateway().body(err.to_string()),
+    };
+    let response = inner_save_secret(db.clone(), pub_key, user_id, own, commitments, scheme, secret_degree, &[]).await;
+    let reported = ClusterService::awaitReports(&mut reports).await;
+    let decision: Outcome = match (&reported, response.status().is_success()) {
+        (Ok(()), true) => Ok(()),
+        (Err(err), _) => Err(err.to_string()),
+        (Ok(()), false) => Err(format!("node {} could not store its share", cluster.x)),
+    };
+    if let Err(err) = ClusterService::decide(&reports, &decision).await {
+        log::warn!("Could not tell every node the decision on {}: {}", pub_key, err);
+    }
+    match decision {
+        Ok(()) => response,
+        Err(reason) => {
+            if let Err(err) = db.delete_key(pub_key).await {
+                log::error!("Could not roll back {}: {}", pub_key, err);
+            }
+            match reported {
+                Ok(()) => response,
+                Err(_) => HttpResponse::BadGateway().body(reason),
+            }
+        }
+    }
+}
+
+/// Checks and stores this node's share of a key dealt by the peer `from`. The delivery must be signed by that peer, open
+/// with this node's encryption key and verify against the commitments it comes with.
+async fn accept_delivery(cluster: &ClusterConfig, db: Data<SecretRepository>, from: u8, delivery: ShareDelivery) -> Outcome {
+    if delivery.sender != from {
+        return Err("the delivery claims another sender".to_string());
+    }
+    let user_id = ObjectId::from_str(&delivery.user_id).map_err(|_| "user_id must be an object id".to_string())?;
+    let envelope = cluster.open(&delivery).map_err(|err| err.to_string())?;
+    match ShareEnvelope::fromText(&envelope).and_then(|parsed| parsed.checkKey(&delivery.scheme, SecretService::curveOfScheme(&delivery.scheme), delivery.degree + 1, &delivery.public_key).map(|_| parsed)) {
+        Ok(parsed) if parsed.index == vec![cluster.x] => (),
+        Ok(_) => return Err("the share belongs to another node".to_string()),
+        Err(err) => return Err(err.to_string()),
+    };
+    match db.find_commitments(&delivery.public_key).await {
+        Ok(None) => (),
+        Ok(Some(_)) => return Err("this node already stores a share of this key".to_string()),
+        Err(err) => return Err(err.to_string()),
+    };
+    // Deliveries carry keys dealt by `inner_deal_secret`, never derived wallets, so no key is a Taproot output key
+    let verification = if delivery.scheme == Ed25519FeldmanVSS::SCHEME {
+        SecretService::parseShare::<Ed25519Scalar>(&envelope).and_then(|share| SecretService::verifyEd25519Share(&share, &delivery.commitments, &delivery.public_key))
+    } else if delivery.scheme == BlsFeldmanVSS::SCHEME {
+        SecretService::parseShare::<BlsScalar>(&envelope).and_then(|share| SecretService::verifyBlsShare(&share, &delivery.commitments, &delivery.public_key))
+    } else {
+        SecretService::parseShare(&envelope).and_then(|share| SecretService::verifyShare(&share, &delivery.commitments, &delivery.scheme, &delivery.public_key, None))
+    };
+    if !verification.map(|verification| verification.valid_share).unwrap_or(false) {
+        return Err("the share does not lie on the committed polynomial".to_string());
+    }
+    let share: Vec<String> = ShareEnvelope::fromText(&envelope).unwrap().toShareString().split("||").map(str::to_owned).collect();
+    let response = inner_save_secret(db, &delivery.public_key, user_id, vec![share], delivery.commitments, &delivery.scheme, delivery.degree, &[]).await;
+    match response.status().is_success() {
+        true => Ok(()),
+        false => Err(format!("storing the share failed with {}", response.status())),
+    }
+}
+
+/// Generates the keys of a user another node creates together with every node and stores this node's shares and the user.
+async fn join_user_creation(router: &SessionRouter, cluster: &ClusterConfig, db: &UserRepository, db2: &Data<SecretRepository>, session_id: &str, user_id: &str, degree: u8) -> Outcome {
+    let user_id = ObjectId::from_str(user_id).map_err(|_| "user_id must be an object id".to_string())?;
+    if degree < 2 || cluster.coordinates().len() < ThresholdEcdsa::requiredHolders(degree) {
+        return Err(format!("a degree of {} does not fit a cluster of {} nodes", degree, cluster.coordinates().len()));
+    }
+    let keys = ClusterService::userKeyGeneration(router, cluster, session_id, degree).await.map_err(|err| err.to_string())?;
+    match store_user_keys(db, db2, user_id, degree, keys, &[], Vec::new()).await {
+        Ok(_) => Ok(()),
+        Err(response) => Err(format!("storing the user failed with {}", response.status())),
+    }
+}
+
+/// Does this node's part of a task the peer `from` asked for, reports it and rolls it back unless `from` decides to keep
+/// it once every node reported.
+async fn take_part(from: u8, request: ClusterRequest, router: SessionRouter, cluster: ClusterConfig, db: Data<UserRepository>, db2: Data<SecretRepository>) {
+    let mut reports = match ClusterService::answer(&router, &request.session_id, from) {
+        Ok(reports) => reports,
+        Err(err) => return log::warn!("Refused request {} of node {}: {}", request.session_id, from, err),
+    };
+    let stored = match &request.task {
+        ClusterTask::CreateUser { user_id, degree } => join_user_creation(&router, &cluster, &db, &db2, &request.session_id, user_id, *degree).await,
+        ClusterTask::StoreShare(delivery) => accept_delivery(&cluster, db2.clone(), from, delivery.clone()).await,
+    };
+    if let Err(err) = ClusterService::report(&reports, from, &stored).await {
+        log::warn!("Could not report {} to node {}: {}", request.session_id, from, err);
+    }
+    if let Err(reason) = stored {
+        return log::warn!("Failed request {} of node {}: {}", request.session_id, from, reason);
+    }
+    if let Err(reason) = ClusterService::awaitDecision(&mut reports, from).await {
+        log::warn!("Rolling back request {} of node {}: {}", request.session_id, from, reason);
+        match &request.task {
+            ClusterTask::CreateUser { user_id, .. } => rollback_user(&db, &db2, ObjectId::from_str(user_id).unwrap()).await,
+            ClusterTask::StoreShare(delivery) => if let Err(err) = db2.delete_key(&delivery.public_key).await {
+                log::error!("Could not roll back {}: {}", delivery.public_key, err);
+            },
+        }
+    }
+}
+
+/// Takes part in the work the peers ask this node for on `requests`, each request on its own, until the node transport stops.
+pub async fn serve_requests(mut requests: SessionChannel, router: SessionRouter, cluster: ClusterConfig, db: Data<UserRepository>, db2: Data<SecretRepository>) {
+    while let Ok((from, payload)) = requests.receive().await {
+        match serde_json::from_slice::<ClusterRequest>(&payload) {
+            Ok(request) => {
+                actix_web::rt::spawn(take_part(from, request, router.clone(), cluster.clone(), db.clone(), db2.clone()));
+            }
+            Err(err) => log::warn!("Dropped a request from node {}: {}", from, err),
+        }
+    }
+}
+
+/// Public identity of this node and the x-coordinates and addresses of its peers.
+#[get("/cluster/node")]
+pub async fn node_info(cluster: Data<Option<ClusterConfig>>) -> HttpResponse {
+    match cluster.get_ref() {
+        Some(cluster) => HttpResponse::Ok().json(doc!{
+            "x": cluster.x as i32,
+            "signing_key": hex::encode(cluster.signingKey().to_bytes()),
+            "encryption_key": hex::encode(cluster.encryptionKey()),
+            "peers": cluster.peers.iter().map(|peer| doc!{ "x": peer.x as i32, "address": peer.address.clone() }).collect::<Vec<_>>()
+        }),
+        None => HttpResponse::NotFound().body("This node does not run in cluster mode"),
+    }
+}
\ No newline at end of file
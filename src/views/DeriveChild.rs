@@ -0,0 +1,63 @@
+use crate::{database::{SecretRepository::SecretRepository, UserRepository::UserRepository}, models::{Holder::ShareCustody, User::Wallet}, services::{SecretService::SecretService, ServiceError::ServiceError}, util::{bip32::HARDENED, cluster::ClusterConfig, feldman::FeldmanVSS}, views::{Cluster::standalone_only, HolderEnvelope::server_shares, SaveSecret::inner_save_secret}};
+
+use actix_web::{post, web::Data, HttpResponse, HttpRequest};
+
+/// Derives the receiving address at `address_index` `index` next to the wallet `public_key`, e.g. `m/44'/60'/0'/0/index`
+/// beside a dealt Ethereum wallet or `m/44/60/0/0/index` below a jointly generated seed, from the change level `xpub`
+/// the wallet records. Every holder's share is moved by public tweaks, so no key is ever reconstructed. The address is
+/// added to the owner's wallets.
+#[post("/derive_child/{public_key}/{index}")]
+pub async fn derive_child(cluster: Data<Option<ClusterConfig>>, db: Data<UserRepository>, db2: Data<SecretRepository>, req: HttpRequest) -> HttpResponse {
+    if let Err(response) = standalone_only(&cluster) {
+        return response;
+    }
+    let pub_key = req.match_info().get("public_key").unwrap();
+    let index: u32 = match req.match_info().get("index").unwrap().parse() {
+        Ok(index) if index < HARDENED => index,
+        _ => return HttpResponse::BadRequest().body("index must be a non-hardened child index, lower than 2^31"),
+    };
+    let secrets = match db2.find_secrets(pub_key).await {
+        Ok(secrets) if !secrets.is_empty() => secrets,
+        Ok(_) => return HttpResponse::NotFound().body("No partial secrets stored for this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let commitments = match db2.find_commitments(pub_key).await {
+        Ok(Some(commitments)) if commitments.scheme == FeldmanVSS::SCHEME => commitments,
+        Ok(Some(_)) => return HttpResponse::BadRequest().body("Only secp256k1 wallet keys can derive BIP-32 children"),
+        Ok(None) => return HttpResponse::NotFound().body("No commitments stored for this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let user = match db.find_user(secrets[0].user_id).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return HttpResponse::NotFound().body("No user owns this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let (derivation_path, xpub) = match user.wallets.iter().find(|wallet| wallet.pub_key == pub_key) {
+        Some(Wallet { derivation_path: Some(path), xpub: Some(xpub), .. }) => (path.clone(), xpub.clone()),
+        Some(_) => return HttpResponse::BadRequest().body("Wallet has no derivation path and change level xpub"),
+        None => return HttpResponse::NotFound().body("No wallet with this public key"),
+    };
+    let shares = match server_shares(&secrets, &commitments.scheme) {
+        Ok(shares) => shares,
+        Err(response) => return response,
+    };
+
+    let degree = secrets[0].secret_degree;
+    let derived = match SecretService::deriveChildWallet(shares, &commitments.commitments, pub_key, &derivation_path, &xpub, index) {
+        Ok(derived) => derived,
+        Err(err @ ServiceError::Derivation(_)) => return HttpResponse::BadRequest().body(err.to_string()),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    if user.wallets.iter().any(|wallet| wallet.pub_key == derived.key.public_key) {
+        return HttpResponse::Conflict().body("The user already has this child wallet");
+    }
+    let wallet = Wallet::derived(derived.key.public_key.clone(), degree, &derived.derivation_path, &derived.chain_code, &derived.xpub);
+    let response = inner_save_secret(db2, &derived.key.public_key, secrets[0].user_id, derived.key.shares, derived.key.commitments, FeldmanVSS::SCHEME, degree, &ShareCustody::same_holders_as(&secrets)).await;
+    if !response.status().is_success() {
+        return response;
+    }
+    match db.add_wallet(secrets[0].user_id, wallet.copy()).await {
+        Ok(_) => HttpResponse::Ok().json(wallet),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+use std::str::FromStr;
+
+use crate::{database::{SecretRepository::SecretRepository, UserRepository::UserRepository}, models::{Holder::ShareCustody, User::Wallet}, services::SecretService::SecretService, util::{cluster::ClusterConfig, feldman::FeldmanVSS}, views::{Cluster::standalone_only, HolderEnvelope::server_shares, SaveSecret::inner_save_secret}};
+
+use actix_web::{post, web::{Data, Json}, HttpResponse, HttpRequest};
+use mongodb::bson::oid::ObjectId;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct DeriveWalletBody {
+    /// BIP-32 path below the user's seed, e.g. `m/44'/60'/1'/0/0` for a second Ethereum account, or
+    /// `m/44/60/1/0/0` below a jointly generated seed.
+    pub path: String,
+    /// Combine the shares of a BIP-39 seed this server dealt to derive the wallet, which hardened steps of `path` need.
+    #[serde(default)]
+    pub reconstruct_seed: bool,
+}
+
+/// Adds a wallet derived from the user's seed along `path`. Below a jointly generated seed, `path` must be non-hardened
+/// and every share of the seed is moved to a share of the wallet key by public tweaks. A dealt BIP-39 seed can only be
+/// derived from by combining its shares on this server, which the body has to ask for with `reconstruct_seed`.
+#[post("/derive_wallet/{user_id}")]
+pub async fn derive_wallet(cluster: Data<Option<ClusterConfig>>, db: Data<UserRepository>, db2: Data<SecretRepository>, req: HttpRequest, body: Json<DeriveWalletBody>) -> HttpResponse {
+    if let Err(response) = standalone_only(&cluster) {
+        return response;
+    }
+    let user_id = match ObjectId::from_str(req.match_info().get("user_id").unwrap()) {
+        Ok(user_id) => user_id,
+        Err(_) => return HttpResponse::BadRequest().body("user_id must be an object id"),
+    };
+    let user = match db.find_user(user_id).await {
+        Ok(Some(user)) if !user.seed.is_empty() => user,
+        Ok(Some(_)) => return HttpResponse::BadRequest().body("User has no seed to derive wallets from"),
+        Ok(None) => return HttpResponse::NotFound().body("No user with this id"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let secrets = match db2.find_secrets(&user.seed).await {
+        Ok(secrets) if !secrets.is_empty() => secrets,
+        Ok(_) => return HttpResponse::NotFound().body("No partial secrets stored for the user's seed"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let shares = match server_shares(&secrets, FeldmanVSS::SCHEME) {
+        Ok(shares) => shares,
+        Err(response) => return response,
+    };
+
+    let degree = secrets[0].secret_degree;
+    let derivation = match (&user.seed_xpub, body.reconstruct_seed) {
+        (Some(_), _) => match db2.find_commitments(&user.seed).await {
+            Ok(Some(commitments)) => SecretService::deriveWallet(shares, &commitments.commitments, &body.path),
+            Ok(None) => return HttpResponse::NotFound().body("No commitments stored for the user's seed"),
+            Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+        },
+        (None, true) => SecretService::deriveDealtWallet(degree, shares, &body.path),
+        (None, false) => return HttpResponse::Conflict().body("The user's seed was dealt by this server, deriving from it combines its shares and needs reconstruct_seed"),
+    };
+    let derived = match derivation {
+        Ok(derived) => derived,
+        Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+    };
+    if user.wallets.iter().any(|wallet| wallet.derivation_path.as_deref() == Some(derived.derivation_path.as_str())) {
+        return HttpResponse::Conflict().body("The user already has a wallet at this path");
+    }
+    let wallet = Wallet::derived(derived.key.public_key.clone(), degree, &derived.derivation_path, &derived.chain_code, &derived.xpub);
+    let response = inner_save_secret(db2, &derived.key.public_key, user_id, derived.key.shares, derived.key.commitments, FeldmanVSS::SCHEME, degree, &ShareCustody::same_holders_as(&secrets)).await;
+    if !response.status().is_success() {
+        return response;
+    }
+    match db.add_wallet(user_id, wallet.copy()).await {
+        Ok(_) => HttpResponse::Ok().json(wallet),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
\ No newline at end of file
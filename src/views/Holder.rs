@@ -0,0 +1,156 @@
+use std::str::FromStr;
+
+use crate::{database::{HolderRepository::HolderRepository, SecretRepository::SecretRepository}, models::Holder::{HeldShare, Holder, HolderStatus, ShareCustody}, services::SecretService::SecretService, util::hpke::Hpke};
+
+use actix_web::{get, post, web::{Data, Json}, HttpResponse, HttpRequest};
+use ed25519_dalek::VerifyingKey;
+use mongodb::bson::oid::ObjectId;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct CreateHolderBody {
+    pub name: String,
+    #[serde(default)]
+    pub contact: String,
+    /// Hex X25519 public key.
+    pub encryption_key: String,
+    /// Hex Ed25519 public key.
+    pub signing_key: String,
+}
+
+#[derive(Deserialize)]
+pub struct HolderStatusBody {
+    pub status: HolderStatus,
+}
+
+fn parseHolderId(req: &HttpRequest) -> Option<ObjectId> {
+    ObjectId::from_str(req.match_info().get("holder_id").unwrap()).ok()
+}
+
+/// Looks up the registered holders with the ids `holder_ids`, in that order. Every one must exist, be active and appear once.
+pub async fn resolve_holders(db: &HolderRepository, holder_ids: &[String]) -> Result<Vec<Holder>, HttpResponse> {
+    let mut holders: Vec<Holder> = Vec::new();
+    for holder_id in holder_ids {
+        let id = ObjectId::from_str(holder_id).map_err(|_| HttpResponse::BadRequest().body("Holder ids must be object ids"))?;
+        if holders.iter().any(|holder| holder.id == Some(id)) {
+            return Err(HttpResponse::BadRequest().body(format!("Holder {} is listed twice", holder_id)));
+        }
+        match db.find_holder(id).await {
+            Ok(Some(holder)) if holder.status == HolderStatus::Active => holders.push(holder),
+            Ok(Some(_)) => return Err(HttpResponse::Conflict().body(format!("Holder {} is not active", holder_id))),
+            Ok(None) => return Err(HttpResponse::NotFound().body(format!("No holder with the id {}", holder_id))),
+            Err(err) => return Err(HttpResponse::InternalServerError().body(err.to_string())),
+        }
+    }
+    Ok(holders)
+}
+
+/// Picks the x-coordinates and custody of the `holders_count` shares of a new key: the registered holders `holder_ids`
+/// at their own x-coordinates, sealed to their keys if `seal` is set, or else random x-coordinates, sealed to
+/// `encryption_keys` if there are any.
+pub async fn assign_custody(db: &HolderRepository, holders_count: u8, holder_ids: &[String], encryption_keys: &[String], seal: bool) -> Result<(Vec<u8>, Vec<ShareCustody>), HttpResponse> {
+    if !encryption_keys.is_empty() && encryption_keys.len() != holders_count as usize {
+        return Err(HttpResponse::BadRequest().body("One encryption key is needed per holder"));
+    }
+    if encryption_keys.iter().any(|key| Hpke::parsePublicKey(key).is_none()) {
+        return Err(HttpResponse::BadRequest().body("Encryption keys must be 32 hex encoded bytes"));
+    }
+    let holders = resolve_holders(db, holder_ids).await?;
+    if holders.is_empty() {
+        let registered: Vec<u8> = match db.find_holders().await {
+            Ok(registered) => registered.iter().map(|holder| holder.x).collect(),
+            Err(err) => return Err(HttpResponse::InternalServerError().body(err.to_string())),
+        };
+        // The i-th key goes to the holder at the i-th lowest x-coordinate, as `register_encryption_keys` matches them
+        let mut xs = SecretService::randomCoordinates(holders_count, &registered).map_err(|err| HttpResponse::BadRequest().body(err.to_string()))?;
+        xs.sort();
+        return Ok((xs, ShareCustody::sealed_to(encryption_keys)));
+    }
+    if holders.len() != holders_count as usize {
+        return Err(HttpResponse::BadRequest().body("One registered holder is needed per holder"));
+    }
+    if !encryption_keys.is_empty() {
+        return Err(HttpResponse::BadRequest().body("Shares of registered holders are sealed to the holders' own encryption keys"));
+    }
+    Ok((holders.iter().map(|holder| holder.x).collect(), ShareCustody::assigned_to(&holders, seal)))
+}
+
+/// Registers a holder and hands it the lowest free x-coordinate, which every share it ever receives will sit at. The
+/// x-coordinate of a retired holder is never handed out again, so a share at it always tells who had custody of it.
+#[post("/holders")]
+pub async fn create_holder(db: Data<HolderRepository>, body: Json<CreateHolderBody>) -> HttpResponse {
+    if Hpke::parsePublicKey(&body.encryption_key).is_none() {
+        return HttpResponse::BadRequest().body("encryption_key must be 32 hex encoded bytes");
+    }
+    let signing_key: Option<[u8; 32]> = hex::decode(&body.signing_key).ok().and_then(|bytes| bytes.try_into().ok());
+    if signing_key.and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok()).is_none() {
+        return HttpResponse::BadRequest().body("signing_key must be a hex encoded Ed25519 public key");
+    }
+    let holder = Holder {
+        id: None,
+        name: body.name.clone(),
+        contact: body.contact.clone(),
+        encryption_key: body.encryption_key.to_lowercase(),
+        signing_key: body.signing_key.to_lowercase(),
+        x: 0,
+        status: HolderStatus::Active
+    };
+    match db.create_holder(holder).await {
+        Ok(Some(holder)) => HttpResponse::Ok().json(holder),
+        Ok(None) => HttpResponse::Conflict().body("Every x-coordinate is already assigned to a holder"),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+#[get("/holders")]
+pub async fn list_holders(db: Data<HolderRepository>) -> HttpResponse {
+    match db.find_holders().await {
+        Ok(holders) => HttpResponse::Ok().json(holders),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+#[get("/holders/{holder_id}")]
+pub async fn get_holder(db: Data<HolderRepository>, req: HttpRequest) -> HttpResponse {
+    let holder_id = match parseHolderId(&req) {
+        Some(holder_id) => holder_id,
+        None => return HttpResponse::BadRequest().body("holder_id must be an object id"),
+    };
+    match db.find_holder(holder_id).await {
+        Ok(Some(holder)) => HttpResponse::Ok().json(holder),
+        Ok(None) => HttpResponse::NotFound().body("No holder with this id"),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+#[post("/holders/{holder_id}/status")]
+pub async fn update_holder_status(db: Data<HolderRepository>, req: HttpRequest, body: Json<HolderStatusBody>) -> HttpResponse {
+    let holder_id = match parseHolderId(&req) {
+        Some(holder_id) => holder_id,
+        None => return HttpResponse::BadRequest().body("holder_id must be an object id"),
+    };
+    match db.update_status(holder_id, body.status).await {
+        Ok(update) if update.matched_count == 0 => HttpResponse::NotFound().body("No holder with this id"),
+        Ok(update) => HttpResponse::Ok().json(update),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+/// Lists every share the holder has been given custody of, revoked generations included. Only where each share sits
+/// is shown, never the share or its ciphertext.
+#[get("/holders/{holder_id}/shares")]
+pub async fn holder_shares(db: Data<SecretRepository>, holder_db: Data<HolderRepository>, req: HttpRequest) -> HttpResponse {
+    let holder_id = match parseHolderId(&req) {
+        Some(holder_id) => holder_id,
+        None => return HttpResponse::BadRequest().body("holder_id must be an object id"),
+    };
+    let holder = match holder_db.find_holder(holder_id).await {
+        Ok(Some(holder)) => holder,
+        Ok(None) => return HttpResponse::NotFound().body("No holder with this id"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    match db.find_holder_secrets(holder_id).await {
+        Ok(secrets) => HttpResponse::Ok().json(secrets.iter().map(|secret| HeldShare::of(secret, holder.x)).collect::<Vec<HeldShare>>()),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,101 @@
+use std::str::FromStr;
+
+use crate::{database::{SecretRepository::SecretRepository, UserRepository::UserRepository}, models::PartialSecret::PartialSecret, services::SecretService::SecretService, util::hpke::Hpke};
+
+use actix_web::{get, post, web::{Data, Json}, HttpResponse, HttpRequest};
+use mongodb::bson::oid::ObjectId;
+use serde::Deserialize;
+
+/// The shares of one key of `scheme` for the server to compute with, or the response to give when it can not: 409 if
+/// they are sealed to their holders, 500 if a stored share does not parse or its envelope belongs to another key.
+pub fn server_shares<F: FromStr>(secrets: &[PartialSecret], scheme: &str) -> Result<Vec<Vec<F>>, HttpResponse> {
+    if secrets.iter().any(|secret| secret.encrypted_to.is_some()) {
+        return Err(HttpResponse::Conflict().body("The shares of this key are sealed to their holders, the server cannot use them"));
+    }
+    secrets.iter()
+        .map(|secret| SecretService::parseShareOf(&secret.partial_secret, scheme, secret.secret_degree + 1, &secret.public_key))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| HttpResponse::InternalServerError().body(format!("Stored partial secrets are not valid shares: {}", err)))
+}
+
+#[derive(Deserialize)]
+pub struct HolderKey {
+    /// x-coordinate of the holder's shares.
+    pub x: u8,
+    /// Hex X25519 key of the holder.
+    pub encryption_key: String,
+}
+
+#[derive(Deserialize)]
+pub struct EncryptionKeysBody {
+    /// One key per holder, every share of the user is sealed to the key of the holder at its x-coordinate.
+    pub encryption_keys: Vec<HolderKey>,
+}
+
+/// Registers the holders' encryption keys of a user whose shares are stored in plaintext, and seals every current
+/// share to its holder. Shares sealed by an earlier, interrupted call are left as they are, so the call can be retried.
+/// Later wallets of the user are sealed when they are dealt.
+#[post("/encryption_keys/{user_id}")]
+pub async fn register_encryption_keys(db: Data<UserRepository>, db2: Data<SecretRepository>, req: HttpRequest, body: Json<EncryptionKeysBody>) -> HttpResponse {
+    let user_id = match ObjectId::from_str(req.match_info().get("user_id").unwrap()) {
+        Ok(user_id) => user_id,
+        Err(_) => return HttpResponse::BadRequest().body("user_id must be an object id"),
+    };
+    match db.find_user(user_id).await {
+        Ok(Some(user)) if user.encryption_keys.is_empty() => user,
+        Ok(Some(_)) => return HttpResponse::Conflict().body("The user's holders already registered their encryption keys"),
+        Ok(None) => return HttpResponse::NotFound().body("No user with this id"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    if body.encryption_keys.iter().any(|key| Hpke::parsePublicKey(&key.encryption_key).is_none()) {
+        return HttpResponse::BadRequest().body("Encryption keys must be 32 hex encoded bytes");
+    }
+    let mut keys: Vec<&HolderKey> = body.encryption_keys.iter().collect();
+    keys.sort_by_key(|key| key.x);
+    if keys.windows(2).any(|pair| pair[0].x == pair[1].x) {
+        return HttpResponse::BadRequest().body("Every holder can only register one encryption key");
+    }
+    let secrets = match db2.find_user_secrets(user_id).await {
+        Ok(secrets) => secrets,
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+
+    let mut sealed = Vec::new();
+    for secret in secrets.iter().filter(|secret| secret.encrypted_to.is_none()) {
+        let x = match SecretService::shareCoordinate(&secret.partial_secret) {
+            Some(x) => x,
+            None => return HttpResponse::InternalServerError().body("Stored partial secrets are not valid shares"),
+        };
+        let key = match keys.iter().find(|key| key.x == x) {
+            Some(key) => &key.encryption_key,
+            None => return HttpResponse::BadRequest().body(format!("No encryption key for the holder at x = {} of key {}", x, secret.public_key)),
+        };
+        match SecretService::sealShare(&secret.partial_secret, key, &secret.public_key) {
+            Ok(ciphertext) => sealed.push((secret.id.unwrap(), ciphertext, key)),
+            Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+        }
+    }
+    for (id, ciphertext, key) in sealed {
+        if let Err(err) = db2.seal_secret(id, ciphertext, key).await {
+            return HttpResponse::InternalServerError().body(err.to_string());
+        }
+    }
+    match db.set_encryption_keys(user_id, keys.iter().map(|key| key.encryption_key.clone()).collect()).await {
+        Ok(update) => HttpResponse::Ok().json(update),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+/// Returns the share of `public_key` sealed to the holder's `holder_key`. The holder opens `partial_secret` client-side
+/// with HPKE (DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, ChaCha20-Poly1305), the share info string, and the wallet public
+/// key as associated data, which yields the bech32m share envelope.
+#[get("/envelope/{public_key}/{holder_key}")]
+pub async fn fetch_envelope(db: Data<SecretRepository>, req: HttpRequest) -> HttpResponse {
+    let pub_key = req.match_info().get("public_key").unwrap();
+    let holder_key = req.match_info().get("holder_key").unwrap();
+    match db.find_sealed_secret(pub_key, holder_key).await {
+        Ok(Some(secret)) => HttpResponse::Ok().json(secret),
+        Ok(None) => HttpResponse::NotFound().body("No share of this public key is sealed to this holder key"),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
\ No newline at end of file
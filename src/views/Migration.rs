@@ -0,0 +1,17 @@
+use crate::{database::SecretRepository::SecretRepository, services::MigrationService::MigrationService};
+
+use actix_web::{post, web::Data, HttpResponse};
+use mongodb::bson::doc;
+
+/// Rewrites the shares still stored in the legacy `x||y` form as share envelopes. Run it once after upgrading; running it
+/// again only picks up shares written by older nodes meanwhile.
+#[post("/migrations/share_envelopes")]
+pub async fn migrate_share_envelopes(db: Data<SecretRepository>) -> HttpResponse {
+    match MigrationService::migrateShareEnvelopes(&db).await {
+        Ok(migrated) => {
+            log::info!("Migrated {} shares to envelopes", migrated);
+            HttpResponse::Ok().json(doc!{ "migrated": migrated as i64 })
+        },
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
\ No newline at end of file
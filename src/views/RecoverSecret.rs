@@ -0,0 +1,31 @@
+use crate::{services::SecretService::SecretService, util::cluster::ClusterConfig, views::Cluster::standalone_only};
+
+use actix_web::{post, web::{Data, Json}, HttpResponse, HttpRequest};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct RecoverSecretBody {
+    /// Shares as handed out to the holders, share envelopes or in the legacy `x||y` form. Some of them may be wrong.
+    pub shares: Vec<String>,
+}
+
+/// Reconstructs a secret of `degree` from submitted shares with error correction. Responds with the public key of the
+/// recovered secret and the x-coordinates of the shares that had to be corrected.
+#[post("/recover/{degree}")]
+pub async fn recover_secret(cluster: Data<Option<ClusterConfig>>, req: HttpRequest, body: Json<RecoverSecretBody>) -> HttpResponse {
+    if let Err(response) = standalone_only(&cluster) {
+        return response;
+    }
+    let degree: u8 = match req.match_info().get("degree").unwrap().parse() {
+        Ok(degree) if degree >= 2 => degree,
+        _ => return HttpResponse::BadRequest().body("degree must be a number greater than or equal to 2"),
+    };
+    let shares = match body.shares.iter().map(|share| SecretService::parseShare(share)).collect::<Option<Vec<_>>>() {
+        Some(shares) => shares,
+        None => return HttpResponse::BadRequest().body("Shares must be share envelopes or in the x||y form"),
+    };
+    match SecretService::recoverSecret(degree, shares) {
+        Ok(recovered) => HttpResponse::Ok().json(recovered),
+        Err(err) => HttpResponse::UnprocessableEntity().body(err.to_string()),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,99 @@
+use crate::{database::SecretRepository::SecretRepository, models::{PartialSecret::PartialSecret, SecretCommitment::SecretCommitment}, services::SecretService::SecretService, util::{cluster::ClusterConfig, feldman::FeldmanVSS, pedersen::PedersenVSS}, views::{Cluster::standalone_only, HolderEnvelope::server_shares}};
+
+use actix_web::{post, web::{Data}, HttpResponse, HttpRequest};
+use mongodb::bson::doc;
+
+/// Proactively refreshes every share stored for `public_key`. The secret, and so the wallet key, stays the same while the
+/// previous epoch's shares stop combining with the new ones. The refreshed shares are staged next to the current ones and
+/// take over in a single switch of the commitments, so an interrupted refresh leaves the previous epoch in place. Every
+/// refreshed share is sealed to the same holder key as the share it replaces. Only secp256k1 keys shared with Feldman or
+/// Pedersen commitments can be refreshed.
+#[post("/refresh/{public_key}")]
+pub async fn refresh_secret(cluster: Data<Option<ClusterConfig>>, db: Data<SecretRepository>, req: HttpRequest) -> HttpResponse {
+    if let Err(response) = standalone_only(&cluster) {
+        return response;
+    }
+    let pub_key = req.match_info().get("public_key").unwrap();
+    let secrets = match db.find_secrets(pub_key).await {
+        Ok(secrets) if !secrets.is_empty() => secrets,
+        Ok(_) => return HttpResponse::NotFound().body("No partial secrets stored for this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let commitments = match db.find_commitments(pub_key).await {
+        Ok(Some(commitments)) if [FeldmanVSS::SCHEME, PedersenVSS::SCHEME].contains(&commitments.scheme.as_str()) => commitments,
+        Ok(Some(commitments)) => return HttpResponse::BadRequest().body(format!("Keys shared with {} can not be refreshed", commitments.scheme)),
+        Ok(None) => return HttpResponse::NotFound().body("No commitments stored for this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let shares = match server_shares(&secrets, &commitments.scheme) {
+        Ok(shares) => shares,
+        Err(response) => return response,
+    };
+
+    let degree = secrets[0].secret_degree;
+    let epoch = commitments.epoch + 1;
+    let (refreshed, new_commitments) = match SecretService::refreshShares(degree, shares, &commitments.commitments) {
+        Ok(result) => result,
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let mut new_secrets: Vec<PartialSecret> = Vec::new();
+    for (secret, share) in secrets.iter().zip(refreshed.iter()) {
+        let envelope = match SecretService::encodeShare(share, &commitments.scheme, degree, pub_key) {
+            Ok(envelope) => envelope,
+            Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+        };
+        let partial_secret = match &secret.encrypted_to {
+            Some(key) => match SecretService::sealShare(&envelope, key, pub_key) {
+                Ok(ciphertext) => ciphertext,
+                Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+            },
+            None => envelope,
+        };
+        new_secrets.push(PartialSecret {
+            id: None,
+            user_id: secret.user_id,
+            public_key: pub_key.to_string(),
+            partial_secret,
+            secret_degree: degree,
+            epoch,
+            generation: commitments.generation,
+            revoked: false,
+            curve: secret.curve.clone(),
+            encrypted_to: secret.encrypted_to.clone(),
+            holder_id: secret.holder_id
+        });
+    }
+    if let Err(response) = switch_shares(&db, &commitments, new_secrets, new_commitments, degree, commitments.generation, epoch).await {
+        return response;
+    }
+    // The previous epoch is already inert, deleting it only keeps its shares from outliving the refresh.
+    if let Err(err) = db.delete_secrets(secrets.iter().filter_map(|secret| secret.id).collect()).await {
+        log::warn!("Refreshed {} but could not delete the previous epoch: {}", pub_key, err);
+    }
+    HttpResponse::Ok().json(doc!{ "public_key": pub_key, "epoch": epoch })
+}
+
+/// Stages `new_secrets`, shares of `generation` and `epoch` verified by `new_commitments`, and switches the key behind
+/// `commitments` over to them in one update. Fails with 409 if the key moved on since `commitments` were read or another
+/// refresh or resharing is under way; nothing changes for the current shares until the switch.
+pub async fn switch_shares(db: &SecretRepository, commitments: &SecretCommitment, new_secrets: Vec<PartialSecret>, new_commitments: Vec<String>, degree: u8, generation: u32, epoch: u32) -> Result<(), HttpResponse> {
+    let pub_key = commitments.public_key.as_str();
+    let token = match db.begin_update(pub_key, commitments.generation, commitments.epoch).await {
+        Ok(Some(token)) => token,
+        Ok(None) => return Err(HttpResponse::Conflict().body("The key changed or another refresh or resharing of it is in progress")),
+        Err(err) => return Err(HttpResponse::InternalServerError().body(err.to_string())),
+    };
+    let staged = match db.discard_staged_secrets(pub_key, commitments.generation, commitments.epoch).await {
+        Ok(_) => db.save_muliple_secret(new_secrets).await.map(|_| ()),
+        Err(err) => Err(err),
+    };
+    if let Err(err) = staged {
+        let _ = db.abandon_update(pub_key, token).await;
+        return Err(HttpResponse::InternalServerError().body(err.to_string()));
+    }
+    match db.activate_commitments(pub_key, token, new_commitments, degree, generation, epoch).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(HttpResponse::Conflict().body("The update of this key was taken over by another refresh or resharing")),
+        Err(err) => Err(HttpResponse::InternalServerError().body(err.to_string())),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+use crate::{database::{HolderRepository::HolderRepository, SecretRepository::SecretRepository}, models::PartialSecret::PartialSecret, services::SecretService::SecretService, util::{cluster::ClusterConfig, feldman::FeldmanVSS, field::{Field, FieldElement}, pedersen::PedersenVSS}, views::{Cluster::standalone_only, HolderEnvelope::server_shares}};
+
+use actix_web::{post, web::{Data}, HttpResponse, HttpRequest};
+use mongodb::bson::doc;
+
+/// Restores the share of a holder that lost its `PartialSecret`. The remaining holders of `public_key` compute the share at
+/// x-coordinate `x` between themselves and the result is stored as a replacement, in the same epoch and generation.
+/// If the key is dealt to registered holders, the replacement is assigned to the holder at `x`, the only holder ever
+/// given `x`, whether it still serves or not.
+#[post("/repair/{public_key}/{x}")]
+pub async fn repair_secret(cluster: Data<Option<ClusterConfig>>, db: Data<SecretRepository>, holder_db: Data<HolderRepository>, req: HttpRequest) -> HttpResponse {
+    if let Err(response) = standalone_only(&cluster) {
+        return response;
+    }
+    let pub_key = req.match_info().get("public_key").unwrap();
+    let x: u8 = match req.match_info().get("x").unwrap().parse() {
+        Ok(x) if x >= 1 => x,
+        _ => return HttpResponse::BadRequest().body("x must be a number between 1 and 255"),
+    };
+    let lost_x = FieldElement::from_u64(x as u64);
+    let secrets = match db.find_secrets(pub_key).await {
+        Ok(secrets) if !secrets.is_empty() => secrets,
+        Ok(_) => return HttpResponse::NotFound().body("No partial secrets stored for this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let commitments = match db.find_commitments(pub_key).await {
+        Ok(Some(commitments)) if [FeldmanVSS::SCHEME, PedersenVSS::SCHEME].contains(&commitments.scheme.as_str()) => commitments,
+        Ok(Some(commitments)) => return HttpResponse::BadRequest().body(format!("Keys shared with {} can not be repaired", commitments.scheme)),
+        Ok(None) => return HttpResponse::NotFound().body("No commitments stored for this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let shares = match server_shares(&secrets, &commitments.scheme) {
+        Ok(shares) => shares,
+        Err(response) => return response,
+    };
+    if shares.iter().any(|share| share[0] == lost_x) {
+        return HttpResponse::Conflict().body("A share with this x-coordinate is still stored");
+    }
+
+    let repaired = match SecretService::repairShare(secrets[0].secret_degree, shares, &commitments.commitments, lost_x) {
+        Ok(share) => share,
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let holder_id = match secrets.iter().any(|secret| secret.holder_id.is_some()) {
+        true => match holder_db.find_holder_by_x(x).await {
+            Ok(holder) => holder.and_then(|holder| holder.id),
+            Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+        },
+        false => None,
+    };
+    let envelope = match SecretService::encodeShare(&repaired, &commitments.scheme, secrets[0].secret_degree, pub_key) {
+        Ok(envelope) => envelope,
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let result = db.save_secret(PartialSecret {
+        id: None,
+        user_id: secrets[0].user_id,
+        public_key: pub_key.to_string(),
+        partial_secret: envelope,
+        secret_degree: secrets[0].secret_degree,
+        epoch: secrets[0].epoch,
+        generation: secrets[0].generation,
+        revoked: false,
+        curve: secrets[0].curve.clone(),
+        encrypted_to: None,
+        holder_id
+    }).await;
+    match result {
+        Ok(insertion) => HttpResponse::Ok().json(doc!{ "public_key": pub_key, "x": x.to_string(), "id": insertion.inserted_id }),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,113 @@
+use crate::{database::{HolderRepository::HolderRepository, SecretRepository::SecretRepository, UserRepository::UserRepository}, models::PartialSecret::PartialSecret, services::SecretService::SecretService, util::{cluster::ClusterConfig, ecdsa::ThresholdEcdsa, feldman::FeldmanVSS, pedersen::PedersenVSS}, views::{Cluster::standalone_only, Holder::assign_custody, HolderEnvelope::server_shares, RefreshSecret::switch_shares}};
+
+use actix_web::{post, web::{Data, Json}, HttpResponse, HttpRequest};
+use mongodb::bson::doc;
+use serde::Deserialize;
+
+#[derive(Deserialize, Default)]
+pub struct ReshareSecretBody {
+    /// Ids of registered holders to move the secret to, one per new holder.
+    #[serde(default)]
+    pub holders: Vec<String>,
+    /// Seal the new shares to the registered `holders`' encryption keys.
+    #[serde(default)]
+    pub seal: bool,
+}
+
+/// Moves the secret behind `public_key` to `new_holders_count` new holders with threshold `new_degree + 1`. The new
+/// generation is staged and takes over in a single switch of the commitments, after which the old holders' shares are
+/// revoked; the public key does not change. The body optionally names registered holders for the new generation,
+/// otherwise the new shares sit at random x-coordinates.
+#[post("/reshare/{public_key}/{new_degree}/{new_holders_count}")]
+pub async fn reshare_secret(cluster: Data<Option<ClusterConfig>>, db: Data<SecretRepository>, users: Data<UserRepository>, holder_db: Data<HolderRepository>, req: HttpRequest, body: Option<Json<ReshareSecretBody>>) -> HttpResponse {
+    if let Err(response) = standalone_only(&cluster) {
+        return response;
+    }
+    let pub_key = req.match_info().get("public_key").unwrap();
+    let new_degree: u8 = match req.match_info().get("new_degree").unwrap().parse() {
+        Ok(new_degree) => new_degree,
+        Err(_) => return HttpResponse::BadRequest().body("new_degree must be a number between 2 and 254"),
+    };
+    let new_holders_count: u8 = match req.match_info().get("new_holders_count").unwrap().parse() {
+        Ok(new_holders_count) => new_holders_count,
+        Err(_) => return HttpResponse::BadRequest().body("new_holders_count must be a number between 3 and 255"),
+    };
+    if new_degree < 2 || new_holders_count <= new_degree {
+        return HttpResponse::BadRequest().body("Holders count must be greater than the degree, and the degree at least 2");
+    }
+    let body = body.map(|body| body.into_inner()).unwrap_or_default();
+    let (new_xs, custody) = match assign_custody(&holder_db, new_holders_count, &body.holders, &[], body.seal).await {
+        Ok(assignment) => assignment,
+        Err(response) => return response,
+    };
+    let secrets = match db.find_secrets(pub_key).await {
+        Ok(secrets) if !secrets.is_empty() => secrets,
+        Ok(_) => return HttpResponse::NotFound().body("No partial secrets stored for this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let commitments = match db.find_commitments(pub_key).await {
+        Ok(Some(commitments)) if [FeldmanVSS::SCHEME, PedersenVSS::SCHEME].contains(&commitments.scheme.as_str()) => commitments,
+        Ok(Some(commitments)) => return HttpResponse::BadRequest().body(format!("Keys shared with {} can not be reshared", commitments.scheme)),
+        Ok(None) => return HttpResponse::NotFound().body("No commitments stored for this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let wallet = match users.find_wallet(pub_key).await {
+        Ok(wallet) => wallet,
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let derivation_path = wallet.as_ref().and_then(|wallet| wallet.derivation_path.as_deref());
+    if SecretService::signsWithEcdsa(&commitments.scheme, derivation_path) && (new_holders_count as usize) < ThresholdEcdsa::requiredHolders(new_degree) {
+        return HttpResponse::BadRequest().body(format!("ECDSA wallet keys need an honest majority: at least 2 * degree + 1 = {} holders", ThresholdEcdsa::requiredHolders(new_degree)));
+    }
+    let shares = match server_shares(&secrets, &commitments.scheme) {
+        Ok(shares) if shares.iter().all(|share| share.len() == 2) => shares,
+        Ok(_) => return HttpResponse::BadRequest().body("Only x||y shares with Feldman commitments can be reshared"),
+        Err(response) => return response,
+    };
+
+    let degree = secrets[0].secret_degree;
+    let generation = commitments.generation + 1;
+    let (new_shares, new_commitments) = match SecretService::reshareSecret(degree, shares, &commitments.commitments, new_degree, &new_xs) {
+        Ok(result) => result,
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let mut new_secrets: Vec<PartialSecret> = Vec::new();
+    for (i, share) in new_shares.iter().enumerate() {
+        let envelope = match SecretService::encodeShare(share, &commitments.scheme, new_degree, pub_key) {
+            Ok(envelope) => envelope,
+            Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+        };
+        let custody = custody.get(i).cloned().unwrap_or_default();
+        let partial_secret = match &custody.encryption_key {
+            Some(key) => match SecretService::sealShare(&envelope, key, pub_key) {
+                Ok(ciphertext) => ciphertext,
+                Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+            },
+            None => envelope,
+        };
+        new_secrets.push(PartialSecret {
+            id: None,
+            user_id: secrets[0].user_id,
+            public_key: pub_key.to_string(),
+            partial_secret,
+            secret_degree: new_degree,
+            epoch: 0,
+            generation,
+            revoked: false,
+            curve: secrets[0].curve.clone(),
+            encrypted_to: custody.encryption_key,
+            holder_id: custody.holder_id
+        });
+    }
+    if let Err(response) = switch_shares(&db, &commitments, new_secrets, new_commitments, new_degree, generation, 0).await {
+        return response;
+    }
+    // The previous generation is inert from the switch on, revoking it and the wallet's copy of the degree follow it.
+    if let Err(err) = db.revoke_secrets(secrets.iter().filter_map(|secret| secret.id).collect()).await {
+        log::warn!("Reshared {} but could not revoke the previous generation: {}", pub_key, err);
+    }
+    if let Err(err) = users.update_wallet_degree(pub_key, new_degree).await {
+        log::warn!("Reshared {} but could not update the wallet degree: {}", pub_key, err);
+    }
+    HttpResponse::Ok().json(doc!{ "public_key": pub_key, "generation": generation, "degree": new_degree as i32, "holders_count": new_holders_count as i32 })
+}
\ No newline at end of file
@@ -1,46 +1,69 @@
 
-use std::str::FromStr;
+use std::fmt::Display;
 
-use crate::{models::PartialSecret::PartialSecret, database::SecretRepository::SecretRepository};
+use crate::{models::{Holder::ShareCustody, PartialSecret::PartialSecret, SecretCommitment::SecretCommitment}, database::SecretRepository::SecretRepository, services::SecretService::SecretService};
 
-use actix_web::{post, web::{Data}, HttpResponse, HttpRequest};
-use bigdecimal::BigDecimal;
+use actix_web::{web::{Data}, HttpResponse};
 use mongodb::bson::oid::ObjectId;
 
-#[post("/save/{user_id}/{public_key}/{partial_secret}/{degree}")]
-pub async fn save_secret(db: Data<SecretRepository>, req: HttpRequest) -> HttpResponse {
-    let pub_key = req.match_info().get("public_key").unwrap();
-    let user_id = req.match_info().get("user_id").unwrap();
-    let partial_secret = req.match_info().get("partial_secret").unwrap();
-    let secret_degree: u8 = req.match_info().get("partial_secret").unwrap().parse().unwrap();
-    let data = PartialSecret {
-        id: None,
-        user_id: ObjectId::from_str(user_id).unwrap(),
-        partial_secret: partial_secret.to_owned(),
-        public_key: pub_key.to_owned(),
-        secret_degree: secret_degree
-    };
-    let partial_secret_detail = db.save_secret(data).await;
-    match partial_secret_detail {
-        Ok(partial_secret) => HttpResponse::Ok().json(partial_secret),
-        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+/// Stores the commitments and every holder's share of `pub_key`. The i-th share goes into the i-th `custody`: it is
+/// assigned to its holder and sealed to its encryption key first, so the stored document only holds a ciphertext.
+/// An empty `custody` stores unassigned plaintext shares. If the shares can not be stored, the commitments and any share
+/// stored before the failure are deleted again.
+#[allow(clippy::too_many_arguments)]
+pub async fn inner_save_secret<F: Display>(db: Data<SecretRepository>, pub_key: &str, user_id: ObjectId, partial_secret: Vec<Vec<F>>, commitments: Vec<String>, scheme: &str, secret_degree: u8, custody: &[ShareCustody]) -> HttpResponse {
+    if !custody.is_empty() && custody.len() != partial_secret.len() {
+        return HttpResponse::BadRequest().body("One holder or encryption key is needed per share");
     }
-}
-
-pub async fn inner_save_secret(db: Data<SecretRepository>, pub_key: &str, user_id: &str, partial_secret: Vec<Vec<BigDecimal>>, secret_degree: u8) -> HttpResponse {
-    let user_id = ObjectId::from_str(user_id).unwrap();
-    let mapped: Vec<PartialSecret> = partial_secret.iter()
-        .map(|x| PartialSecret {
+    let mut mapped: Vec<PartialSecret> = Vec::new();
+    for (i, x) in partial_secret.iter().enumerate() {
+        let envelope = match SecretService::encodeShare(x, scheme, secret_degree, pub_key) {
+            Ok(envelope) => envelope,
+            Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+        };
+        let custody = custody.get(i).cloned().unwrap_or_default();
+        let partial_secret = match &custody.encryption_key {
+            Some(key) => match SecretService::sealShare(&envelope, key, pub_key) {
+                Ok(ciphertext) => ciphertext,
+                Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+            },
+            None => envelope,
+        };
+        mapped.push(PartialSecret {
             id: None,
-            user_id: user_id,
-            partial_secret: (x[0].to_string() + "||" + x[1].to_string().as_str()).to_string(),
+            user_id,
+            partial_secret,
             public_key: pub_key.to_owned(),
-            secret_degree: secret_degree
-        })
-        .collect();
+            secret_degree,
+            epoch: 0,
+            generation: 0,
+            revoked: false,
+            curve: SecretService::curveOfScheme(scheme).to_owned(),
+            encrypted_to: custody.encryption_key,
+            holder_id: custody.holder_id
+        });
+    }
+    let saved_commitments = db.save_commitments(SecretCommitment {
+        id: None,
+        user_id,
+        public_key: pub_key.to_owned(),
+        commitments,
+        scheme: scheme.to_owned(),
+        secret_degree,
+        epoch: 0,
+        generation: 0
+    }).await;
+    if let Err(err) = saved_commitments {
+        return HttpResponse::InternalServerError().body(err.to_string());
+    }
     let partial_secret_detail = db.save_muliple_secret(mapped).await;
     match partial_secret_detail {
         Ok(partial_secret) => HttpResponse::Ok().json(partial_secret),
-        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+        Err(err) => {
+            if let Err(err) = db.delete_key(pub_key).await {
+                log::error!("Could not roll back {}: {}", pub_key, err);
+            }
+            HttpResponse::InternalServerError().body(err.to_string())
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+use crate::{database::SessionRepository::SessionRepository, services::SessionService::SessionService, util::{cluster::ClusterConfig, session::{nowMillis, Session, SessionError, SessionRouter}}};
+
+use actix_web::{get, post, web::{Data, Json}, HttpResponse, HttpRequest};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct OpenSessionBody {
+    /// Id every participant opens the session under, picked at random when absent.
+    pub session_id: Option<String>,
+    pub protocol: String,
+    /// x-coordinates of the participating nodes, this one included.
+    pub participants: Vec<u8>,
+    pub rounds: u32,
+    /// Milliseconds every round may take.
+    pub round_timeout: u64,
+}
+
+#[derive(Deserialize)]
+pub struct SubmitMessageBody {
+    pub round: u32,
+    /// Hex encoded payload.
+    pub payload: String,
+}
+
+#[derive(Deserialize)]
+pub struct AbortSessionBody {
+    pub reason: String,
+}
+
+/// Opens a protocol session between this node and some of its peers. Whether it survives a restart of the node follows
+/// from its protocol, see `RESUMABLE_PROTOCOLS`.
+#[post("/sessions")]
+pub async fn open_session(cluster: Data<Option<ClusterConfig>>, router: Data<Option<SessionRouter>>, db: Data<SessionRepository>, body: Json<OpenSessionBody>) -> HttpResponse {
+    let cluster = match cluster.get_ref() {
+        Some(cluster) => cluster,
+        None => return HttpResponse::NotFound().body("This node does not run in cluster mode"),
+    };
+    let coordinates = cluster.coordinates();
+    if let Some(x) = body.participants.iter().find(|x| !coordinates.contains(x)) {
+        return HttpResponse::BadRequest().body(format!("No node of the cluster has the x-coordinate {}", x));
+    }
+    let session = match Session::new(body.session_id.clone(), &body.protocol, cluster.x, &body.participants, body.rounds, body.round_timeout, nowMillis()) {
+        Ok(session) => session,
+        Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+    };
+    match SessionService::open(&db, router.get_ref().as_ref(), session).await {
+        Ok(Some(session)) => HttpResponse::Ok().json(session),
+        Ok(None) => HttpResponse::Conflict().body("A session with this id exists"),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+/// Records this node's message for a round of a session and sends it to the other participants.
+#[post("/sessions/{session_id}/messages")]
+pub async fn submit_message(router: Data<Option<SessionRouter>>, db: Data<SessionRepository>, req: HttpRequest, body: Json<SubmitMessageBody>) -> HttpResponse {
+    let router = match router.get_ref() {
+        Some(router) => router,
+        None => return HttpResponse::NotFound().body("This node does not run a node transport"),
+    };
+    let session_id = req.match_info().get("session_id").unwrap();
+    let payload = match hex::decode(&body.payload) {
+        Ok(payload) => payload,
+        Err(_) => return HttpResponse::BadRequest().body("payload must be hex encoded"),
+    };
+    match SessionService::submit(&db, router, session_id, body.round, &payload).await {
+        Ok((session, _)) => HttpResponse::Ok().json(session),
+        Err(SessionError::Unknown(_)) => HttpResponse::NotFound().body("No session with this id"),
+        Err(err @ SessionError::Failed(_)) => HttpResponse::Conflict().body(err.to_string()),
+        Err(err @ SessionError::Transport(_)) => HttpResponse::BadGateway().body(err.to_string()),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+#[get("/sessions/{session_id}")]
+pub async fn get_session(db: Data<SessionRepository>, req: HttpRequest) -> HttpResponse {
+    let session_id = req.match_info().get("session_id").unwrap();
+    match db.find_session(session_id).await {
+        Ok(Some(session)) => HttpResponse::Ok().json(session),
+        Ok(None) => HttpResponse::NotFound().body("No session with this id"),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+/// Stops a running session for good, e.g. because a participant went away. Finished sessions are left as they are.
+#[post("/sessions/{session_id}/abort")]
+pub async fn abort_session(db: Data<SessionRepository>, req: HttpRequest, body: Json<AbortSessionBody>) -> HttpResponse {
+    let session_id = req.match_info().get("session_id").unwrap();
+    match db.find_session(session_id).await {
+        Ok(Some(_)) => (),
+        Ok(None) => return HttpResponse::NotFound().body("No session with this id"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    match SessionService::abort(&db, session_id, &body.reason).await {
+        Ok(session) => HttpResponse::Ok().json(session),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+use crate::{database::{HolderRepository::HolderRepository, SecretRepository::SecretRepository}, services::SecretService::SecretService, util::{cluster::ClusterConfig, pedersen::PedersenVSS, session::SessionRouter}, views::{Cluster::inner_deal_secret, Holder::assign_custody}};
+
+use actix_web::{post, web::{Data, Json}, HttpResponse, HttpRequest};
+use mongodb::bson::oid::ObjectId;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ShareSecretBody {
+    /// Hex encoded secret, sent in the body so it never shows up in access logs.
+    pub secret: String,
+    /// Hex X25519 keys to seal the holders' shares to, one per holder. Shares are stored in plaintext without them.
+    #[serde(default)]
+    pub encryption_keys: Vec<String>,
+    /// Ids of registered holders to deal the shares to, one per holder.
+    #[serde(default)]
+    pub holders: Vec<String>,
+    /// Seal the shares of registered `holders` to their encryption keys.
+    #[serde(default)]
+    pub seal: bool,
+}
+
+/// Splits a generic secret with Pedersen VSS. The shares are stored under the hex encoded first commitment,
+/// which is returned so holders can later verify their `x||y||r` share.
+#[post("/share_secret/{user_id}/{degree}/{holders_count}")]
+pub async fn share_secret(db: Data<SecretRepository>, holder_db: Data<HolderRepository>, cluster: Data<Option<ClusterConfig>>, router: Data<Option<SessionRouter>>, req: HttpRequest, body: Json<ShareSecretBody>) -> HttpResponse {
+    let user_id = match ObjectId::from_str(req.match_info().get("user_id").unwrap()) {
+        Ok(user_id) => user_id,
+        Err(_) => return HttpResponse::BadRequest().body("user_id must be an object id"),
+    };
+    let degree: u8 = match req.match_info().get("degree").unwrap().parse() {
+        Ok(degree) => degree,
+        Err(_) => return HttpResponse::BadRequest().body("degree must be a number between 2 and 254"),
+    };
+    let holders_count: u8 = match req.match_info().get("holders_count").unwrap().parse() {
+        Ok(holders_count) => holders_count,
+        Err(_) => return HttpResponse::BadRequest().body("holders_count must be a number between 3 and 255"),
+    };
+    if degree < 2 || holders_count <= degree {
+        return HttpResponse::BadRequest().body("Holders count must be greater than the degree, and the degree at least 2");
+    }
+
+    let (xs, custody) = match cluster.get_ref() {
+        Some(cluster) if cluster.coordinates().len() != holders_count as usize => return HttpResponse::BadRequest().body("In cluster mode there is one holder per node"),
+        Some(_) if !body.holders.is_empty() || !body.encryption_keys.is_empty() => return HttpResponse::BadRequest().body("In cluster mode every node holds its own share, holders and encryption keys do not apply"),
+        Some(cluster) => (cluster.coordinates(), Vec::new()),
+        None => match assign_custody(&holder_db, holders_count, &body.holders, &body.encryption_keys, body.seal).await {
+            Ok(assignment) => assignment,
+            Err(response) => return response,
+        },
+    };
+
+    let (partitions, commitments) = match SecretService::pedersenSecretPartition(degree, body.secret.clone(), &xs) {
+        Ok(result) => result,
+        Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+    };
+    let key_id = commitments[0].clone();
+    let response = inner_deal_secret(cluster.get_ref().as_ref(), router.get_ref().as_ref(), db, key_id.as_str(), user_id, partitions, commitments, PedersenVSS::SCHEME, degree, &custody).await;
+    if response.status().is_success() {
+        HttpResponse::Ok().json(key_id)
+    } else {
+        response
+    }
+}
\ No newline at end of file
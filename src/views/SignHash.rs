@@ -0,0 +1,184 @@
+use std::str::FromStr;
+
+use crate::{database::{SecretRepository::SecretRepository, UserRepository::UserRepository}, models::SecretCommitment::SecretCommitment, services::SecretService::SecretService, util::{bls::{BlsFeldmanVSS, BlsScalar}, cluster::ClusterConfig, ecdsa::ThresholdEcdsa, ed25519::{Ed25519FeldmanVSS, Ed25519Scalar}, feldman::FeldmanVSS, field::FieldElement}, views::{Cluster::standalone_only, HolderEnvelope::server_shares}};
+
+use actix_web::{post, web::{Data, Json}, HttpResponse, HttpRequest};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct SignHashBody {
+    /// Hex encoded 32 byte message hash, keccak-256 for Ethereum or double SHA-256 for Bitcoin.
+    pub hash: String,
+}
+
+#[derive(Deserialize)]
+pub struct SignMessageBody {
+    /// Hex encoded message. Ed25519 and BLS hash the message themselves, so it is sent whole.
+    pub message: String,
+}
+
+fn parseHash(hash: &str) -> Option<[u8; 32]> {
+    hex::decode(hash.trim_start_matches("0x")).ok().and_then(|bytes| bytes.try_into().ok())
+}
+
+fn parseMessage(message: &str) -> Result<Vec<u8>, HttpResponse> {
+    hex::decode(message.trim_start_matches("0x")).map_err(|_| HttpResponse::BadRequest().body("message must be hex encoded"))
+}
+
+/// The key behind `public_key` as the signing handlers need it: its degree, the holders' shares and its commitments.
+struct SigningKey<F> {
+    degree: u8,
+    shares: Vec<Vec<F>>,
+    commitments: SecretCommitment,
+}
+
+/// Loads the shares and commitments of the key behind `public_key`, which must be shared with `scheme`, or the response
+/// to give when it can not sign: 409 in cluster mode or for sealed shares, 404 for an unknown key and 400 for another scheme.
+async fn signing_key<F: FromStr>(cluster: &Option<ClusterConfig>, db: &SecretRepository, public_key: &str, scheme: &str) -> Result<SigningKey<F>, HttpResponse> {
+    standalone_only(cluster)?;
+    let secrets = match db.find_secrets(public_key).await {
+        Ok(secrets) if !secrets.is_empty() => secrets,
+        Ok(_) => return Err(HttpResponse::NotFound().body("No partial secrets stored for this public key")),
+        Err(err) => return Err(HttpResponse::InternalServerError().body(err.to_string())),
+    };
+    let commitments = match db.find_commitments(public_key).await {
+        Ok(Some(commitments)) if commitments.scheme == scheme => commitments,
+        Ok(Some(commitments)) => return Err(HttpResponse::BadRequest().body(format!("The key is shared with {}, signing needs {}", commitments.scheme, scheme))),
+        Ok(None) => return Err(HttpResponse::NotFound().body("No commitments stored for this public key")),
+        Err(err) => return Err(HttpResponse::InternalServerError().body(err.to_string())),
+    };
+    let shares = server_shares(&secrets, scheme)?;
+    Ok(SigningKey { degree: secrets[0].secret_degree, shares, commitments })
+}
+
+/// Refuses a wallet at `derivation_path` that the handler's protocol does not sign for: `/sign` takes the keys that sign
+/// with threshold ECDSA, `/sign_taproot` (`taproot`) only Taproot wallets.
+fn check_protocol(scheme: &str, derivation_path: Option<&str>, taproot: bool) -> Result<(), HttpResponse> {
+    if taproot && !SecretService::isTaproot(derivation_path) {
+        return Err(HttpResponse::BadRequest().body("Only Taproot wallets sign with /sign_taproot, sign this key with /sign"));
+    }
+    if !taproot && !SecretService::signsWithEcdsa(scheme, derivation_path) {
+        return Err(HttpResponse::BadRequest().body("Taproot wallets do not sign with ECDSA, sign this key with /sign_taproot"));
+    }
+    Ok(())
+}
+
+/// Like `signing_key` for a secp256k1 wallet that signs with the handler's protocol, see `check_protocol`. The user's
+/// seed key is refused, only the wallets derived from it sign.
+async fn wallet_key(cluster: &Option<ClusterConfig>, db: &SecretRepository, users: &UserRepository, public_key: &str, taproot: bool) -> Result<SigningKey<FieldElement>, HttpResponse> {
+    let key = signing_key(cluster, db, public_key, FeldmanVSS::SCHEME).await?;
+    let user = match users.find_user(key.commitments.user_id).await {
+        Ok(Some(user)) if user.seed == public_key => return Err(HttpResponse::BadRequest().body("The seed key does not sign, sign with a wallet derived from it")),
+        Ok(user) => user,
+        Err(err) => return Err(HttpResponse::InternalServerError().body(err.to_string())),
+    };
+    let derivation_path = user.as_ref()
+        .and_then(|user| user.wallets.iter().find(|wallet| wallet.pub_key == public_key))
+        .and_then(|wallet| wallet.derivation_path.as_deref());
+    check_protocol(&key.commitments.scheme, derivation_path, taproot)?;
+    Ok(key)
+}
+
+/// Signs a message hash with the wallet key behind `public_key` by threshold ECDSA between its holders, 400 for a
+/// Taproot wallet. Returns `{r, s, v}`; the key is never reconstructed. Every one of the at least `2 * degree + 1`
+/// holders takes part.
+#[post("/sign/{public_key}")]
+pub async fn sign_hash(cluster: Data<Option<ClusterConfig>>, db: Data<SecretRepository>, users: Data<UserRepository>, req: HttpRequest, body: Json<SignHashBody>) -> HttpResponse {
+    let hash: [u8; 32] = match parseHash(&body.hash) {
+        Some(hash) => hash,
+        None => return HttpResponse::BadRequest().body("hash must be 32 hex encoded bytes"),
+    };
+    let key = match wallet_key(&cluster, &db, &users, req.match_info().get("public_key").unwrap(), false).await {
+        Ok(key) => key,
+        Err(response) => return response,
+    };
+    if key.shares.len() < ThresholdEcdsa::requiredHolders(key.degree) {
+        return HttpResponse::Conflict().body(format!("Threshold ECDSA needs at least 2 * degree + 1 = {} holders, this key has {}", ThresholdEcdsa::requiredHolders(key.degree), key.shares.len()));
+    }
+
+    match SecretService::signHash(key.degree, key.shares, &key.commitments.commitments, &hash) {
+        Ok(signature) => HttpResponse::Ok().json(signature),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+/// Signs a Taproot sighash with the Bitcoin wallet key behind the x-only `public_key` by FROST between its holders.
+/// Returns the hex encoded 64 byte BIP-340 signature for the key-path spend, 400 for a wallet that is not a Taproot one.
+#[post("/sign_taproot/{public_key}")]
+pub async fn sign_taproot(cluster: Data<Option<ClusterConfig>>, db: Data<SecretRepository>, users: Data<UserRepository>, req: HttpRequest, body: Json<SignHashBody>) -> HttpResponse {
+    let sighash = match parseHash(&body.hash) {
+        Some(sighash) => sighash,
+        None => return HttpResponse::BadRequest().body("hash must be 32 hex encoded bytes"),
+    };
+    let key = match wallet_key(&cluster, &db, &users, req.match_info().get("public_key").unwrap(), true).await {
+        Ok(key) => key,
+        Err(response) => return response,
+    };
+
+    match SecretService::signTaproot(key.degree, key.shares, &key.commitments.commitments, &sighash) {
+        Ok(signature) => HttpResponse::Ok().json(signature),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+/// Signs a message with the Ed25519 wallet key behind `public_key` by FROST between its holders.
+/// Returns the hex encoded 64 byte Ed25519 signature.
+#[post("/sign_ed25519/{public_key}")]
+pub async fn sign_ed25519(cluster: Data<Option<ClusterConfig>>, db: Data<SecretRepository>, req: HttpRequest, body: Json<SignMessageBody>) -> HttpResponse {
+    let message = match parseMessage(&body.message) {
+        Ok(message) => message,
+        Err(response) => return response,
+    };
+    let key = match signing_key::<Ed25519Scalar>(&cluster, &db, req.match_info().get("public_key").unwrap(), Ed25519FeldmanVSS::SCHEME).await {
+        Ok(key) => key,
+        Err(response) => return response,
+    };
+
+    match SecretService::signEd25519(key.degree, key.shares, &key.commitments.commitments, &message) {
+        Ok(signature) => HttpResponse::Ok().json(signature),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+/// Signs a message with the BLS wallet key behind `public_key`. Every holder signs on its own and the partial signatures
+/// are combined, so there is a single round. Returns the hex encoded compressed G2 signature.
+#[post("/sign_bls/{public_key}")]
+pub async fn sign_bls(cluster: Data<Option<ClusterConfig>>, db: Data<SecretRepository>, req: HttpRequest, body: Json<SignMessageBody>) -> HttpResponse {
+    let message = match parseMessage(&body.message) {
+        Ok(message) => message,
+        Err(response) => return response,
+    };
+    let key = match signing_key::<BlsScalar>(&cluster, &db, req.match_info().get("public_key").unwrap(), BlsFeldmanVSS::SCHEME).await {
+        Ok(key) => key,
+        Err(response) => return response,
+    };
+
+    match SecretService::signBls(key.degree, key.shares, &key.commitments.commitments, &message).await {
+        Ok(signature) => HttpResponse::Ok().json(signature),
+        Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::StatusCode;
+
+    use super::*;
+    use crate::util::bip32::DerivationPath;
+
+    fn status(result: Result<(), HttpResponse>) -> Option<StatusCode> {
+        result.err().map(|response| response.status())
+    }
+
+    #[test]
+    fn test_wallets_sign_with_their_own_protocol_only() {
+        let (ethereum, taproot) = (Some(DerivationPath::ETHEREUM), Some(DerivationPath::TAPROOT));
+        assert_eq!(status(check_protocol(FeldmanVSS::SCHEME, ethereum, false)), None);
+        assert_eq!(status(check_protocol(FeldmanVSS::SCHEME, taproot, true)), None);
+        // A Taproot wallet is refused by /sign and an ECDSA wallet by /sign_taproot
+        assert_eq!(status(check_protocol(FeldmanVSS::SCHEME, taproot, false)), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(status(check_protocol(FeldmanVSS::SCHEME, ethereum, true)), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(status(check_protocol(FeldmanVSS::SCHEME, None, true)), Some(StatusCode::BAD_REQUEST));
+        assert_eq!(status(check_protocol(Ed25519FeldmanVSS::SCHEME, None, false)), Some(StatusCode::BAD_REQUEST));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::services::SecretService::SecretService;
+
+use actix_web::{post, web::Json, HttpResponse};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct Slip39Group {
+    pub threshold: u8,
+    pub count: u8,
+}
+
+#[derive(Deserialize)]
+pub struct Slip39SplitBody {
+    /// Hex encoded master secret, at least 16 bytes and of even length.
+    pub secret: String,
+    #[serde(default)]
+    pub passphrase: String,
+    pub group_threshold: u8,
+    pub groups: Vec<Slip39Group>,
+}
+
+#[derive(Deserialize)]
+pub struct Slip39CombineBody {
+    pub mnemonics: Vec<String>,
+    #[serde(default)]
+    pub passphrase: String,
+}
+
+/// Splits a master secret into SLIP-0039 mnemonic shares holders can keep on paper. Nothing is stored, the mnemonics
+/// of every group are returned in group order.
+#[post("/slip39/split")]
+pub async fn slip39_split(body: Json<Slip39SplitBody>) -> HttpResponse {
+    let secret = match hex::decode(body.secret.trim_start_matches("0x")) {
+        Ok(secret) => secret,
+        Err(_) => return HttpResponse::BadRequest().body("secret must be hex encoded"),
+    };
+    let groups: Vec<(u8, u8)> = body.groups.iter().map(|group| (group.threshold, group.count)).collect();
+    match SecretService::slip39Split(body.group_threshold, &groups, &secret, &body.passphrase) {
+        Ok(mnemonics) => HttpResponse::Ok().json(mnemonics),
+        Err(err) => HttpResponse::BadRequest().body(err.to_string()),
+    }
+}
+
+/// Combines SLIP-0039 mnemonic shares and returns the hex encoded master secret.
+#[post("/slip39/combine")]
+pub async fn slip39_combine(body: Json<Slip39CombineBody>) -> HttpResponse {
+    match SecretService::slip39Combine(&body.mnemonics, &body.passphrase) {
+        Ok(secret) => HttpResponse::Ok().json(hex::encode(secret)),
+        Err(err) => HttpResponse::UnprocessableEntity().body(err.to_string()),
+    }
+}
\ No newline at end of file
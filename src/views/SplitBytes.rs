@@ -0,0 +1,48 @@
+use crate::services::SecretService::SecretService;
+
+use actix_web::{post, web::Json, HttpResponse, HttpRequest};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct SplitBytesBody {
+    /// Hex encoded secret of any length: a seed phrase, an API token, a key file.
+    pub secret: String,
+}
+
+#[derive(Deserialize)]
+pub struct CombineBytesBody {
+    /// Hex encoded shares, each the y bytes followed by the x-coordinate byte.
+    pub shares: Vec<String>,
+}
+
+/// Splits an arbitrary secret byte-wise over GF(2^8) into `holders_count` shares in HashiCorp Vault's layout.
+/// Nothing is stored, the shares are returned to be handed out.
+#[post("/split_bytes/{degree}/{holders_count}")]
+pub async fn split_bytes(req: HttpRequest, body: Json<SplitBytesBody>) -> HttpResponse {
+    let degree: u8 = match req.match_info().get("degree").unwrap().parse() {
+        Ok(degree) if degree >= 1 => degree,
+        _ => return HttpResponse::BadRequest().body("degree must be a number between 1 and 254"),
+    };
+    let holders_count: u8 = match req.match_info().get("holders_count").unwrap().parse() {
+        Ok(holders_count) => holders_count,
+        Err(_) => return HttpResponse::BadRequest().body("holders_count must be a number between 1 and 255"),
+    };
+    let secret = match hex::decode(body.secret.trim_start_matches("0x")) {
+        Ok(secret) => secret,
+        Err(_) => return HttpResponse::BadRequest().body("secret must be hex encoded"),
+    };
+    match SecretService::splitBytes(degree, &secret, holders_count) {
+        Ok(shares) => HttpResponse::Ok().json(shares),
+        Err(err) => HttpResponse::BadRequest().body(err.to_string()),
+    }
+}
+
+/// Recombines shares made by `split_bytes` or by Vault and returns the hex encoded secret. Like Vault, every given share
+/// is interpolated, so passing fewer shares than the threshold yields a wrong secret rather than an error.
+#[post("/combine_bytes")]
+pub async fn combine_bytes(body: Json<CombineBytesBody>) -> HttpResponse {
+    match SecretService::combineBytes(&body.shares) {
+        Ok(secret) => HttpResponse::Ok().json(hex::encode(secret)),
+        Err(err) => HttpResponse::UnprocessableEntity().body(err.to_string()),
+    }
+}
\ No newline at end of file
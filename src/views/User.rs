@@ -1,29 +1,186 @@
-use crate::{models::User::{User, Wallet}, database::{UserRepository::UserRepository, SecretRepository::SecretRepository}, services::{WalletService::WalletService, SecretService}, views::SaveSecret::inner_save_secret};
+use crate::{models::{Holder::ShareCustody, User::{User, Wallet}}, database::{HolderRepository::HolderRepository, UserRepository::UserRepository, SecretRepository::SecretRepository}, services::{ClusterService::{ClusterService, ClusterTask, Outcome}, SecretService::{SecretService, UserKeys}}, util::{ecdsa::ThresholdEcdsa, bls::{self, BlsFeldmanVSS}, ed25519::{self, Ed25519FeldmanVSS}, cluster::ClusterConfig, feldman::FeldmanVSS, session::SessionRouter}, views::{Cluster::cluster_router, Holder::assign_custody, SaveSecret::inner_save_secret}};
 
-use actix_web::{post, web::{Data}, HttpResponse, HttpRequest};
+use actix_web::{post, web::{Data, Json}, HttpResponse, HttpRequest};
+use mongodb::bson::{oid::ObjectId, Bson};
+use serde::Deserialize;
 
+#[derive(Deserialize, Default)]
+pub struct CreateUserBody {
+    /// Hex X25519 keys of the holders, one per holder. Every share is sealed to its holder before it is stored.
+    #[serde(default)]
+    pub encryption_keys: Vec<String>,
+    /// Ids of registered holders, one per holder. Every key is dealt at the holders' x-coordinates and assigned to them.
+    #[serde(default)]
+    pub holders: Vec<String>,
+    /// Seal the shares of registered `holders` to their encryption keys.
+    #[serde(default)]
+    pub seal: bool,
+    /// Generate the seed key jointly instead of dealing a BIP-39 seed, so the server never sees the seed or a wallet key.
+    /// The wallets are then at the non-hardened paths `m/44/60/0/0/0`, `m/84/0/0/0/0` and `m/86/0/0/0/0` below a seed
+    /// with no mnemonic, which no standard wallet restores. Required in cluster mode.
+    #[serde(default)]
+    pub joint_seed: bool,
+}
+
+/// Creates a user with a BIP-39 seed dealt to `holders_count` holders. The Ethereum, Bitcoin SegWit and Taproot wallets
+/// are at the first hardened BIP-44, BIP-84 and BIP-86 accounts of the seed and dealt to the same holders, while the
+/// Ed25519 and BLS keys come out of distributed key generations of their own. With `joint_seed` the seed key is
+/// generated jointly instead and no key is ever in one place, see `CreateUserBody`. The body is optional, without
+/// holders the shares sit at random x-coordinates, and without encryption keys they are stored in plaintext.
+///
+/// The Ethereum and SegWit wallets sign with threshold ECDSA, which needs `2 * degree + 1` holders rather than
+/// `degree + 1`, so users with fewer holders are refused.
+///
+/// In cluster mode the seed can only be generated jointly. Every node is a holder and runs its own part of the key
+/// generations. This node asks every peer to
+/// take part, stores its own shares and the user, and keeps them only once every peer reported the same; otherwise every
+/// node rolls its part back.
 #[post("/create_user/{degree}/{holders_count}")]
-pub async fn create_user(db: Data<UserRepository>, db2: Data<SecretRepository>, req: HttpRequest) -> HttpResponse {
-    let eth_wallet = WalletService::createEthWallet();
-    let btc_wallet = WalletService::createBitcoinWallet();
+pub async fn create_user(db: Data<UserRepository>, db2: Data<SecretRepository>, db3: Data<HolderRepository>, cluster: Data<Option<ClusterConfig>>, router: Data<Option<SessionRouter>>, req: HttpRequest, body: Option<Json<CreateUserBody>>) -> HttpResponse {
+    let degree: u8 = match req.match_info().get("degree").unwrap().parse() {
+        Ok(degree) => degree,
+        Err(_) => return HttpResponse::BadRequest().body("degree must be a number between 2 and 254"),
+    };
+    let holders_count: u8 = match req.match_info().get("holders_count").unwrap().parse() {
+        Ok(holders_count) => holders_count,
+        Err(_) => return HttpResponse::BadRequest().body("holders_count must be a number between 3 and 255"),
+    };
+    if degree < 2 || holders_count <= degree {
+        return HttpResponse::BadRequest().body("Holders count must be greater than the degree, and the degree at least 2");
+    }
+    if (holders_count as usize) < ThresholdEcdsa::requiredHolders(degree) {
+        return HttpResponse::BadRequest().body(format!("The ECDSA wallets need an honest majority: at least 2 * degree + 1 = {} holders", ThresholdEcdsa::requiredHolders(degree)));
+    }
+    let body = body.map(|body| body.into_inner()).unwrap_or_default();
+    let cluster = match cluster.get_ref() {
+        Some(cluster) if cluster.coordinates().len() != holders_count as usize => return HttpResponse::BadRequest().body("In cluster mode there is one holder per node"),
+        Some(_) if !body.holders.is_empty() || !body.encryption_keys.is_empty() => return HttpResponse::BadRequest().body("In cluster mode every node holds its own share, holders and encryption keys do not apply"),
+        Some(_) if !body.joint_seed => return HttpResponse::BadRequest().body("In cluster mode the seed can only be generated jointly, a dealt BIP-39 seed would pass through one node. Ask for a joint_seed"),
+        Some(cluster) => cluster,
+        None => {
+            let (xs, custody) = match assign_custody(&db3, holders_count, &body.holders, &body.encryption_keys, body.seal).await {
+                Ok(assignment) => assignment,
+                Err(response) => return response,
+            };
+            let keys = match SecretService::userKeyGeneration(degree, &xs, body.joint_seed).await {
+                Ok(keys) => keys,
+                Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+            };
+            let encryption_keys: Vec<String> = custody.iter().filter_map(|custody| custody.encryption_key.clone()).collect();
+            return match store_user_keys(&db, &db2, ObjectId::new(), degree, keys, &custody, encryption_keys).await {
+                Ok(user_id) => HttpResponse::Ok().json(user_id),
+                Err(response) => response,
+            };
+        }
+    };
+    let router = match cluster_router(router.get_ref().as_ref()) {
+        Ok(router) => router,
+        Err(response) => return response,
+    };
 
-    let degree: u8 = req.match_info().get("degree").unwrap().parse().unwrap();
-    let holders_count: u8 = req.match_info().get("holders_count").unwrap().parse().unwrap();
-    assert!(!(holders_count < degree + 1), "Number of holders {} must be greater than or equal to the degree of the polynomial plus one: {} + 1 => {}", holders_count, degree, (degree+1));
+    let user_id = ObjectId::new();
+    let session_id = format!("create_user/{}", user_id.to_hex());
+    let requests = cluster.peers.iter()
+        .map(|peer| (peer.x, ClusterTask::CreateUser { user_id: user_id.to_hex(), degree }))
+        .collect();
+    let mut reports = match ClusterService::ask(router, &session_id, requests).await {
+        Ok(reports) => reports,
+        Err(err) => return HttpResponse::BadGateway().body(err.to_string()),
+    };
+    let stored = match ClusterService::userKeyGeneration(router, cluster, &session_id, degree).await {
+        Ok(keys) => store_user_keys(&db, &db2, user_id, degree, keys, &[], Vec::new()).await,
+        Err(err) => Err(HttpResponse::BadGateway().body(err.to_string())),
+    };
+    let reported = ClusterService::awaitReports(&mut reports).await;
+    let decision: Outcome = match (&stored, &reported) {
+        (Ok(_), Ok(())) => Ok(()),
+        (Err(response), _) => Err(format!("node {} failed with {}", cluster.x, response.status())),
+        (Ok(_), Err(err)) => Err(err.to_string()),
+    };
+    if let Err(err) = ClusterService::decide(&reports, &decision).await {
+        log::warn!("Could not tell every node the decision on user {}: {}", user_id, err);
+    }
+    match (stored, reported) {
+        (Ok(user_id), Ok(())) => HttpResponse::Ok().json(user_id),
+        (Err(response), _) => response,
+        (Ok(_), Err(err)) => {
+            rollback_user(&db, &db2, user_id).await;
+            HttpResponse::BadGateway().body(err.to_string())
+        }
+    }
+}
 
-    let data = User {
-        id: None,
-        wallets: vec![Wallet::new(eth_wallet[0].clone(), degree), Wallet::new(btc_wallet[0].clone(), degree)]
+/// Stores every key of `keys` with the i-th share in the i-th `custody`, see `inner_save_secret`, and then the user under
+/// `user_id`. Everything stored is deleted again as soon as one step fails, the failed step's response is returned then.
+pub async fn store_user_keys(db: &UserRepository, db2: &Data<SecretRepository>, user_id: ObjectId, degree: u8, keys: UserKeys, custody: &[ShareCustody], encryption_keys: Vec<String>) -> Result<Bson, HttpResponse> {
+    let mut wallets: Vec<Wallet> = keys.wallets.iter()
+        .map(|derived| Wallet::derived(derived.key.public_key.clone(), degree, &derived.derivation_path, &derived.chain_code, &derived.xpub))
+        .collect();
+    wallets.push(Wallet::new(keys.ed25519.public_key.clone(), degree, ed25519::CURVE));
+    wallets.push(Wallet::new(keys.bls.public_key.clone(), degree, bls::CURVE));
+    let user = User {
+        id: Some(user_id),
+        seed: keys.seed.public_key.clone(),
+        seed_xpub: keys.seed_xpub,
+        wallets,
+        encryption_keys
     };
 
-    let user_detail = db.create_user(data.clone()).await;
+    let mut responses = vec![inner_save_secret(db2.clone(), &keys.seed.public_key, user_id, keys.seed.shares, keys.seed.commitments, FeldmanVSS::SCHEME, degree, custody).await];
+    for derived in keys.wallets {
+        responses.push(inner_save_secret(db2.clone(), &derived.key.public_key, user_id, derived.key.shares, derived.key.commitments, FeldmanVSS::SCHEME, degree, custody).await);
+    }
+    responses.push(inner_save_secret(db2.clone(), &keys.ed25519.public_key, user_id, keys.ed25519.shares, keys.ed25519.commitments, Ed25519FeldmanVSS::SCHEME, degree, custody).await);
+    responses.push(inner_save_secret(db2.clone(), &keys.bls.public_key, user_id, keys.bls.shares, keys.bls.commitments, BlsFeldmanVSS::SCHEME, degree, custody).await);
+    if let Some(failed) = responses.into_iter().find(|response| !response.status().is_success()) {
+        rollback_user(db, db2, user_id).await;
+        return Err(failed);
+    }
+    match db.create_user(user).await {
+        Ok(inserted) => Ok(inserted),
+        Err(err) => {
+            rollback_user(db, db2, user_id).await;
+            Err(HttpResponse::InternalServerError().body(err.to_string()))
+        }
+    }
+}
+
+/// Deletes the user `user_id` and every share and commitment of its keys, after its creation failed.
+pub async fn rollback_user(db: &UserRepository, db2: &SecretRepository, user_id: ObjectId) {
+    if let Err(err) = db2.delete_user_keys(user_id).await {
+        log::error!("Could not delete the keys of user {}: {}", user_id, err);
+    }
+    if let Err(err) = db.delete_user(user_id).await {
+        log::error!("Could not delete user {}: {}", user_id, err);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::MongoConnection::MongoConnection;
+    use mongodb::{bson::{doc, Document}, options::CreateCollectionOptions};
+
+    #[actix_web::test]
+    #[ignore = "needs a MongoDB at MONGO_HOST"]
+    async fn test_failed_save_leaves_nothing_stored() {
+        let db = MongoConnection::client().await.database("RustDBRollbackTest");
+        db.drop(None).await.unwrap();
+        // Every share but the BLS ones is stored, so the user is rolled back after most of its keys made it in
+        let reject_bls = CreateCollectionOptions::builder().validator(doc!{ "curve": { "$ne": bls::CURVE } }).build();
+        db.create_collection("PartialSecrets", reject_bls).await.unwrap();
+        let users = UserRepository::init(&db).await;
+        let secrets = Data::new(SecretRepository::init(&db).await);
+
+        let user_id = ObjectId::new();
+        let keys = SecretService::userKeyGeneration(2, &[1, 2, 3, 4, 5], false).await.unwrap();
+        let stored = store_user_keys(&users, &secrets, user_id, 2, keys, &[], Vec::new()).await;
+        assert!(stored.is_err());
 
-    match user_detail {
-        Ok(user_detail) => {
-            let partitions = SecretService::SecretService::secretPartition(data.wallets[0].degree, eth_wallet[1].clone(), holders_count);
-            inner_save_secret(db2, data.wallets[0].pub_key.as_str(), user_detail.as_object_id().unwrap().to_hex().as_str(), partitions, data.to_owned().wallets[0].degree).await;
-            HttpResponse::Ok().json(user_detail)
-        },
-        Err(err) => HttpResponse::InternalServerError().body(err.to_string())
+        assert!(users.find_user(user_id).await.unwrap().is_none());
+        for collection in ["PartialSecrets", "SecretCommitments"] {
+            let left = db.collection::<Document>(collection).count_documents(doc!{ "user_id": user_id }, None).await.unwrap();
+            assert_eq!(left, 0, "{} of the user are left", collection);
+        }
+        db.drop(None).await.unwrap();
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,49 @@
+use crate::{database::{SecretRepository::SecretRepository, UserRepository::UserRepository}, services::SecretService::SecretService, util::{bls::{BlsFeldmanVSS, BlsScalar}, ed25519::{Ed25519FeldmanVSS, Ed25519Scalar}}};
+
+use actix_web::{post, web::{Data, Json}, HttpResponse, HttpRequest};
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct VerifySecretBody {
+    /// The share as a share envelope or in the `x||y` (Feldman) or `x||y||r` (Pedersen) form.
+    pub partial_secret: String,
+}
+
+/// Lets a holder check their share against the commitments stored for `public_key`. The share travels in the body so
+/// it does not end up in URLs and access logs.
+#[post("/verify/{public_key}")]
+pub async fn verify_secret(db: Data<SecretRepository>, users: Data<UserRepository>, req: HttpRequest, body: Json<VerifySecretBody>) -> HttpResponse {
+    let pub_key = req.match_info().get("public_key").unwrap();
+    let partial_secret = body.partial_secret.as_str();
+    let commitments = match db.find_commitments(pub_key).await {
+        Ok(Some(commitments)) => commitments,
+        Ok(None) => return HttpResponse::NotFound().body("No commitments stored for this public key"),
+        Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+    };
+    let threshold = commitments.secret_degree + 1;
+    let verification = if commitments.scheme == Ed25519FeldmanVSS::SCHEME {
+        match SecretService::parseShareOf::<Ed25519Scalar>(partial_secret, &commitments.scheme, threshold, pub_key) {
+            Ok(share) => SecretService::verifyEd25519Share(&share, &commitments.commitments, pub_key),
+            Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+        }
+    } else if commitments.scheme == BlsFeldmanVSS::SCHEME {
+        match SecretService::parseShareOf::<BlsScalar>(partial_secret, &commitments.scheme, threshold, pub_key) {
+            Ok(share) => SecretService::verifyBlsShare(&share, &commitments.commitments, pub_key),
+            Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+        }
+    } else {
+        let wallet = match users.find_wallet(pub_key).await {
+            Ok(wallet) => wallet,
+            Err(err) => return HttpResponse::InternalServerError().body(err.to_string()),
+        };
+        let derivation_path = wallet.as_ref().and_then(|wallet| wallet.derivation_path.as_deref());
+        match SecretService::parseShareOf(partial_secret, &commitments.scheme, threshold, pub_key) {
+            Ok(share) => SecretService::verifyShare(&share, &commitments.commitments, &commitments.scheme, pub_key, derivation_path),
+            Err(err) => return HttpResponse::BadRequest().body(err.to_string()),
+        }
+    };
+    match verification {
+        Some(verification) => HttpResponse::Ok().json(verification),
+        None => HttpResponse::InternalServerError().body("Stored commitments or public key are not valid curve points"),
+    }
+}
\ No newline at end of file
@@ -1,3 +1,19 @@
 pub mod Default;
 pub mod SaveSecret;
-pub mod User;
\ No newline at end of file
+pub mod User;
+pub mod VerifySecret;
+pub mod ShareSecret;
+pub mod RefreshSecret;
+pub mod ReshareSecret;
+pub mod RepairSecret;
+pub mod SignHash;
+pub mod RecoverSecret;
+pub mod SplitBytes;
+pub mod Slip39;
+pub mod DeriveWallet;
+pub mod DeriveChild;
+pub mod HolderEnvelope;
+pub mod Holder;
+pub mod Cluster;
+pub mod Session;
+pub mod Migration;
\ No newline at end of file